@@ -31,10 +31,10 @@
 use bevy::prelude::*;
 use bevy::utils::{HashMap, Uuid};
 use bevy::window::PrimaryWindow;
-use bevy_math::{DQuat, DVec3};
 use render::{DrawDataHandles, TransformGizmoRenderPlugin};
 use transform_gizmo::config::{
-    TransformPivotPoint, DEFAULT_SNAP_ANGLE, DEFAULT_SNAP_DISTANCE, DEFAULT_SNAP_SCALE,
+    RotationInput, SurfacePlacement, TransformPivotPoint, DEFAULT_PRECISION_FACTOR,
+    DEFAULT_SNAP_POINT_DISTANCE,
 };
 
 pub use transform_gizmo::{
@@ -64,27 +64,45 @@ impl Plugin for TransformGizmoPlugin {
     }
 }
 
+/// Keys that constrain a drag to an axis, see [`GizmoInteraction::axis_key`].
+const AXIS_KEYS: [(KeyCode, GizmoDirection); 3] = [
+    (KeyCode::KeyX, GizmoDirection::X),
+    (KeyCode::KeyY, GizmoDirection::Y),
+    (KeyCode::KeyZ, GizmoDirection::Z),
+];
+
 /// Various options for configuring the transform gizmos.
+///
+/// The gizmos are interacted with the left mouse button. `Ctrl` inverts [`Self::snapping`],
+/// `Shift` slows the drag down to [`Self::precision_factor`] and `Alt` duplicates the targets.
+/// `X`, `Y` and `Z` constrain a drag to an axis, or exclude the axis with `Shift`.
+/// While an axis key is held, `Shift` excludes the axis and does not enable precision.
 #[derive(Resource, Copy, Clone, Debug)]
 pub struct GizmoOptions {
     /// Modes to use in the gizmos.
     pub gizmo_modes: EnumSet<GizmoMode>,
     /// Orientation of the gizmo. This affects the behaviour of transformations.
     pub gizmo_orientation: GizmoOrientation,
+    /// Rotation order of the targets' Euler angles, used by [`GizmoOrientation::Gimbal`].
+    pub euler_order: EulerOrder,
     /// Orientation of the gizmo. This affects the behaviour of transformations.
     pub pivot_point: TransformPivotPoint,
+    /// Determines how cursor movement is converted into rotation angles.
+    pub rotation_input: RotationInput,
     /// Look and feel of the gizmo.
     pub visuals: GizmoVisuals,
     /// Controls the visibility of the sub gizmos
     pub gizmo_visibility: GizmoVisibility,
     /// Whether snapping is enabled in the gizmo transformations.
     pub snapping: bool,
-    /// Angle increment for snapping rotations, in radians.
-    pub snap_angle: f32,
-    /// Distance increment for snapping translations.
-    pub snap_distance: f32,
-    /// Scale increment for snapping scalings.
-    pub snap_scale: f32,
+    /// Increments and angles that transformations snap to.
+    pub snap_settings: SnapSettings,
+    /// Allows dragging the scale handles past the gizmo origin,
+    /// which mirrors the targets with a negative scale.
+    pub negative_scaling: bool,
+    /// Ratio of transformation to cursor movement while dragging with
+    /// [`GizmoModifiers::precision`] held.
+    pub precision_factor: f32,
     /// If `true`, all [`GizmoTarget`]s are transformed
     /// using a single gizmo. If `false`, each target
     /// has its own gizmo.
@@ -100,13 +118,15 @@ impl Default for GizmoOptions {
         Self {
             gizmo_modes: EnumSet::only(GizmoMode::Rotate),
             gizmo_orientation: GizmoOrientation::default(),
+            euler_order: EulerOrder::default(),
             pivot_point: TransformPivotPoint::default(),
+            rotation_input: RotationInput::default(),
             visuals: Default::default(),
             gizmo_visibility: GizmoVisibility::default(),
             snapping: false,
-            snap_angle: DEFAULT_SNAP_ANGLE,
-            snap_distance: DEFAULT_SNAP_DISTANCE,
-            snap_scale: DEFAULT_SNAP_SCALE,
+            snap_settings: SnapSettings::default(),
+            negative_scaling: false,
+            precision_factor: DEFAULT_PRECISION_FACTOR,
             group_targets: true,
             viewport_rect: None,
         }
@@ -133,6 +153,9 @@ pub struct GizmoTarget {
     /// This gets replaced with the result of the most recent
     /// gizmo interaction that affected this entity.
     pub(crate) latest_result: Option<GizmoResult>,
+
+    /// Distance increments that translations snap to.
+    pub(crate) snap_distance: Vec3,
 }
 
 impl GizmoTarget {
@@ -151,6 +174,13 @@ impl GizmoTarget {
     pub fn latest_result(&self) -> Option<GizmoResult> {
         self.latest_result
     }
+
+    /// Distance increments that translations snap to, along each axis of the gizmo orientation.
+    /// These depend on the zoom level of the camera when adaptive snapping is used.
+    /// See [`Gizmo::snap_distance`].
+    pub fn snap_distance(&self) -> Vec3 {
+        self.snap_distance
+    }
 }
 
 /// Marker used to specify which camera to use for gizmos.
@@ -170,6 +200,8 @@ fn update_gizmos(
     q_gizmo_camera: Query<(&Camera, &GlobalTransform), With<GizmoCamera>>,
     mut q_targets: Query<(Entity, &mut Transform, &mut GizmoTarget), Without<GizmoCamera>>,
     mouse: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
     gizmo_options: Res<GizmoOptions>,
     mut gizmo_storage: ResMut<GizmoStorage>,
     mut last_cursor_pos: Local<Vec2>,
@@ -236,20 +268,41 @@ fn update_gizmos(
         viewport,
         modes: gizmo_options.gizmo_modes,
         orientation: gizmo_options.gizmo_orientation,
+        euler_order: gizmo_options.euler_order,
         pivot_point: gizmo_options.pivot_point,
+        rotation_input: gizmo_options.rotation_input,
+        // Surface placement requires a scene raycast, which is not available here
+        surface_placement: SurfacePlacement::Disabled,
         visuals: gizmo_options.visuals,
         gizmo_visibility: gizmo_options.gizmo_visibility,
         snapping: gizmo_options.snapping,
-        snap_angle: gizmo_options.snap_angle,
-        snap_distance: gizmo_options.snap_distance,
-        snap_scale: gizmo_options.snap_scale,
+        snap_settings: gizmo_options.snap_settings,
+        negative_scaling: gizmo_options.negative_scaling,
+        snap_point_distance: DEFAULT_SNAP_POINT_DISTANCE,
+        precision_factor: gizmo_options.precision_factor,
         pixels_per_point: scale_factor,
     };
 
     let gizmo_interaction = GizmoInteraction {
         cursor_pos: (cursor_pos.x, cursor_pos.y),
+        ray: None,
         drag_started: mouse.just_pressed(MouseButton::Left),
         dragging: mouse.any_pressed([MouseButton::Left]),
+        delta_time: Some(time.delta_seconds()),
+        modifiers: GizmoModifiers {
+            invert_snapping: keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]),
+            // Shift excludes the axis while an axis key is held
+            precision: keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+                && !keyboard.any_pressed(AXIS_KEYS.map(|(key, _)| key)),
+            duplicate: keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        },
+        axis_key: AXIS_KEYS
+            .into_iter()
+            .find(|&(key, _)| keyboard.just_pressed(key))
+            .map(|(_, direction)| AxisKey {
+                direction,
+                exclude: keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            }),
     };
 
     let mut target_entities: Vec<Entity> = vec![];
@@ -280,82 +333,100 @@ fn update_gizmos(
         let gizmo = gizmo_storage.gizmos.entry(gizmo_uuid).or_default();
         gizmo.update_config(gizmo_config);
 
-        let gizmo_result = gizmo.update(
-            gizmo_interaction,
-            &[transform_gizmo::math::Transform {
-                translation: target_transform.translation.as_dvec3().into(),
-                rotation: target_transform.rotation.as_dquat().into(),
-                scale: target_transform.scale.as_dvec3().into(),
-            }],
-        );
+        let mut targets = [gizmo_transform(&target_transform)];
+
+        let gizmo_result = gizmo.update_into(gizmo_interaction, &mut targets);
 
         let is_focused = gizmo.is_focused();
 
         gizmo_target.is_active = gizmo_result.is_some();
         gizmo_target.is_focused = is_focused;
+        gizmo_target.snap_distance = snap_distance(gizmo);
 
-        if let Some((_, updated_targets)) = &gizmo_result {
-            let Some(result_transform) = updated_targets.first() else {
-                bevy::log::warn!("No transform found in GizmoResult!");
-                continue;
-            };
+        if gizmo_result.is_some() {
+            let [result_transform] = targets;
 
-            target_transform.translation = DVec3::from(result_transform.translation).as_vec3();
-            target_transform.rotation = DQuat::from(result_transform.rotation).as_quat();
-            target_transform.scale = DVec3::from(result_transform.scale).as_vec3();
+            apply_gizmo_transform(&mut target_transform, result_transform);
         }
 
-        gizmo_target.latest_result = gizmo_result.map(|(result, _)| result);
+        gizmo_target.latest_result = gizmo_result;
     }
 
     if gizmo_options.group_targets {
         let gizmo = gizmo_storage.gizmos.entry(GIZMO_GROUP_UUID).or_default();
         gizmo.update_config(gizmo_config);
 
-        let gizmo_result = gizmo.update(
-            gizmo_interaction,
-            target_transforms
-                .iter()
-                .map(|transform| transform_gizmo::math::Transform {
-                    translation: transform.translation.as_dvec3().into(),
-                    rotation: transform.rotation.as_dquat().into(),
-                    scale: transform.scale.as_dvec3().into(),
-                })
-                .collect::<Vec<_>>()
-                .as_slice(),
-        );
+        let mut targets = target_transforms
+            .iter()
+            .map(gizmo_transform)
+            .collect::<Vec<_>>();
+
+        let gizmo_result = gizmo.update_into(gizmo_interaction, &mut targets);
 
         let is_focused = gizmo.is_focused();
+        let snap_distance = snap_distance(gizmo);
 
         for (i, (_, mut target_transform, mut gizmo_target)) in q_targets.iter_mut().enumerate() {
             gizmo_target.is_active = gizmo_result.is_some();
             gizmo_target.is_focused = is_focused;
+            gizmo_target.snap_distance = snap_distance;
 
-            if let Some((_, updated_targets)) = &gizmo_result {
-                let Some(result_transform) = updated_targets.get(i) else {
+            if gizmo_result.is_some() {
+                let Some(result_transform) = targets.get(i) else {
                     bevy::log::warn!("No transform {i} found in GizmoResult!");
                     continue;
                 };
 
-                target_transform.translation = DVec3::from(result_transform.translation).as_vec3();
-                target_transform.rotation = DQuat::from(result_transform.rotation).as_quat();
-                target_transform.scale = DVec3::from(result_transform.scale).as_vec3();
+                apply_gizmo_transform(&mut target_transform, *result_transform);
             }
 
-            gizmo_target.latest_result = gizmo_result.as_ref().map(|(result, _)| *result);
+            gizmo_target.latest_result = gizmo_result;
         }
     }
 
     gizmo_storage.target_entities = target_entities;
 }
 
+/// Converts a bevy transform to a gizmo target transform
+fn gizmo_transform(transform: &Transform) -> transform_gizmo::math::Transform {
+    transform_gizmo::math::Transform::from_scale_rotation_translation_f32(
+        transform.scale,
+        transform.rotation,
+        transform.translation,
+    )
+}
+
+/// Writes a transform updated by a gizmo to a bevy transform
+fn apply_gizmo_transform(transform: &mut Transform, result: transform_gizmo::math::Transform) {
+    let (scale, rotation, translation) = result.to_scale_rotation_translation_f32();
+
+    transform.scale = scale.into();
+    transform.rotation = rotation.into();
+    transform.translation = translation.into();
+}
+
+/// Distance increments that translations of the gizmo snap to
+fn snap_distance(gizmo: &Gizmo) -> Vec3 {
+    let snap_distance = gizmo.snap_distance();
+    Vec3::new(
+        snap_distance.x as f32,
+        snap_distance.y as f32,
+        snap_distance.z as f32,
+    )
+}
+
 fn draw_gizmos(
-    gizmo_storage: Res<GizmoStorage>,
+    mut gizmo_storage: ResMut<GizmoStorage>,
     mut draw_data_assets: ResMut<Assets<render::GizmoDrawData>>,
     mut draw_data_handles: ResMut<DrawDataHandles>,
 ) {
-    for (gizmo_uuid, gizmo) in &gizmo_storage.gizmos {
-        let draw_data = gizmo.draw();
+    for (gizmo_uuid, gizmo) in &mut gizmo_storage.gizmos {
+        let viewport = gizmo.config().viewport;
+
+        // Skip gizmos that look the same as before, so that their buffers are not re-uploaded.
+        let Some(draw_data) = gizmo.draw_if_changed() else {
+            continue;
+        };
 
         let mut bevy_draw_data = render::GizmoDrawData::default();
 
@@ -366,21 +437,19 @@ fn draw_gizmos(
             (&mut bevy_draw_data, true)
         };
 
-        let viewport = &gizmo.config().viewport;
-
         asset.0.vertices.clear();
         asset
             .0
             .vertices
-            .extend(draw_data.vertices.into_iter().map(|vert| {
+            .extend(draw_data.vertices.iter().map(|vert| {
                 [
                     ((vert[0] - viewport.left()) / viewport.width()) * 2.0 - 1.0,
                     ((vert[1] - viewport.top()) / viewport.height()) * 2.0 - 1.0,
                 ]
             }));
 
-        asset.0.colors = draw_data.colors;
-        asset.0.indices = draw_data.indices;
+        asset.0.colors.clone_from(&draw_data.colors);
+        asset.0.indices.clone_from(&draw_data.indices);
 
         if is_new_asset {
             let asset = draw_data_assets.add(bevy_draw_data);
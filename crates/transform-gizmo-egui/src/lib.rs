@@ -44,15 +44,26 @@
 //! ```
 //!
 //!
-use egui::{epaint::Vertex, Mesh, PointerButton, Pos2, Rgba, Ui};
+use egui::{epaint::Vertex, Key, Mesh, PointerButton, Pos2, Rgba, Ui};
 
 use transform_gizmo::math::Transform;
 pub use transform_gizmo::*;
 pub mod prelude;
 
+/// Keys that constrain a drag to an axis, see [`GizmoInteraction::axis_key`].
+const AXIS_KEYS: [(Key, GizmoDirection); 3] = [
+    (Key::X, GizmoDirection::X),
+    (Key::Y, GizmoDirection::Y),
+    (Key::Z, GizmoDirection::Z),
+];
+
 pub trait GizmoExt {
     /// Interact with the gizmo and draw it to Ui.
     ///
+    /// `Ctrl` inverts snapping, `Shift` enables precision and `Alt` duplicates.
+    /// `X`, `Y` and `Z` constrain a drag to an axis, or exclude the axis with `Shift`.
+    /// While an axis key is held, `Shift` excludes the axis and does not enable precision.
+    ///
     /// Returns result of the gizmo interaction.
     fn interact(&mut self, ui: &Ui, targets: &[Transform])
         -> Option<(GizmoResult, Vec<Transform>)>;
@@ -66,10 +77,7 @@ impl GizmoExt for Gizmo {
     ) -> Option<(GizmoResult, Vec<Transform>)> {
         let config = self.config();
 
-        let egui_viewport = egui::Rect {
-            min: Pos2::new(config.viewport.min.x, config.viewport.min.y),
-            max: Pos2::new(config.viewport.max.x, config.viewport.max.y),
-        };
+        let egui_viewport = egui::Rect::from(config.viewport);
 
         let cursor_pos = ui
             .input(|input| input.pointer.hover_pos())
@@ -77,7 +85,7 @@ impl GizmoExt for Gizmo {
 
         let mut viewport = self.config().viewport;
         if !viewport.is_finite() {
-            viewport = ui.clip_rect();
+            viewport = ui.clip_rect().into();
         }
 
         self.update_config(GizmoConfig {
@@ -89,13 +97,36 @@ impl GizmoExt for Gizmo {
         let gizmo_result = self.update(
             GizmoInteraction {
                 cursor_pos: (cursor_pos.x, cursor_pos.y),
+                ray: None,
                 drag_started: ui
                     .input(|input| input.pointer.button_pressed(PointerButton::Primary)),
                 dragging: ui.input(|input| input.pointer.button_down(PointerButton::Primary)),
+                delta_time: Some(ui.input(|input| input.stable_dt)),
+                modifiers: ui.input(|input| GizmoModifiers {
+                    invert_snapping: input.modifiers.ctrl,
+                    // Shift excludes the axis while an axis key is held
+                    precision: input.modifiers.shift
+                        && !AXIS_KEYS.iter().any(|&(key, _)| input.key_down(key)),
+                    duplicate: input.modifiers.alt,
+                }),
+                axis_key: ui.input(|input| {
+                    AXIS_KEYS
+                        .into_iter()
+                        .find(|&(key, _)| input.key_pressed(key))
+                        .map(|(_, direction)| AxisKey {
+                            direction,
+                            exclude: input.modifiers.shift,
+                        })
+                }),
             },
             targets,
         );
 
+        // Keep redrawing until the highlight and fade transitions are finished
+        if self.is_animating() {
+            ui.ctx().request_repaint();
+        }
+
         let draw_data = self.draw();
 
         ui.painter().with_clip_rect(egui_viewport).add(Mesh {
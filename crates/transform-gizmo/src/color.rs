@@ -0,0 +1,251 @@
+//! Minimal color types.
+//!
+//! The API mirrors the corresponding types of `ecolor`. With the `epaint` feature,
+//! [`Color32`] converts to and from `ecolor::Color32`.
+
+/// sRGBA color with premultiplied alpha, 8 bits per channel.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color32([u8; 4]);
+
+impl Color32 {
+    pub const TRANSPARENT: Self = Self::from_rgba_premultiplied(0, 0, 0, 0);
+    pub const BLACK: Self = Self::from_rgb(0, 0, 0);
+    pub const WHITE: Self = Self::from_rgb(255, 255, 255);
+    pub const RED: Self = Self::from_rgb(255, 0, 0);
+    pub const GREEN: Self = Self::from_rgb(0, 255, 0);
+    pub const BLUE: Self = Self::from_rgb(0, 0, 255);
+
+    #[inline]
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self([r, g, b, 255])
+    }
+
+    #[inline]
+    pub const fn from_rgba_premultiplied(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self([r, g, b, a])
+    }
+
+    /// From sRGBA with separate alpha.
+    pub fn from_rgba_unmultiplied(r: u8, g: u8, b: u8, a: u8) -> Self {
+        match a {
+            255 => Self::from_rgb(r, g, b),
+            0 => Self::TRANSPARENT,
+            _ => {
+                let a_lin = linear_f32_from_linear_u8(a);
+                let premultiply =
+                    |c: u8| gamma_u8_from_linear_f32(linear_f32_from_gamma_u8(c) * a_lin);
+                Self([premultiply(r), premultiply(g), premultiply(b), a])
+            }
+        }
+    }
+
+    #[inline]
+    pub const fn r(&self) -> u8 {
+        self.0[0]
+    }
+
+    #[inline]
+    pub const fn g(&self) -> u8 {
+        self.0[1]
+    }
+
+    #[inline]
+    pub const fn b(&self) -> u8 {
+        self.0[2]
+    }
+
+    #[inline]
+    pub const fn a(&self) -> u8 {
+        self.0[3]
+    }
+
+    #[inline]
+    pub const fn to_array(self) -> [u8; 4] {
+        self.0
+    }
+
+    /// Multiply with 0.5 to make the color half as opaque, perceptually.
+    pub fn gamma_multiply(self, factor: f32) -> Self {
+        let multiply = |c: u8| (c as f32 * factor + 0.5) as u8;
+        Self(self.0.map(multiply))
+    }
+
+    /// Multiply with 0.5 to make the color half as opaque in linear space.
+    pub fn linear_multiply(self, factor: f32) -> Self {
+        Self::from(Rgba::from(self) * factor)
+    }
+}
+
+/// Linear RGBA color with premultiplied alpha, 32 bits per channel.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Rgba([f32; 4]);
+
+impl Rgba {
+    pub const TRANSPARENT: Self = Self([0.0; 4]);
+
+    #[inline]
+    pub const fn from_rgba_premultiplied(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self([r, g, b, a])
+    }
+
+    #[inline]
+    pub const fn r(&self) -> f32 {
+        self.0[0]
+    }
+
+    #[inline]
+    pub const fn g(&self) -> f32 {
+        self.0[1]
+    }
+
+    #[inline]
+    pub const fn b(&self) -> f32 {
+        self.0[2]
+    }
+
+    #[inline]
+    pub const fn a(&self) -> f32 {
+        self.0[3]
+    }
+
+    #[inline]
+    pub const fn to_array(self) -> [f32; 4] {
+        self.0
+    }
+}
+
+impl std::ops::Mul<f32> for Rgba {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, factor: f32) -> Self {
+        Self(self.0.map(|c| c * factor))
+    }
+}
+
+impl From<Color32> for Rgba {
+    fn from(color: Color32) -> Self {
+        Self([
+            linear_f32_from_gamma_u8(color.r()),
+            linear_f32_from_gamma_u8(color.g()),
+            linear_f32_from_gamma_u8(color.b()),
+            linear_f32_from_linear_u8(color.a()),
+        ])
+    }
+}
+
+impl From<Rgba> for Color32 {
+    fn from(rgba: Rgba) -> Self {
+        Self([
+            gamma_u8_from_linear_f32(rgba.r()),
+            gamma_u8_from_linear_f32(rgba.g()),
+            gamma_u8_from_linear_f32(rgba.b()),
+            linear_u8_from_linear_f32(rgba.a()),
+        ])
+    }
+}
+
+#[cfg(feature = "epaint")]
+impl From<ecolor::Color32> for Color32 {
+    #[inline]
+    fn from(color: ecolor::Color32) -> Self {
+        Self(color.to_array())
+    }
+}
+
+#[cfg(feature = "epaint")]
+impl From<Color32> for ecolor::Color32 {
+    #[inline]
+    fn from(color: Color32) -> Self {
+        let [r, g, b, a] = color.to_array();
+        Self::from_rgba_premultiplied(r, g, b, a)
+    }
+}
+
+/// [0, 255] sRGB gamma value to [0, 1] linear value.
+fn linear_f32_from_gamma_u8(s: u8) -> f32 {
+    if s <= 10 {
+        s as f32 / 3294.6
+    } else {
+        ((s as f32 + 14.025) / 269.025).powf(2.4)
+    }
+}
+
+fn linear_f32_from_linear_u8(a: u8) -> f32 {
+    a as f32 / 255.0
+}
+
+/// [0, 1] linear value to [0, 255] sRGB gamma value.
+fn gamma_u8_from_linear_f32(l: f32) -> u8 {
+    if l <= 0.0 {
+        0
+    } else if l <= 0.003_130_8 {
+        (3294.6 * l).round() as u8
+    } else if l <= 1.0 {
+        (269.025 * l.powf(1.0 / 2.4) - 14.025).round() as u8
+    } else {
+        255
+    }
+}
+
+fn linear_u8_from_linear_f32(a: f32) -> u8 {
+    (a * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamma_conversion_roundtrip() {
+        for value in 0..=255 {
+            let color = Color32::from_rgba_premultiplied(value, value, value, value);
+            assert_eq!(Color32::from(Rgba::from(color)), color);
+        }
+
+        assert_eq!(Rgba::from(Color32::WHITE).to_array(), [1.0; 4]);
+        assert_eq!(Rgba::from(Color32::BLACK).to_array(), [0.0, 0.0, 0.0, 1.0]);
+        // Mid gray is darker in linear space
+        let gray = Rgba::from(Color32::from_rgb(128, 128, 128));
+        assert!((gray.r() - 0.2158).abs() < 1e-4);
+    }
+
+    #[test]
+    fn alpha_is_premultiplied() {
+        assert_eq!(
+            Color32::from_rgba_unmultiplied(255, 255, 255, 128),
+            Color32::from_rgba_premultiplied(188, 188, 188, 128)
+        );
+        assert_eq!(
+            Color32::from_rgba_unmultiplied(255, 0, 0, 0),
+            Color32::TRANSPARENT
+        );
+
+        let color = Color32::from_rgb(200, 100, 0);
+        assert_eq!(
+            color.gamma_multiply(0.5),
+            Color32::from_rgba_premultiplied(100, 50, 0, 128)
+        );
+        assert_eq!(
+            Color32::WHITE.linear_multiply(0.5),
+            Color32::from_rgba_premultiplied(188, 188, 188, 128)
+        );
+    }
+
+    #[cfg(feature = "epaint")]
+    #[test]
+    fn egui_conversions() {
+        let color = Color32::from_rgba_unmultiplied(200, 100, 50, 128);
+        let egui_color = ecolor::Color32::from(color);
+        assert_eq!(
+            egui_color,
+            ecolor::Color32::from_rgba_unmultiplied(200, 100, 50, 128)
+        );
+        assert_eq!(Color32::from(egui_color), color);
+        assert_eq!(
+            Rgba::from(color).to_array(),
+            ecolor::Rgba::from(egui_color).to_array()
+        );
+    }
+}
@@ -1,12 +1,14 @@
+use std::f64::consts::TAU;
 use std::ops::{Deref, DerefMut};
 
-pub use ecolor::Color32;
+pub use crate::color::Color32;
 
-use emath::Rect;
+use crate::math::Rect;
 use enumset::{enum_set, EnumSet, EnumSetType};
 
 use crate::math::{
-    screen_to_world, world_to_screen, DMat4, DQuat, DVec3, DVec4, Transform, Vec4Swizzles,
+    round_to_interval, screen_to_world, world_to_screen, DMat4, DQuat, DVec3, DVec4, EulerOrder,
+    Transform, Vec3, Vec4Swizzles,
 };
 
 /// The default snapping distance for rotation in radians
@@ -15,8 +17,18 @@ pub const DEFAULT_SNAP_ANGLE: f32 = std::f32::consts::PI / 32.0;
 pub const DEFAULT_SNAP_DISTANCE: f32 = 0.1;
 /// The default snapping distance for scale
 pub const DEFAULT_SNAP_SCALE: f32 = 0.1;
+/// The default screen distance in pixels, below which the gizmo snaps to snap points
+pub const DEFAULT_SNAP_POINT_DISTANCE: f32 = 10.0;
+/// The default smallest screen distance in pixels of an adaptive snap increment
+pub const DEFAULT_ADAPTIVE_SNAP_PIXELS: f32 = 10.0;
+/// The default ratio of transformation to cursor movement while dragging with
+/// [`GizmoModifiers::precision`](crate::GizmoModifiers::precision) held
+pub const DEFAULT_PRECISION_FACTOR: f32 = 0.1;
+/// The default view angle in radians, below which [`RotationInput::Auto`] switches to linear rotation
+pub const DEFAULT_LINEAR_ROTATION_THRESHOLD: f32 = std::f32::consts::PI / 12.0;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AxisConfig {
     pub x: bool,
     pub y: bool,
@@ -50,7 +62,8 @@ impl AxisConfig {
 ///
 /// Defines how the gizmo is drawn to the screen and
 /// how it can be interacted with.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GizmoConfig {
     /// View matrix for the gizmo, aligning it with the camera's viewpoint.
     pub view_matrix: mint::RowMatrix4<f64>,
@@ -62,16 +75,29 @@ pub struct GizmoConfig {
     pub modes: EnumSet<GizmoMode>,
     /// Determines the gizmo's orientation relative to global or local axes.
     pub orientation: GizmoOrientation,
+    /// Rotation order of the targets' Euler angles.
+    /// Used by the rotation rings of [`GizmoOrientation::Gimbal`].
+    pub euler_order: EulerOrder,
     /// Pivot point for transformations
     pub pivot_point: TransformPivotPoint,
+    /// Determines how cursor movement is converted into rotation angles.
+    pub rotation_input: RotationInput,
+    /// Determines whether the view plane translation handle places the targets on scene surfaces.
+    /// Only used when a raycast is given with [`GizmoScene::surface`](crate::GizmoScene::surface).
+    pub surface_placement: SurfacePlacement,
     /// Toggles snapping to predefined increments during transformations for precision.
     pub snapping: bool,
-    /// Angle increment for snapping rotations, in radians.
-    pub snap_angle: f32,
-    /// Distance increment for snapping translations.
-    pub snap_distance: f32,
-    /// Scale increment for snapping scalings.
-    pub snap_scale: f32,
+    /// Increments and angles that transformations snap to.
+    pub snap_settings: SnapSettings,
+    /// Allows dragging the scale handles past the gizmo origin,
+    /// which mirrors the targets with a negative scale.
+    pub negative_scaling: bool,
+    /// Maximum screen distance, in pixels, from which a translated gizmo snaps to a snap point.
+    /// Only used when snap points are given with [`GizmoScene::snap_points`](crate::GizmoScene::snap_points).
+    pub snap_point_distance: f32,
+    /// Ratio of transformation to cursor movement while dragging with
+    /// [`GizmoModifiers::precision`](crate::GizmoModifiers::precision) held.
+    pub precision_factor: f32,
     /// Visual settings for the gizmo, affecting appearance and visibility.
     pub visuals: GizmoVisuals,
     /// Visibility of sub gizmo primitives
@@ -88,11 +114,15 @@ impl Default for GizmoConfig {
             viewport: Rect::NOTHING,
             modes: enum_set!(GizmoMode::Rotate),
             orientation: GizmoOrientation::default(),
+            euler_order: EulerOrder::default(),
             pivot_point: TransformPivotPoint::default(),
+            rotation_input: RotationInput::default(),
+            surface_placement: SurfacePlacement::default(),
             snapping: false,
-            snap_angle: DEFAULT_SNAP_ANGLE,
-            snap_distance: DEFAULT_SNAP_DISTANCE,
-            snap_scale: DEFAULT_SNAP_SCALE,
+            snap_settings: SnapSettings::default(),
+            negative_scaling: false,
+            snap_point_distance: DEFAULT_SNAP_POINT_DISTANCE,
+            precision_factor: DEFAULT_PRECISION_FACTOR,
             visuals: GizmoVisuals::default(),
             gizmo_visibility: GizmoVisibility::default(),
             pixels_per_point: 1.0,
@@ -101,6 +131,145 @@ impl Default for GizmoConfig {
 }
 
 impl GizmoConfig {
+    /// Creates a builder for a validated [`GizmoConfig`], starting from the default configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use transform_gizmo::prelude::*;
+    /// # use transform_gizmo::math::{DMat4, DVec3, Pos2};
+    /// let config = GizmoConfig::builder()
+    ///     .view_matrix(DMat4::look_at_rh(DVec3::splat(5.0), DVec3::ZERO, DVec3::Y))
+    ///     .projection_matrix(DMat4::perspective_rh(1.0, 4.0 / 3.0, 0.1, 100.0))
+    ///     .viewport(Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(800.0, 600.0)))
+    ///     .modes(GizmoMode::Translate | GizmoMode::Rotate)
+    ///     .build();
+    ///
+    /// assert!(config.is_ok());
+    ///
+    /// let config = GizmoConfig::builder().viewport(Rect::NOTHING).build();
+    ///
+    /// assert_eq!(config.unwrap_err(), GizmoConfigError::InvalidViewport);
+    /// ```
+    pub fn builder() -> GizmoConfigBuilder {
+        GizmoConfigBuilder::default()
+    }
+
+    /// Checks that the configuration can be used for interacting with and drawing a gizmo.
+    ///
+    /// Returns the first problem found.
+    pub fn validate(&self) -> Result<(), GizmoConfigError> {
+        let view_matrix = DMat4::from(self.view_matrix);
+        if !view_matrix.is_finite() {
+            return Err(GizmoConfigError::NonFiniteViewMatrix);
+        }
+        if !is_invertible(view_matrix) {
+            return Err(GizmoConfigError::SingularViewMatrix);
+        }
+
+        let projection_matrix = DMat4::from(self.projection_matrix);
+        if !projection_matrix.is_finite() {
+            return Err(GizmoConfigError::NonFiniteProjectionMatrix);
+        }
+        if !is_invertible(projection_matrix) {
+            return Err(GizmoConfigError::SingularProjectionMatrix);
+        }
+
+        if !self.viewport.is_finite()
+            || self.viewport.width() <= 0.0
+            || self.viewport.height() <= 0.0
+        {
+            return Err(GizmoConfigError::InvalidViewport);
+        }
+
+        if let RotationInput::Auto { threshold } = self.rotation_input {
+            if !threshold.is_finite() || threshold <= 0.0 {
+                return Err(GizmoConfigError::InvalidRotationInputThreshold);
+            }
+        }
+
+        // Snap increments are checked even when snapping is disabled,
+        // since it can be enabled during a drag with `GizmoModifiers::invert_snapping`.
+        // Lists of snap angles are validated when they are created.
+        let snap = &self.snap_settings;
+        let is_valid_snap = |snap: f32| snap.is_finite() && snap > 0.0;
+        if let AngleSnap::Increment(increment) = snap.angle {
+            if !is_valid_snap(increment) {
+                return Err(GizmoConfigError::InvalidSnapAngle);
+            }
+        }
+        let distance = snap.distance;
+        if ![distance.x, distance.y, distance.z]
+            .into_iter()
+            .all(is_valid_snap)
+        {
+            return Err(GizmoConfigError::InvalidSnapDistance);
+        }
+        if let Some(adaptive) = snap.adaptive_distance {
+            if !is_valid_snap(adaptive.min_pixels)
+                || !adaptive.base.is_finite()
+                || adaptive.base <= 1.0
+            {
+                return Err(GizmoConfigError::InvalidAdaptiveSnap);
+            }
+        }
+        let scale = snap.scale;
+        if ![scale.x, scale.y, scale.z].into_iter().all(is_valid_snap) {
+            return Err(GizmoConfigError::InvalidSnapScale);
+        }
+
+        if !self.snap_point_distance.is_finite() || self.snap_point_distance < 0.0 {
+            return Err(GizmoConfigError::InvalidSnapPointDistance);
+        }
+
+        if !self.precision_factor.is_finite() || self.precision_factor <= 0.0 {
+            return Err(GizmoConfigError::InvalidPrecisionFactor);
+        }
+
+        if !self.visuals.gizmo_size.is_finite() || self.visuals.gizmo_size <= 0.0 {
+            return Err(GizmoConfigError::InvalidGizmoSize);
+        }
+
+        if let GizmoSizing::World {
+            size,
+            min_pixels,
+            max_pixels,
+        } = self.visuals.sizing
+        {
+            let is_valid_size = |size: f32| size.is_finite() && size > 0.0;
+            if !is_valid_size(size)
+                || !min_pixels.map_or(true, is_valid_size)
+                || !max_pixels.map_or(true, is_valid_size)
+            {
+                return Err(GizmoConfigError::InvalidGizmoSize);
+            }
+
+            if min_pixels
+                .zip(max_pixels)
+                .is_some_and(|(min, max)| min > max)
+            {
+                return Err(GizmoConfigError::InvalidGizmoSizeRange);
+            }
+        }
+
+        if !self.visuals.stroke_width.is_finite() || self.visuals.stroke_width < 0.0 {
+            return Err(GizmoConfigError::InvalidStrokeWidth);
+        }
+
+        let is_valid_duration = |duration: f32| duration.is_finite() && duration >= 0.0;
+        if !is_valid_duration(self.visuals.highlight_duration)
+            || !is_valid_duration(self.visuals.fade_duration)
+        {
+            return Err(GizmoConfigError::InvalidTransitionDuration);
+        }
+
+        if !self.pixels_per_point.is_finite() || self.pixels_per_point <= 0.0 {
+            return Err(GizmoConfigError::InvalidPixelsPerPoint);
+        }
+
+        Ok(())
+    }
+
     /// Forward vector of the view camera
     pub(crate) fn view_forward(&self) -> DVec3 {
         DVec4::from(self.view_matrix.z).xyz()
@@ -115,25 +284,226 @@ impl GizmoConfig {
     pub(crate) fn view_right(&self) -> DVec3 {
         DVec4::from(self.view_matrix.x).xyz()
     }
+}
 
-    /// Whether local orientation is used
-    pub(crate) fn local_space(&self) -> bool {
-        self.orientation() == GizmoOrientation::Local
-    }
+/// A problem with a [`GizmoConfig`], found by [`GizmoConfig::validate`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GizmoConfigError {
+    /// The view matrix contains infinite or NaN values
+    NonFiniteViewMatrix,
+    /// The view matrix is not invertible, or too close to it to be inverted reliably
+    SingularViewMatrix,
+    /// The projection matrix contains infinite or NaN values
+    NonFiniteProjectionMatrix,
+    /// The projection matrix is not invertible, or too close to it to be inverted reliably
+    SingularProjectionMatrix,
+    /// The viewport is not finite, or it has no area
+    InvalidViewport,
+    /// The threshold of [`RotationInput::Auto`] is not a positive finite number
+    InvalidRotationInputThreshold,
+    /// The snap angle increment is not a positive finite number, or a list of [`SnapAngles`]
+    /// is empty, too long or contains angles that are not positive finite numbers
+    InvalidSnapAngle,
+    /// A snap distance increment is not a positive finite number
+    InvalidSnapDistance,
+    /// A snap scale increment is not a positive finite number
+    InvalidSnapScale,
+    /// The minimum pixels of [`AdaptiveSnap`] is not a positive finite number,
+    /// or its base is not a finite number greater than one
+    InvalidAdaptiveSnap,
+    /// The snap point distance is not a non-negative finite number
+    InvalidSnapPointDistance,
+    /// The precision factor is not a positive finite number
+    InvalidPrecisionFactor,
+    /// The gizmo size, or the size limits of [`GizmoSizing::World`], are not positive finite numbers
+    InvalidGizmoSize,
+    /// The minimum pixel size of [`GizmoSizing::World`] is larger than its maximum pixel size
+    InvalidGizmoSizeRange,
+    /// The stroke width is not a non-negative finite number
+    InvalidStrokeWidth,
+    /// The highlight or fade duration of the visuals is not a non-negative finite number
+    InvalidTransitionDuration,
+    /// The pixels per point ratio is not a positive finite number
+    InvalidPixelsPerPoint,
+}
 
-    /// Transform orientation of the gizmo
-    pub(crate) fn orientation(&self) -> GizmoOrientation {
-        if self.modes.contains(GizmoMode::Scale) {
-            // Scaling currently only works in local orientation,
-            // so the configured orientation is ignored.
-            GizmoOrientation::Local
-        } else {
-            self.orientation
-        }
+impl std::fmt::Display for GizmoConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::NonFiniteViewMatrix => "view matrix contains infinite or NaN values",
+            Self::SingularViewMatrix => "view matrix is not invertible",
+            Self::NonFiniteProjectionMatrix => "projection matrix contains infinite or NaN values",
+            Self::SingularProjectionMatrix => "projection matrix is not invertible",
+            Self::InvalidViewport => "viewport is not finite or has no area",
+            Self::InvalidRotationInputThreshold => {
+                "automatic rotation input threshold must be a positive finite number"
+            }
+            Self::InvalidSnapAngle => {
+                "snap angles must be a non-empty list of at most 16 positive finite numbers"
+            }
+            Self::InvalidSnapDistance => "snap distances must be positive finite numbers",
+            Self::InvalidSnapScale => "snap scales must be positive finite numbers",
+            Self::InvalidAdaptiveSnap => {
+                "adaptive snap must have positive minimum pixels and a base greater than one"
+            }
+            Self::InvalidSnapPointDistance => {
+                "snap point distance must be a non-negative finite number"
+            }
+            Self::InvalidPrecisionFactor => "precision factor must be a positive finite number",
+            Self::InvalidGizmoSize => "gizmo size must be a positive finite number",
+            Self::InvalidGizmoSizeRange => {
+                "minimum gizmo pixel size must not be larger than the maximum pixel size"
+            }
+            Self::InvalidStrokeWidth => "stroke width must be a non-negative finite number",
+            Self::InvalidTransitionDuration => {
+                "highlight and fade durations must be non-negative finite numbers"
+            }
+            Self::InvalidPixelsPerPoint => "pixels per point must be a positive finite number",
+        };
+
+        f.write_str(message)
     }
 }
 
+impl std::error::Error for GizmoConfigError {}
+
+/// Whether the matrix can be reliably inverted.
+///
+/// The determinant is compared with the product of the column lengths, which bounds it,
+/// so that the check does not depend on the units of the scene.
+fn is_invertible(matrix: DMat4) -> bool {
+    let determinant = matrix.determinant();
+    let column_lengths = matrix.x_axis.length()
+        * matrix.y_axis.length()
+        * matrix.z_axis.length()
+        * matrix.w_axis.length();
+    determinant.is_finite()
+        && column_lengths.is_finite()
+        && determinant.abs() > column_lengths * f64::EPSILON
+}
+
+/// Builder for a validated [`GizmoConfig`].
+///
+/// Created with [`GizmoConfig::builder`]. Values that are not set are taken from [`GizmoConfig::default`].
 #[derive(Debug, Copy, Clone, Default)]
+pub struct GizmoConfigBuilder {
+    config: GizmoConfig,
+}
+
+impl GizmoConfigBuilder {
+    /// Sets the view matrix. See [`GizmoConfig::view_matrix`].
+    pub fn view_matrix(mut self, view_matrix: impl Into<mint::RowMatrix4<f64>>) -> Self {
+        self.config.view_matrix = view_matrix.into();
+        self
+    }
+
+    /// Sets the projection matrix. See [`GizmoConfig::projection_matrix`].
+    pub fn projection_matrix(
+        mut self,
+        projection_matrix: impl Into<mint::RowMatrix4<f64>>,
+    ) -> Self {
+        self.config.projection_matrix = projection_matrix.into();
+        self
+    }
+
+    /// Sets the viewport. See [`GizmoConfig::viewport`].
+    pub fn viewport(mut self, viewport: Rect) -> Self {
+        self.config.viewport = viewport;
+        self
+    }
+
+    /// Sets the operation modes. See [`GizmoConfig::modes`].
+    pub fn modes(mut self, modes: impl Into<EnumSet<GizmoMode>>) -> Self {
+        self.config.modes = modes.into();
+        self
+    }
+
+    /// Sets the orientation. See [`GizmoConfig::orientation`](GizmoConfig#structfield.orientation).
+    pub fn orientation(mut self, orientation: GizmoOrientation) -> Self {
+        self.config.orientation = orientation;
+        self
+    }
+
+    /// Sets the Euler rotation order. See [`GizmoConfig::euler_order`].
+    pub fn euler_order(mut self, euler_order: EulerOrder) -> Self {
+        self.config.euler_order = euler_order;
+        self
+    }
+
+    /// Sets the pivot point. See [`GizmoConfig::pivot_point`].
+    pub fn pivot_point(mut self, pivot_point: TransformPivotPoint) -> Self {
+        self.config.pivot_point = pivot_point;
+        self
+    }
+
+    /// Sets the rotation input. See [`GizmoConfig::rotation_input`].
+    pub fn rotation_input(mut self, rotation_input: RotationInput) -> Self {
+        self.config.rotation_input = rotation_input;
+        self
+    }
+
+    /// Sets the surface placement. See [`GizmoConfig::surface_placement`].
+    pub fn surface_placement(mut self, surface_placement: SurfacePlacement) -> Self {
+        self.config.surface_placement = surface_placement;
+        self
+    }
+
+    /// Enables or disables snapping. See [`GizmoConfig::snapping`].
+    pub fn snapping(mut self, snapping: bool) -> Self {
+        self.config.snapping = snapping;
+        self
+    }
+
+    /// Sets the snap settings. See [`GizmoConfig::snap_settings`].
+    pub fn snap_settings(mut self, snap_settings: SnapSettings) -> Self {
+        self.config.snap_settings = snap_settings;
+        self
+    }
+
+    /// Enables or disables negative scaling. See [`GizmoConfig::negative_scaling`].
+    pub fn negative_scaling(mut self, negative_scaling: bool) -> Self {
+        self.config.negative_scaling = negative_scaling;
+        self
+    }
+
+    /// Sets the snap point distance, in pixels. See [`GizmoConfig::snap_point_distance`].
+    pub fn snap_point_distance(mut self, snap_point_distance: f32) -> Self {
+        self.config.snap_point_distance = snap_point_distance;
+        self
+    }
+
+    /// Sets the precision factor. See [`GizmoConfig::precision_factor`].
+    pub fn precision_factor(mut self, precision_factor: f32) -> Self {
+        self.config.precision_factor = precision_factor;
+        self
+    }
+
+    /// Sets the visual settings. See [`GizmoConfig::visuals`].
+    pub fn visuals(mut self, visuals: GizmoVisuals) -> Self {
+        self.config.visuals = visuals;
+        self
+    }
+
+    /// Sets the visibility of subgizmos. See [`GizmoConfig::gizmo_visibility`].
+    pub fn gizmo_visibility(mut self, gizmo_visibility: GizmoVisibility) -> Self {
+        self.config.gizmo_visibility = gizmo_visibility;
+        self
+    }
+
+    /// Sets the pixels per point ratio. See [`GizmoConfig::pixels_per_point`].
+    pub fn pixels_per_point(mut self, pixels_per_point: f32) -> Self {
+        self.config.pixels_per_point = pixels_per_point;
+        self
+    }
+
+    /// Validates and returns the configuration.
+    pub fn build(self) -> Result<GizmoConfig, GizmoConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
 pub(crate) struct PreparedGizmoConfig {
     config: GizmoConfig,
     /// Rotation of the gizmo
@@ -148,14 +518,23 @@ pub(crate) struct PreparedGizmoConfig {
     pub(crate) model_matrix: DMat4,
     /// Combined model-view-projection matrix
     pub(crate) mvp: DMat4,
-    /// Scale factor for the gizmo rendering
+    /// Scale factor for the gizmo rendering.
+    /// Lengths of the gizmo, given in pixels by [`GizmoVisuals`], are multiplied by this
+    /// to get the corresponding lengths in world units.
     pub(crate) scale_factor: f32,
+    /// Size of a screen pixel in world units, at the gizmo's distance from the camera
+    pub(crate) pixel_size: f32,
     /// How close the mouse pointer needs to be to a subgizmo before it is focused
     pub(crate) focus_distance: f32,
     /// Whether left-handed projection is used
     pub(crate) left_handed: bool,
     /// Direction from the camera to the gizmo in world space
     pub(crate) eye_to_model_dir: DVec3,
+    /// Orientation of the axis constraint of the current drag, used instead of the configured one
+    pub(crate) constraint_orientation: Option<GizmoOrientation>,
+    /// Snap distance increments of the current drag, kept from the start of the drag
+    /// so that adaptive increments do not change while dragging
+    pub(crate) drag_snap_distance: Option<DVec3>,
 }
 
 impl Deref for PreparedGizmoConfig {
@@ -232,11 +611,30 @@ impl PreparedGizmoConfig {
             DMat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation);
         self.mvp = self.view_projection * self.model_matrix;
 
-        self.scale_factor = self.mvp.as_ref()[15] as f32
+        self.pixel_size = self.mvp.as_ref()[15] as f32
             / self.projection_matrix.x.x as f32
             / self.config.viewport.width()
             * 2.0;
 
+        self.scale_factor = match self.config.visuals.sizing {
+            GizmoSizing::Screen => self.pixel_size,
+            GizmoSizing::World {
+                size,
+                min_pixels,
+                max_pixels,
+            } => {
+                let gizmo_size = self.config.visuals.gizmo_size;
+                let mut size_in_pixels = size / self.pixel_size;
+                if let Some(max_pixels) = max_pixels {
+                    size_in_pixels = size_in_pixels.min(max_pixels);
+                }
+                if let Some(min_pixels) = min_pixels {
+                    size_in_pixels = size_in_pixels.max(min_pixels);
+                }
+                self.pixel_size * size_in_pixels / gizmo_size
+            }
+        };
+
         let gizmo_screen_pos =
             world_to_screen(self.config.viewport, self.mvp, self.translation).unwrap_or_default();
 
@@ -252,6 +650,82 @@ impl PreparedGizmoConfig {
         self.eye_to_model_dir = (gizmo_view_near - self.translation).normalize_or_zero();
     }
 
+    /// Scale factor for stroke widths, which are drawn in screen pixels.
+    /// With [`GizmoSizing::World`], strokes are scaled along with the rest of the gizmo.
+    pub(crate) fn stroke_scale(&self) -> f32 {
+        match self.config.visuals.sizing {
+            GizmoSizing::Screen => 1.0,
+            GizmoSizing::World { .. } => {
+                let stroke_scale = self.scale_factor / self.pixel_size;
+                if stroke_scale.is_finite() {
+                    stroke_scale
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+
+    /// Distance increments that translations snap to, along each axis of the gizmo orientation
+    pub(crate) fn snap_distance(&self) -> DVec3 {
+        if let Some(snap_distance) = self.drag_snap_distance {
+            return snap_distance;
+        }
+
+        let snap = &self.snap_settings;
+        let distance = Vec3::from(snap.distance).as_dvec3();
+        let Some(adaptive) = snap.adaptive_distance else {
+            return distance;
+        };
+
+        let base = adaptive.base as f64;
+        let min_distance = (adaptive.min_pixels * self.pixel_size).abs() as f64;
+        // Exact powers of the base may come out slightly too large from the logarithm
+        let exponent = min_distance.log(base);
+        let exponent = if (exponent - exponent.round()).abs() < 1e-9 {
+            exponent.round()
+        } else {
+            exponent.ceil()
+        };
+        let increment = base.powf(exponent);
+        if increment.is_finite() && increment > 0.0 {
+            DVec3::splat(increment)
+        } else {
+            // The gizmo is degenerate on screen, for example at the camera position
+            distance
+        }
+    }
+
+    /// Distance increment along the given local axis
+    pub(crate) fn snap_distance_along(&self, axis: DVec3) -> f64 {
+        self.snap_distance().dot(axis.abs())
+    }
+
+    /// Whether local orientation is used
+    pub(crate) fn local_space(&self) -> bool {
+        matches!(
+            self.orientation(),
+            GizmoOrientation::Local | GizmoOrientation::Gimbal
+        )
+    }
+
+    /// Transform orientation of the gizmo
+    pub(crate) fn orientation(&self) -> GizmoOrientation {
+        self.constraint_orientation
+            .unwrap_or_else(|| self.configured_orientation())
+    }
+
+    /// Transform orientation of the gizmo, without the orientation of an axis constraint
+    pub(crate) fn configured_orientation(&self) -> GizmoOrientation {
+        if self.modes.contains(GizmoMode::Scale) {
+            // Scaling currently only works in local orientation,
+            // so the configured orientation is ignored.
+            GizmoOrientation::Local
+        } else {
+            self.config.orientation
+        }
+    }
+
     pub(crate) fn as_transform(&self) -> Transform {
         Transform {
             scale: self.scale.into(),
@@ -262,7 +736,8 @@ impl PreparedGizmoConfig {
 }
 
 /// Operation mode of a gizmo.
-#[derive(Debug, EnumSetType)]
+#[derive(Debug, Hash, EnumSetType)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GizmoMode {
     Rotate,
     Translate,
@@ -271,6 +746,7 @@ pub enum GizmoMode {
 
 /// The point in space around which all rotations are centered.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransformPivotPoint {
     /// Pivot around the median point of targets
     #[default]
@@ -279,17 +755,278 @@ pub enum TransformPivotPoint {
     IndividualOrigins,
 }
 
+/// Increments and angles that transformations snap to while [`GizmoConfig::snapping`] is enabled.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapSettings {
+    /// Distance increments for snapping translations, along each axis of the gizmo orientation.
+    /// The view plane handle does not snap.
+    pub distance: mint::Vector3<f32>,
+    /// Adapts the distance increments to the zoom level of the camera. If set,
+    /// it is used instead of [`SnapSettings::distance`].
+    pub adaptive_distance: Option<AdaptiveSnap>,
+    /// Scale increments for snapping scalings, along each axis.
+    /// Handles that scale several axes use the smallest increment of those axes.
+    pub scale: mint::Vector3<f32>,
+    /// Angles that rotations snap to.
+    pub angle: AngleSnap,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self::uniform(
+            DEFAULT_SNAP_ANGLE,
+            DEFAULT_SNAP_DISTANCE,
+            DEFAULT_SNAP_SCALE,
+        )
+    }
+}
+
+impl SnapSettings {
+    /// Snap settings with the same increments along every axis. `angle` is in radians.
+    pub fn uniform(angle: f32, distance: f32, scale: f32) -> Self {
+        Self {
+            distance: [distance; 3].into(),
+            adaptive_distance: None,
+            scale: [scale; 3].into(),
+            angle: AngleSnap::Increment(angle),
+        }
+    }
+
+    /// Smallest scale increment of the given local axes
+    pub(crate) fn scale_along(&self, axes: DVec3) -> f64 {
+        let scale = Vec3::from(self.scale).as_dvec3();
+        DVec3::select(axes.cmpeq(DVec3::ZERO), DVec3::INFINITY, scale).min_element()
+    }
+}
+
+/// Snap increments that adapt to the zoom level of the camera. See [`SnapSettings::adaptive_distance`].
+///
+/// The increment is the smallest power of [`AdaptiveSnap::base`] that is at least
+/// [`AdaptiveSnap::min_pixels`] long on screen at the gizmo's distance from the camera,
+/// so each snap step is a few pixels long regardless of the zoom level.
+/// The increment is kept for the duration of a drag, and the active increment is given by
+/// [`Gizmo::snap_distance`](crate::Gizmo::snap_distance).
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptiveSnap {
+    /// Smallest screen distance of a single increment, in pixels
+    pub min_pixels: f32,
+    /// Increments are powers of this base. For example, `10.0` gives increments
+    /// of `0.1`, `1.0` and `10.0`, while `2.0` gives `0.5`, `1.0` and `2.0`.
+    pub base: f32,
+}
+
+impl Default for AdaptiveSnap {
+    fn default() -> Self {
+        Self {
+            min_pixels: DEFAULT_ADAPTIVE_SNAP_PIXELS,
+            base: 10.0,
+        }
+    }
+}
+
+/// Determines the angles that rotations snap to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AngleSnap {
+    /// Rotations snap to multiples of the given angle, in radians.
+    Increment(f32),
+    /// Rotations snap to the closest of the given angles, in either direction.
+    /// The angles repeat every full turn.
+    Angles(SnapAngles),
+}
+
+impl Default for AngleSnap {
+    fn default() -> Self {
+        Self::Increment(DEFAULT_SNAP_ANGLE)
+    }
+}
+
+impl AngleSnap {
+    /// Snaps the given rotation angle, in radians
+    pub(crate) fn snap(&self, angle: f64) -> f64 {
+        match self {
+            Self::Increment(increment) => round_to_interval(angle, *increment as f64),
+            Self::Angles(angles) => {
+                let turns = (angle / TAU).floor() * TAU;
+                let angle = angle - turns;
+                let closest = turn_angles(angles)
+                    .chain([TAU])
+                    .min_by(|a, b| (a - angle).abs().total_cmp(&(b - angle).abs()))
+                    .unwrap_or(angle);
+                turns + closest
+            }
+        }
+    }
+
+    /// Angles within a single turn that rotations snap to
+    pub(crate) fn ticks(&self) -> impl Iterator<Item = f64> + '_ {
+        let (increments, angles) = match self {
+            Self::Increment(increment) => {
+                let increment = *increment as f64;
+                let increments =
+                    (0..((TAU / increment) as usize + 1)).map(move |i| i as f64 * increment);
+                (Some(increments), None)
+            }
+            Self::Angles(angles) => (None, Some(turn_angles(angles))),
+        };
+
+        increments
+            .into_iter()
+            .flatten()
+            .chain(angles.into_iter().flatten())
+    }
+}
+
+/// The snap angles and their negations wrapped to a single turn, along with zero
+fn turn_angles(angles: &SnapAngles) -> impl Iterator<Item = f64> + '_ {
+    angles
+        .as_slice()
+        .iter()
+        .flat_map(|&angle| [angle as f64, -angle as f64])
+        .map(|angle| angle.rem_euclid(TAU))
+        .chain([0.0])
+}
+
+/// A list of up to [`SnapAngles::CAPACITY`] angles that rotations snap to. See [`AngleSnap::Angles`].
+///
+/// # Examples
+///
+/// ```
+/// # use transform_gizmo::config::SnapAngles;
+/// let angles = SnapAngles::from_degrees(&[15.0, 45.0, 90.0]).unwrap();
+/// assert_eq!(angles.as_slice().len(), 3);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<f32>", into = "Vec<f32>")
+)]
+pub struct SnapAngles {
+    angles: [f32; Self::CAPACITY],
+    len: usize,
+}
+
+impl SnapAngles {
+    /// Maximum number of angles in a list
+    pub const CAPACITY: usize = 16;
+
+    /// Creates a list of snap angles, given in radians.
+    ///
+    /// Returns [`GizmoConfigError::InvalidSnapAngle`] if the list is empty, has more than
+    /// [`SnapAngles::CAPACITY`] angles, or contains angles that are not positive finite numbers.
+    pub fn new(angles: &[f32]) -> Result<Self, GizmoConfigError> {
+        if angles.is_empty()
+            || angles.len() > Self::CAPACITY
+            || !angles.iter().all(|angle| angle.is_finite() && *angle > 0.0)
+        {
+            return Err(GizmoConfigError::InvalidSnapAngle);
+        }
+
+        let mut list = Self {
+            angles: [0.0; Self::CAPACITY],
+            len: angles.len(),
+        };
+        list.angles[..angles.len()].copy_from_slice(angles);
+        Ok(list)
+    }
+
+    /// Creates a list of snap angles, given in degrees. See [`SnapAngles::new`].
+    pub fn from_degrees(angles: &[f32]) -> Result<Self, GizmoConfigError> {
+        let radians: Vec<f32> = angles.iter().map(|angle| angle.to_radians()).collect();
+        Self::new(&radians)
+    }
+
+    /// The angles of the list, in radians
+    pub fn as_slice(&self) -> &[f32] {
+        &self.angles[..self.len]
+    }
+}
+
+impl TryFrom<Vec<f32>> for SnapAngles {
+    type Error = GizmoConfigError;
+
+    fn try_from(angles: Vec<f32>) -> Result<Self, Self::Error> {
+        Self::new(&angles)
+    }
+}
+
+impl From<SnapAngles> for Vec<f32> {
+    fn from(angles: SnapAngles) -> Self {
+        angles.as_slice().to_vec()
+    }
+}
+
+/// Determines how cursor movement is converted into rotation angles.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RotationInput {
+    /// The angle of the cursor around the gizmo's screen center is used.
+    Circular,
+    /// Cursor movement along the screen space tangent of the rotation arc,
+    /// at the point where the arc was grabbed, maps linearly to the angle.
+    Linear,
+    /// Circular input is used, unless the angle between the view direction and the
+    /// rotation plane is below the given threshold (in radians). The rotation arc is
+    /// then seen nearly edge-on, and linear input is used instead.
+    Auto { threshold: f32 },
+}
+
+impl Default for RotationInput {
+    fn default() -> Self {
+        Self::Auto {
+            threshold: DEFAULT_LINEAR_ROTATION_THRESHOLD,
+        }
+    }
+}
+
+/// Determines how targets are placed when dragging the view plane translation handle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SurfacePlacement {
+    /// Targets are moved along a plane facing the camera.
+    #[default]
+    Disabled,
+    /// Targets follow the scene surface under the cursor.
+    Position,
+    /// Targets follow the scene surface under the cursor, and their up (Y) axis
+    /// is aligned to the surface normal.
+    AlignToNormal,
+}
+
+/// Position that targets are aligned to along an axis. See [`Gizmo::align`](crate::Gizmo::align).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Alignment {
+    /// Smallest position of the targets along the axis
+    Min,
+    /// Center between the smallest and largest positions of the targets along the axis
+    Center,
+    /// Largest position of the targets along the axis
+    Max,
+}
+
 /// Orientation of a gizmo.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GizmoOrientation {
     /// Transformation axes are aligned to world space.
     #[default]
     Global,
     /// Transformation axes are aligned to the last target's orientation.
     Local,
+    /// Rotation axes follow the Euler angles of the last target, in [`GizmoConfig::euler_order`].
+    /// Rotating around an axis only changes the Euler angle of that axis.
+    ///
+    /// Translation and scale axes are aligned to the last target's orientation, as with
+    /// [`GizmoOrientation::Local`].
+    Gimbal,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GizmoDirection {
     /// Gizmo points in the X-direction
     X,
@@ -302,7 +1039,8 @@ pub enum GizmoDirection {
 }
 
 /// Controls the visual style of the gizmo
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GizmoVisuals {
     /// Color of the x axis
     pub x_color: Color32,
@@ -322,6 +1060,24 @@ pub struct GizmoVisuals {
     pub stroke_width: f32,
     /// Gizmo size in pixels
     pub gizmo_size: f32,
+    /// Determines whether the gizmo has a constant size on screen or in world space
+    pub sizing: GizmoSizing,
+    /// Style of the translation handles
+    pub translation_handle: HandleStyle,
+    /// Style of the scale handles
+    pub scale_handle: HandleStyle,
+    /// Style of the rotation handles
+    pub rotation_handle: RotationHandleStyle,
+    /// Shape of the plane handles
+    pub plane_shape: PlaneShape,
+    /// Overlays drawn while translating or scaling
+    pub drag_overlays: DragOverlays,
+    /// Duration of highlight transitions, in seconds.
+    /// Transitions are only animated if [`GizmoInteraction::delta_time`](crate::GizmoInteraction::delta_time) is given.
+    pub highlight_duration: f32,
+    /// Duration of fade-in and fade-out transitions of the gizmo handles, in seconds.
+    /// Transitions are only animated if [`GizmoInteraction::delta_time`](crate::GizmoInteraction::delta_time) is given.
+    pub fade_duration: f32,
 }
 
 impl Default for GizmoVisuals {
@@ -336,11 +1092,145 @@ impl Default for GizmoVisuals {
             highlight_color: None,
             stroke_width: 4.0,
             gizmo_size: 75.0,
+            sizing: GizmoSizing::default(),
+            translation_handle: HandleStyle {
+                tip: HandleTip::Triangle,
+                ..Default::default()
+            },
+            scale_handle: HandleStyle {
+                tip: HandleTip::Bar,
+                ..Default::default()
+            },
+            rotation_handle: RotationHandleStyle::default(),
+            plane_shape: PlaneShape::default(),
+            drag_overlays: DragOverlays::default(),
+            highlight_duration: 0.1,
+            fade_duration: 0.15,
+        }
+    }
+}
+
+/// Determines how the size of a gizmo is calculated.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GizmoSizing {
+    /// The gizmo has a constant size on screen, given by [`GizmoVisuals::gizmo_size`] in pixels.
+    #[default]
+    Screen,
+    /// The gizmo has a constant size in world space, and scales with distance like other objects.
+    ///
+    /// The whole gizmo, including stroke widths and interaction distances, is scaled
+    /// so that [`GizmoVisuals::gizmo_size`] pixels correspond to `size` world units.
+    World {
+        /// Size of the gizmo in world units
+        size: f32,
+        /// Minimum size of the gizmo on screen, in pixels
+        min_pixels: Option<f32>,
+        /// Maximum size of the gizmo on screen, in pixels
+        max_pixels: Option<f32>,
+    },
+}
+
+/// Shape drawn at the end of an axis handle
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HandleTip {
+    /// Flat triangle, pointing away from the gizmo center
+    #[default]
+    Triangle,
+    /// Cone, pointing away from the gizmo center
+    Cone,
+    /// Thick line segment
+    Bar,
+    /// Cube
+    Cube,
+    /// Sphere
+    Sphere,
+}
+
+/// Style of the axis handles of a gizmo mode
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandleStyle {
+    /// Shape drawn at the end of the handles
+    pub tip: HandleTip,
+    /// Length of the handles, relative to the gizmo size
+    pub length: f32,
+    /// Thickness of the handle strokes, relative to the stroke width
+    pub thickness: f32,
+}
+
+impl Default for HandleStyle {
+    fn default() -> Self {
+        Self {
+            tip: HandleTip::default(),
+            length: 1.0,
+            thickness: 1.0,
         }
     }
 }
 
+/// Line style of the rotation arcs
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArcStyle {
+    /// Single solid stroke
+    #[default]
+    Solid,
+    /// Single dashed stroke
+    Dashed,
+    /// Two thinner parallel strokes
+    Double,
+}
+
+/// Style of the rotation handles
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RotationHandleStyle {
+    /// Line style of the arcs
+    pub arc: ArcStyle,
+    /// Radius of the arcs, relative to the gizmo size
+    pub radius: f32,
+    /// Thickness of the arc strokes, relative to the stroke width
+    pub thickness: f32,
+}
+
+impl Default for RotationHandleStyle {
+    fn default() -> Self {
+        Self {
+            arc: ArcStyle::default(),
+            radius: 1.0,
+            thickness: 1.0,
+        }
+    }
+}
+
+/// Shape of the plane handles
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlaneShape {
+    /// Square, aligned to the plane axes
+    #[default]
+    Square,
+    /// Circle
+    Circle,
+}
+
+/// Feedback overlays drawn while a translation or scale is active.
+/// All overlays are disabled by default.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DragOverlays {
+    /// Infinite guide line along the constrained axis, or a grid patch on the constrained plane
+    pub guides: bool,
+    /// Marker at the position where the drag started
+    pub start_ghost: bool,
+    /// Tick marks along the constrained axis at snapping intervals. Only drawn when snapping.
+    pub snap_ticks: bool,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GizmoVisibility {
     // translation helper
     pub translation_arrow: AxisConfig,
@@ -365,3 +1255,231 @@ impl Default for GizmoVisibility {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Pos2;
+    use crate::test_utils::test_config;
+
+    fn valid_config() -> GizmoConfig {
+        test_config(GizmoConfig::default().modes)
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn snap_angles_are_validated_when_deserialized() {
+        let angles = SnapAngles::from_degrees(&[15.0, 45.0]).unwrap();
+        let json = serde_json::to_string(&angles).unwrap();
+        assert_eq!(serde_json::from_str::<SnapAngles>(&json).unwrap(), angles);
+
+        assert!(serde_json::from_str::<SnapAngles>("[]").is_err());
+        assert!(serde_json::from_str::<SnapAngles>("[0.5, -1.0]").is_err());
+        let too_many = format!("{:?}", vec![0.5; SnapAngles::CAPACITY + 1]);
+        assert!(serde_json::from_str::<SnapAngles>(&too_many).is_err());
+    }
+
+    #[test]
+    fn validate_accepts_valid_config() {
+        assert_eq!(valid_config().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_degenerate_matrices() {
+        let mut config = valid_config();
+        config.view_matrix = DMat4::from_scale(DVec3::new(1.0, 1.0, 0.0)).into();
+        assert_eq!(config.validate(), Err(GizmoConfigError::SingularViewMatrix));
+
+        // Nearly singular
+        config.view_matrix = DMat4::from_cols(
+            DVec4::X,
+            DVec4::Y,
+            DVec4::new(0.0, 1.0, 1e-20, 0.0),
+            DVec4::W,
+        )
+        .into();
+        assert_eq!(config.validate(), Err(GizmoConfigError::SingularViewMatrix));
+
+        // Finite values with an infinite determinant
+        config.view_matrix = DMat4::from_scale(DVec3::splat(1e120)).into();
+        assert_eq!(config.validate(), Err(GizmoConfigError::SingularViewMatrix));
+
+        config = valid_config();
+        config.projection_matrix.w.w = f64::NAN;
+        assert_eq!(
+            config.validate(),
+            Err(GizmoConfigError::NonFiniteProjectionMatrix)
+        );
+
+        // Nearly parallel columns
+        config.projection_matrix = DMat4::from_cols(
+            DVec4::X,
+            DVec4::new(1.0, 1e-20, 0.0, 0.0),
+            DVec4::Z,
+            DVec4::W,
+        )
+        .into();
+        assert_eq!(
+            config.validate(),
+            Err(GizmoConfigError::SingularProjectionMatrix)
+        );
+    }
+
+    #[test]
+    fn validate_accepts_matrices_of_large_scenes() {
+        // Orthographic projection over a million units has a tiny determinant
+        let mut config = valid_config();
+        config.projection_matrix = DMat4::orthographic_rh(-1e6, 1e6, -1e6, 1e6, 0.1, 1e6).into();
+        assert_eq!(config.validate(), Ok(()));
+
+        config.projection_matrix = DMat4::from_scale(DVec3::new(1e-10, 1e-10, 1.0)).into();
+        assert_eq!(config.validate(), Ok(()));
+
+        config.view_matrix = DMat4::from_scale(DVec3::splat(1e-6)).into();
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_rotation_input_threshold() {
+        let mut config = valid_config();
+        for threshold in [0.0, -0.5, f32::NAN, f32::INFINITY] {
+            config.rotation_input = RotationInput::Auto { threshold };
+            assert_eq!(
+                config.validate(),
+                Err(GizmoConfigError::InvalidRotationInputThreshold)
+            );
+        }
+
+        config.rotation_input = RotationInput::Auto { threshold: 0.1 };
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_transition_durations() {
+        let mut config = valid_config();
+        for duration in [-0.1, f32::NAN, f32::INFINITY] {
+            config.visuals.highlight_duration = duration;
+            assert_eq!(
+                config.validate(),
+                Err(GizmoConfigError::InvalidTransitionDuration)
+            );
+        }
+
+        config.visuals.highlight_duration = 0.0;
+        config.visuals.fade_duration = -1.0;
+        assert_eq!(
+            config.validate(),
+            Err(GizmoConfigError::InvalidTransitionDuration)
+        );
+
+        // Transitions can be disabled
+        config.visuals.fade_duration = 0.0;
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_world_sizing() {
+        let mut config = valid_config();
+
+        config.visuals.sizing = GizmoSizing::World {
+            size: 0.0,
+            min_pixels: None,
+            max_pixels: None,
+        };
+        assert_eq!(config.validate(), Err(GizmoConfigError::InvalidGizmoSize));
+
+        config.visuals.sizing = GizmoSizing::World {
+            size: 1.0,
+            min_pixels: Some(f32::NAN),
+            max_pixels: None,
+        };
+        assert_eq!(config.validate(), Err(GizmoConfigError::InvalidGizmoSize));
+
+        config.visuals.sizing = GizmoSizing::World {
+            size: 1.0,
+            min_pixels: Some(200.0),
+            max_pixels: Some(100.0),
+        };
+        assert_eq!(
+            config.validate(),
+            Err(GizmoConfigError::InvalidGizmoSizeRange)
+        );
+
+        config.visuals.sizing = GizmoSizing::World {
+            size: 1.0,
+            min_pixels: Some(100.0),
+            max_pixels: Some(100.0),
+        };
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn builder_returns_first_error() {
+        let result = GizmoConfig::builder()
+            .viewport(Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(0.0, 0.0)))
+            .build();
+        assert_eq!(result, Err(GizmoConfigError::InvalidViewport));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_snap_settings() {
+        let mut config = valid_config();
+
+        config.snap_settings.distance = [1.0, 0.0, 1.0].into();
+        assert_eq!(
+            config.validate(),
+            Err(GizmoConfigError::InvalidSnapDistance)
+        );
+
+        config.snap_settings = SnapSettings::default();
+        config.snap_settings.scale = [1.0, 1.0, f32::INFINITY].into();
+        assert_eq!(config.validate(), Err(GizmoConfigError::InvalidSnapScale));
+
+        config.snap_settings = SnapSettings::default();
+        config.snap_settings.angle = AngleSnap::Increment(-1.0);
+        assert_eq!(config.validate(), Err(GizmoConfigError::InvalidSnapAngle));
+
+        assert_eq!(
+            SnapAngles::new(&[]),
+            Err(GizmoConfigError::InvalidSnapAngle)
+        );
+        assert_eq!(
+            SnapAngles::new(&[0.5, f32::NAN]),
+            Err(GizmoConfigError::InvalidSnapAngle)
+        );
+        assert_eq!(
+            SnapAngles::new(&[0.1; SnapAngles::CAPACITY + 1]),
+            Err(GizmoConfigError::InvalidSnapAngle)
+        );
+    }
+
+    #[test]
+    fn angle_snap_to_closest_angle() {
+        let snap = AngleSnap::Angles(SnapAngles::from_degrees(&[45.0, 90.0]).unwrap());
+        let snapped = |degrees: f64| snap.snap(degrees.to_radians()).to_degrees();
+
+        assert!((snapped(10.0) - 0.0).abs() < 1e-4);
+        assert!((snapped(50.0) - 45.0).abs() < 1e-4);
+        assert!((snapped(80.0) - 90.0).abs() < 1e-4);
+        // Negated angles
+        assert!((snapped(-40.0) + 45.0).abs() < 1e-4);
+        // Angles repeat every full turn
+        assert!((snapped(365.0) - 360.0).abs() < 1e-4);
+        assert!((snapped(400.0) - 405.0).abs() < 1e-4);
+        assert!((snapped(330.0) - 315.0).abs() < 1e-4);
+
+        let increment = AngleSnap::Increment(15f32.to_radians());
+        assert!((increment.snap(50f64.to_radians()).to_degrees() - 45.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn scale_snap_uses_smallest_scaled_axis() {
+        let snap = SnapSettings {
+            scale: [0.5, 0.1, 0.25].into(),
+            ..Default::default()
+        };
+        assert!((snap.scale_along(DVec3::X) - 0.5).abs() < 1e-7);
+        assert!((snap.scale_along(DVec3::new(1.0, 0.0, 1.0)) - 0.25).abs() < 1e-7);
+        assert!((snap.scale_along(DVec3::ONE) - 0.1).abs() < 1e-7);
+    }
+}
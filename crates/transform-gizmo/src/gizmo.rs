@@ -1,15 +1,17 @@
-use ecolor::Rgba;
-use emath::Pos2;
 use std::ops::{Add, AddAssign, Sub};
 
 use crate::config::{
-    GizmoConfig, GizmoDirection, GizmoMode, PreparedGizmoConfig, TransformPivotPoint,
+    Alignment, Color32, GizmoConfig, GizmoConfigError, GizmoDirection, GizmoMode,
+    PreparedGizmoConfig, SurfacePlacement, TransformPivotPoint,
 };
-use crate::math::{screen_to_world, Transform};
+use crate::math::{intersect_plane, screen_to_world, world_to_screen, Pos2, Transform};
+use crate::result::{mirror_transform, GizmoFrame, GizmoHandle, GizmoResult, GizmoResultKind};
+use crate::scene::{GizmoScene, SnapPointProvider, SurfaceHit, TransformConstraint};
+use crate::shape::{primitives_to_screen, tessellate_into, DrawScratch, Primitives};
 use crate::GizmoOrientation;
-use epaint::Mesh;
 use glam::{DQuat, DVec3};
 
+use crate::subgizmo::common::{draw_snap_point, gizmo_normal};
 use crate::subgizmo::rotation::RotationParams;
 use crate::subgizmo::scale::ScaleParams;
 use crate::subgizmo::translation::TranslationParams;
@@ -28,11 +30,98 @@ pub struct Gizmo {
     config: PreparedGizmoConfig,
     /// Subgizmos used in the gizmo.
     subgizmos: Vec<SubGizmo>,
+    /// Subgizmos that were removed from the gizmo, for example
+    /// due to a mode switch, but are still fading out.
+    fading_subgizmos: Vec<SubGizmo>,
     active_subgizmo_id: Option<u64>,
 
     target_start_transforms: Vec<Transform>,
 
     gizmo_start_transform: Transform,
+
+    /// Validation problem of the latest configuration.
+    config_error: Option<GizmoConfigError>,
+
+    /// Whether snapping is inverted by [`GizmoModifiers::invert_snapping`] in the latest update.
+    invert_snapping: bool,
+    /// Whether the targets are duplicated in the current drag,
+    /// as requested by [`GizmoModifiers::duplicate`].
+    duplicate: bool,
+    /// Axis constraint of the current drag, set with [`GizmoInteraction::axis_key`].
+    axis_constraint: Option<AxisConstraint>,
+    /// Handle switch of the current drag, made by axis constraints.
+    handle_switch: Option<HandleSwitch>,
+    /// Actual and virtual pointer rays of the previous update of the current drag.
+    /// The virtual ray moves slower than the actual one with [`GizmoModifiers::precision`].
+    drag_pointer: Option<(Ray, Ray)>,
+
+    /// Snap point the gizmo was snapped to in the latest update.
+    snapped_point: Option<DVec3>,
+    /// Scratch buffer for snap point candidates.
+    snap_point_candidates: Vec<mint::Vector3<f64>>,
+    /// Scratch buffer for the world space transforms of the targets.
+    world_targets: Vec<Transform>,
+    /// Transform of the gizmo before it was moved with the constrained targets
+    /// in the latest update. See [`GizmoScene::constraint`].
+    unconstrained_transform: Option<Transform>,
+    /// World space transforms of the targets of the current drag before they were constrained.
+    unconstrained_targets: Vec<Transform>,
+
+    /// Buffers reused between draws.
+    draw_scratch: DrawScratch,
+    /// Output of the latest [`Gizmo::draw_if_changed`] call.
+    draw_cache: Option<DrawCache>,
+}
+
+/// Switch of the dragged handle in the middle of a drag, made by an axis constraint.
+#[derive(Clone, Copy, Debug)]
+struct HandleSwitch {
+    /// Subgizmo the drag was started with
+    start_subgizmo_id: u64,
+    /// World space translation of the drag before the latest switch
+    base_translation: DVec3,
+    /// Scale of the drag before the latest switch
+    base_scale: DVec3,
+}
+
+/// Cached draw output of a [`Gizmo`], along with the inputs it was tessellated from.
+#[derive(Clone, Debug, Default)]
+struct DrawCache {
+    /// State of the gizmo the primitives were drawn from
+    inputs: DrawInputs,
+    pixels_per_point: f32,
+    draw_data: GizmoDrawData,
+    /// Screen space primitives the draw data was tessellated from
+    primitives: Primitives,
+}
+
+/// State of a [`Gizmo`] that its primitives are drawn from.
+///
+/// Covers the camera, viewport and visuals of the configuration, the transform of the gizmo,
+/// and the focus, drag state and transitions of each subgizmo.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct DrawInputs {
+    config: PreparedGizmoConfig,
+    subgizmos: Vec<SubGizmo>,
+    fading_subgizmos: Vec<SubGizmo>,
+    snapped_point: Option<DVec3>,
+}
+
+impl DrawInputs {
+    fn matches(&self, gizmo: &Gizmo) -> bool {
+        self.config == gizmo.config
+            && self.subgizmos == gizmo.subgizmos
+            && self.fading_subgizmos == gizmo.fading_subgizmos
+            && self.snapped_point == gizmo.snapped_point
+    }
+
+    /// Replaces the inputs with the current state of the gizmo, reusing the buffers.
+    fn update(&mut self, gizmo: &Gizmo) {
+        self.config = gizmo.config;
+        self.subgizmos.clone_from(&gizmo.subgizmos);
+        self.fading_subgizmos.clone_from(&gizmo.fading_subgizmos);
+        self.snapped_point = gizmo.snapped_point;
+    }
 }
 
 impl Gizmo {
@@ -48,13 +137,28 @@ impl Gizmo {
         &self.config
     }
 
+    /// Problem found when validating the latest configuration given to the gizmo,
+    /// with [`Gizmo::new`] or [`Gizmo::update_config`]. See [`GizmoConfig::validate`].
+    ///
+    /// Useful for finding integration bugs, for example when the gizmo is not
+    /// drawn or cannot be interacted with.
+    pub fn last_error(&self) -> Option<GizmoConfigError> {
+        self.config_error
+    }
+
     /// Updates the configuration used by the gizmo.
+    ///
+    /// The configuration is validated, and any problem found is available from [`Gizmo::last_error`].
     pub fn update_config(&mut self, config: GizmoConfig) {
+        self.config_error = config.validate().err();
+
         if config.modes != self.config.modes
             || config.gizmo_visibility != self.config.gizmo_visibility
         {
-            self.subgizmos.clear();
+            self.fading_subgizmos.append(&mut self.subgizmos);
             self.active_subgizmo_id = None;
+            self.clear_axis_constraint();
+            self.config.drag_snap_distance = None;
         }
 
         self.config.update_for_config(config);
@@ -81,6 +185,18 @@ impl Gizmo {
         self.subgizmos.iter().any(|subgizmo| subgizmo.is_focused())
     }
 
+    /// Is a highlight or fade transition of this gizmo still in progress
+    /// after the latest [`Gizmo::update`] call.
+    ///
+    /// Applications that only redraw on input should keep updating and drawing
+    /// the gizmo while this is true, so that the transitions are not left unfinished.
+    pub fn is_animating(&self) -> bool {
+        self.subgizmos
+            .iter()
+            .chain(&self.fading_subgizmos)
+            .any(|subgizmo| subgizmo.is_animating())
+    }
+
     /// Updates the gizmo based on given interaction information.
     ///
     /// # Examples
@@ -97,7 +213,8 @@ impl Gizmo {
     /// let interaction = GizmoInteraction {
     ///     cursor_pos,
     ///     drag_started,
-    ///     dragging
+    ///     dragging,
+    ///     ..Default::default()
     /// };
     ///
     /// if let Some((_result, new_transforms)) = gizmo.update(interaction, &transforms) {
@@ -118,24 +235,496 @@ impl Gizmo {
         interaction: GizmoInteraction,
         targets: &[Transform],
     ) -> Option<(GizmoResult, Vec<Transform>)> {
-        if !self.config.viewport.is_finite() {
+        self.update_with_scene(interaction, targets, GizmoScene::default())
+    }
+
+    /// Updates the gizmo based on given interaction information, like [`Gizmo::update`].
+    ///
+    /// The scene gives optional queries and constraints that are used during the update,
+    /// such as surfaces to place the targets on, snap points and parent hierarchies.
+    /// See [`GizmoScene`].
+    pub fn update_with_scene(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &[Transform],
+        scene: GizmoScene<'_>,
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        let result = self.update_scene(interaction, targets, scene)?;
+
+        let mut transforms = targets.to_vec();
+        self.transform_targets(result, &mut transforms, scene);
+
+        Some((result, transforms))
+    }
+
+    /// Updates the gizmo based on given interaction information, like [`Gizmo::update`].
+    ///
+    /// Instead of allocating new transforms, the given targets are updated in place
+    /// when any of the subgizmos is being dragged.
+    ///
+    /// Returns the result of the interaction.
+    pub fn update_into(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &mut [Transform],
+    ) -> Option<GizmoResult> {
+        self.update_into_with_scene(interaction, targets, GizmoScene::default())
+    }
+
+    /// Updates the given targets in place, like [`Gizmo::update_into`],
+    /// using the queries and constraints of the scene, like [`Gizmo::update_with_scene`].
+    pub fn update_into_with_scene(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &mut [Transform],
+        scene: GizmoScene<'_>,
+    ) -> Option<GizmoResult> {
+        let result = self.update_scene(interaction, targets, scene)?;
+
+        self.transform_targets(result, targets, scene);
+
+        Some(result)
+    }
+
+    /// Snap point the gizmo was snapped to in the latest update, if any.
+    pub fn snapped_point(&self) -> Option<mint::Vector3<f64>> {
+        self.snapped_point.map(Into::into)
+    }
+
+    /// Distance increments that translations snap to, along each axis of the gizmo orientation.
+    ///
+    /// With [`SnapSettings::adaptive_distance`](crate::config::SnapSettings::adaptive_distance),
+    /// the increments depend on the zoom level of the camera, and are kept for the duration of a drag.
+    pub fn snap_distance(&self) -> mint::Vector3<f64> {
+        self.config.snap_distance().into()
+    }
+
+    /// Aligns the origins of the targets along an axis of the current gizmo orientation.
+    ///
+    /// The targets are moved along the axis to the minimum, center or maximum of their
+    /// origins along it, as given by `alignment`.
+    ///
+    /// Returns the result with the aligned transforms, like [`Gizmo::update`].
+    /// The result is a [`GizmoResultKind::Arrangement`] around the median point of the targets.
+    /// [`None`] is returned if there are less than two targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use transform_gizmo::prelude::*;
+    /// # use transform_gizmo::config::Alignment;
+    /// # use transform_gizmo::math::Transform;
+    /// let gizmo = Gizmo::default();
+    /// let mut transforms = vec![
+    ///     Transform::from_scale_rotation_translation([1.0; 3], [0.0, 0.0, 0.0, 1.0], [0.0, 0.0, 0.0]),
+    ///     Transform::from_scale_rotation_translation([1.0; 3], [0.0, 0.0, 0.0, 1.0], [2.0, 1.0, 0.0]),
+    /// ];
+    ///
+    /// if let Some((_result, new_transforms)) =
+    ///     gizmo.align(&transforms, GizmoDirection::X, Alignment::Max)
+    /// {
+    ///     transforms = new_transforms;
+    /// }
+    ///
+    /// assert_eq!(transforms[0].translation.x, 2.0);
+    /// ```
+    pub fn align(
+        &self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+        alignment: Alignment,
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        self.align_with_scene(targets, axis, alignment, GizmoScene::default())
+    }
+
+    /// Aligns the targets like [`Gizmo::align`], using the parents and constraint of the scene.
+    ///
+    /// The targets are aligned in world space, and returned relative to their parents.
+    pub fn align_with_scene(
+        &self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+        alignment: Alignment,
+        scene: GizmoScene<'_>,
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        if targets.len() < 2 {
+            return None;
+        }
+
+        Some(Self::arrange_in_scene(targets, scene, |targets| {
+            let axis = self.arrange_axis(targets, axis);
+            let (min, max) = targets
+                .iter()
+                .map(|target| DVec3::from(target.translation).dot(axis))
+                .fold((f64::MAX, f64::MIN), |(min, max), value| {
+                    (min.min(value), max.max(value))
+                });
+
+            let aligned = match alignment {
+                Alignment::Min => min,
+                Alignment::Center => (min + max) / 2.0,
+                Alignment::Max => max,
+            };
+
+            self.arrange_targets(targets, axis, |_| aligned)
+        }))
+    }
+
+    /// Distributes the origins of the targets evenly along an axis of the current gizmo orientation.
+    ///
+    /// The targets with the smallest and largest origins along the axis stay in place,
+    /// and the others are moved along the axis so that they are evenly spaced between them,
+    /// keeping their order.
+    ///
+    /// Returns the result with the distributed transforms, like [`Gizmo::update`].
+    /// The result is a [`GizmoResultKind::Arrangement`] around the median point of the targets.
+    /// [`None`] is returned if there are less than three targets.
+    pub fn distribute(
+        &self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        self.distribute_with_scene(targets, axis, GizmoScene::default())
+    }
+
+    /// Distributes the targets like [`Gizmo::distribute`], using the parents and constraint
+    /// of the scene.
+    ///
+    /// The targets are distributed in world space, and returned relative to their parents.
+    pub fn distribute_with_scene(
+        &self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+        scene: GizmoScene<'_>,
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        if targets.len() < 3 {
+            return None;
+        }
+
+        Some(Self::arrange_in_scene(targets, scene, |targets| {
+            let axis = self.arrange_axis(targets, axis);
+
+            let mut order = (0..targets.len()).collect::<Vec<_>>();
+            let position = |index: usize| DVec3::from(targets[index].translation).dot(axis);
+            order.sort_by(|&a, &b| position(a).total_cmp(&position(b)));
+
+            let min = position(order[0]);
+            let step = (position(order[order.len() - 1]) - min) / (targets.len() - 1) as f64;
+
+            let mut ranks = vec![0; targets.len()];
+            for (rank, &index) in order.iter().enumerate() {
+                ranks[index] = rank;
+            }
+
+            self.arrange_targets(targets, axis, |index| min + step * ranks[index] as f64)
+        }))
+    }
+
+    /// Mirrors the targets along an axis of the current gizmo orientation.
+    ///
+    /// The target origins are mirrored through the plane that passes through their median point
+    /// and is perpendicular to the axis. The orientation of each target is mirrored as well.
+    /// As a mirroring cannot be expressed as a rotation, one of the scale components of each target
+    /// is negated. The component whose local axis is the closest to the mirroring axis is chosen,
+    /// so that the rotation is left unchanged when the target is aligned with the axis.
+    ///
+    /// Returns the result with the mirrored transforms, like [`Gizmo::update`].
+    /// The result is a [`GizmoResultKind::Flip`] around the median point of the targets.
+    /// [`None`] is returned if there are no targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use transform_gizmo::prelude::*;
+    /// # use transform_gizmo::math::Transform;
+    /// let gizmo = Gizmo::default();
+    /// let transforms = vec![Transform::default()];
+    ///
+    /// let (_result, new_transforms) = gizmo.flip(&transforms, GizmoDirection::X).unwrap();
+    ///
+    /// assert_eq!(new_transforms[0].scale.x, -1.0);
+    /// ```
+    pub fn flip(
+        &self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        self.flip_with_scene(targets, axis, GizmoScene::default())
+    }
+
+    /// Mirrors the targets like [`Gizmo::flip`], using the parents and constraint of the scene.
+    ///
+    /// The targets are mirrored in world space, and returned relative to their parents.
+    pub fn flip_with_scene(
+        &self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+        scene: GizmoScene<'_>,
+    ) -> Option<(GizmoResult, Vec<Transform>)> {
+        if targets.is_empty() {
             return None;
         }
 
+        Some(Self::arrange_in_scene(targets, scene, |targets| {
+            let axis = self.arrange_axis(targets, axis);
+            let result = self.arrange_result(targets, GizmoResultKind::Flip { axis: axis.into() });
+
+            let new_targets = targets
+                .iter()
+                .map(|target| mirror_transform(target, axis, result.pivot.into()))
+                .collect();
+
+            (result, new_targets)
+        }))
+    }
+
+    /// Arranges the world space transforms of the targets in the scene with `arrange`,
+    /// applies the constraint of the scene, and returns the targets relative to their parents.
+    fn arrange_in_scene(
+        targets: &[Transform],
+        scene: GizmoScene<'_>,
+        arrange: impl FnOnce(&[Transform]) -> (GizmoResult, Vec<Transform>),
+    ) -> (GizmoResult, Vec<Transform>) {
+        let world_targets = targets
+            .iter()
+            .enumerate()
+            .map(|(index, &target)| scene.hierarchy_target(index, target).world_transform())
+            .collect::<Vec<_>>();
+
+        let (result, mut new_targets) = arrange(&world_targets);
+
+        for (index, (new_target, &target)) in new_targets.iter_mut().zip(targets).enumerate() {
+            if let Some(constraint) = scene.constraint {
+                *new_target = constraint.constrain(index, &world_targets[index], *new_target);
+            }
+            *new_target = scene
+                .hierarchy_target(index, target)
+                .local_transform(*new_target);
+        }
+
+        (result, new_targets)
+    }
+
+    /// World space direction of the given axis, in the orientation the gizmo has for the targets
+    fn arrange_axis(&self, targets: &[Transform], axis: GizmoDirection) -> DVec3 {
+        let mut config = self.config;
+        config.update_for_targets(targets);
+        gizmo_normal(&config, axis).normalize()
+    }
+
+    /// Moves each target along the axis, to the position given by `position_on_axis`
+    /// for the target index.
+    fn arrange_targets(
+        &self,
+        targets: &[Transform],
+        axis: DVec3,
+        position_on_axis: impl Fn(usize) -> f64,
+    ) -> (GizmoResult, Vec<Transform>) {
+        let new_targets = targets
+            .iter()
+            .enumerate()
+            .map(|(index, target)| {
+                let translation = DVec3::from(target.translation);
+                let current = translation.dot(axis);
+
+                Transform {
+                    translation: (translation + axis * (position_on_axis(index) - current)).into(),
+                    ..*target
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let result =
+            self.arrange_result(targets, GizmoResultKind::Arrangement { axis: axis.into() });
+
+        (result, new_targets)
+    }
+
+    /// Result of an arrangement of the targets, pivoting around the median point of the
+    /// given targets instead of where the gizmo was in the latest update
+    fn arrange_result(&self, targets: &[Transform], kind: GizmoResultKind) -> GizmoResult {
+        let mut config = self.config;
+        config.update_for_targets(targets);
+
+        GizmoResult {
+            pivot: config.translation.into(),
+            ..self.result(None, kind)
+        }
+    }
+
+    /// Updates the gizmo with the world space transforms of the targets in the scene.
+    fn update_scene(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &[Transform],
+        scene: GizmoScene<'_>,
+    ) -> Option<GizmoResult> {
+        let mut world_targets = std::mem::take(&mut self.world_targets);
+        world_targets.clear();
+        world_targets.extend(
+            targets
+                .iter()
+                .enumerate()
+                .map(|(index, &target)| scene.hierarchy_target(index, target).world_transform()),
+        );
+
+        let result = self.update_interaction(interaction, &world_targets, scene);
+
+        self.world_targets = world_targets;
+        result
+    }
+
+    /// Transforms the targets with the given result, and applies the constraint of the scene.
+    ///
+    /// Uses the world space transforms of the targets from the latest [`Gizmo::update_scene`].
+    fn transform_targets(
+        &mut self,
+        result: GizmoResult,
+        targets: &mut [Transform],
+        scene: GizmoScene<'_>,
+    ) {
+        let mut world_targets = std::mem::take(&mut self.world_targets);
+
+        for (index, (transform, start_transform)) in world_targets
+            .iter_mut()
+            .zip(&self.target_start_transforms)
+            .enumerate()
+        {
+            // Constrained targets continue from where they would be without the constraint
+            if let Some(&unconstrained) = self.unconstrained_targets.get(index) {
+                *transform = unconstrained;
+            }
+            *transform = self.transform_with_result(result, transform, start_transform);
+        }
+
+        if let Some(constraint) = scene.constraint {
+            self.unconstrained_targets.clear();
+            self.unconstrained_targets.extend_from_slice(&world_targets);
+            self.constrain_targets(&mut world_targets, constraint);
+        }
+
+        for (index, (target, &world_transform)) in
+            targets.iter_mut().zip(&world_targets).enumerate()
+        {
+            *target = scene
+                .hierarchy_target(index, *target)
+                .local_transform(world_transform);
+        }
+
+        self.world_targets = world_targets;
+    }
+
+    /// Applies the constraint to the updated targets, and moves the gizmo along with them.
+    ///
+    /// The gizmo is only moved for drawing. Like the targets, the next update continues the drag
+    /// from the unconstrained transform of the gizmo, so that the constraints do not accumulate.
+    fn constrain_targets(
+        &mut self,
+        targets: &mut [Transform],
+        constraint: &dyn TransformConstraint,
+    ) {
+        let mut changed = false;
+        let mut translation_offset = DVec3::ZERO;
+        let mut scale_offset = DVec3::ZERO;
+        let mut rotation_offset = DQuat::IDENTITY;
+        let last_index = targets.len().saturating_sub(1);
+
+        for (index, (transform, start_transform)) in targets
+            .iter_mut()
+            .zip(&self.target_start_transforms)
+            .enumerate()
+        {
+            let constrained = constraint.constrain(index, start_transform, *transform);
+            if constrained == *transform {
+                continue;
+            }
+
+            changed = true;
+            translation_offset +=
+                DVec3::from(constrained.translation) - DVec3::from(transform.translation);
+            scale_offset += DVec3::from(constrained.scale) - DVec3::from(transform.scale);
+            // The gizmo is oriented like the last target
+            if index == last_index {
+                rotation_offset =
+                    DQuat::from(constrained.rotation) * DQuat::from(transform.rotation).inverse();
+            }
+
+            *transform = constrained;
+        }
+
+        if !changed {
+            return;
+        }
+
+        let unconstrained_transform = self.config.as_transform();
+        let target_count = targets.len() as f64;
+        self.config.update_transform(Transform {
+            scale: (self.config.scale + scale_offset / target_count).into(),
+            rotation: (rotation_offset * self.config.rotation).into(),
+            translation: (self.config.translation + translation_offset / target_count).into(),
+        });
+        self.unconstrained_transform = Some(unconstrained_transform);
+
+        // Draw the subgizmos where the constrained targets are
+        let subgizmo_config = self.subgizmo_config();
+        for subgizmo in &mut self.subgizmos {
+            subgizmo.update_config(subgizmo_config);
+        }
+    }
+
+    fn update_interaction(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &[Transform],
+        scene: GizmoScene<'_>,
+    ) -> Option<GizmoResult> {
+        self.snapped_point = None;
+
+        // Continue the drag from where the gizmo would be without the constraint
+        if let Some(transform) = self.unconstrained_transform.take() {
+            self.config.update_transform(transform);
+        }
+
+        if !self.config.viewport.is_finite() {
+            return None;
+        }
         // Update the gizmo based on the given target transforms,
         // unless the gizmo is currently being interacted with.
         if self.active_subgizmo_id.is_none() {
             self.config.update_for_targets(targets);
         }
 
+        self.invert_snapping = interaction.modifiers.invert_snapping;
+        let subgizmo_config = self.subgizmo_config();
+
         for subgizmo in &mut self.subgizmos {
             // Update current configuration to each subgizmo.
-            subgizmo.update_config(self.config);
+            subgizmo.update_config(subgizmo_config);
             // All subgizmos are initially considered unfocused.
             subgizmo.set_focused(false);
         }
 
-        let pointer_ray = self.pointer_ray(Pos2::from(interaction.cursor_pos));
+        let mut pointer_ray = match interaction.ray {
+            Some(ray) => self.world_ray(ray.origin.into(), ray.direction.into()),
+            None => self.pointer_ray(Pos2::from(interaction.cursor_pos)),
+        };
+        pointer_ray = self.precise_pointer_ray(pointer_ray, interaction);
+
+        // Scene surfaces are only used while dragging the view plane translation handle
+        if let Some(surface) = scene.surface {
+            let surface_handle = GizmoHandle::Plane {
+                mode: GizmoMode::Translate,
+                direction: GizmoDirection::View,
+            };
+            if self.config.surface_placement != SurfacePlacement::Disabled
+                && self.active_subgizmo().map(SubGizmo::handle) == Some(surface_handle)
+            {
+                pointer_ray.surface_hit = surface.raycast(GizmoRay {
+                    origin: pointer_ray.origin.into(),
+                    direction: pointer_ray.direction.into(),
+                });
+            }
+        }
 
         // If there is no active subgizmo, find which one of them
         // is under the mouse pointer, if any.
@@ -146,99 +735,553 @@ impl Gizmo {
                 // If we started dragging from one of the subgizmos, mark it as active.
                 if interaction.drag_started {
                     self.active_subgizmo_id = Some(subgizmo.id());
-                    self.target_start_transforms = targets.to_vec();
+                    self.target_start_transforms.clear();
+                    self.target_start_transforms.extend_from_slice(targets);
+                    self.unconstrained_targets.clear();
                     self.gizmo_start_transform = self.config.as_transform();
+                    self.duplicate = interaction.modifiers.duplicate;
+                    self.config.drag_snap_distance = Some(self.config.snap_distance());
                 }
             }
         }
 
+        if let (Some(key), true) = (interaction.axis_key, interaction.dragging) {
+            self.press_axis_key(key, pointer_ray);
+        }
+
         let mut result = None;
+        let handle_switch = self.handle_switch;
 
         if let Some(subgizmo) = self.active_subgizmo_mut() {
             if interaction.dragging {
                 subgizmo.set_active(true);
                 subgizmo.set_focused(true);
-                result = subgizmo.update(pointer_ray);
+                let handle = subgizmo.handle();
+                result = subgizmo.update(pointer_ray).map(|kind| (handle, kind));
+
+                if let (Some(handle_switch), Some((handle, kind))) = (handle_switch, result) {
+                    result = Some((handle, self.switched_result_kind(kind, handle_switch)));
+                }
+
+                if let (Some(snap_points), Some((handle, kind))) = (scene.snap_points, result) {
+                    result = Some((handle, self.snap_to_points(kind, snap_points)));
+                }
             } else {
                 subgizmo.set_active(false);
                 subgizmo.set_focused(false);
                 self.active_subgizmo_id = None;
+                self.clear_axis_constraint();
+                self.config.drag_snap_distance = None;
             }
         }
 
-        let Some(result) = result else {
+        self.animate(interaction.delta_time);
+
+        let Some((handle, kind)) = result else {
             // No interaction, no result.
 
             self.config.update_for_targets(targets);
 
+            let subgizmo_config = self.subgizmo_config();
             for subgizmo in &mut self.subgizmos {
-                subgizmo.update_config(self.config);
+                subgizmo.update_config(subgizmo_config);
             }
 
             return None;
         };
 
+        let result = GizmoResult {
+            duplicate: self.duplicate,
+            ..self.result(Some(handle), kind)
+        };
         self.update_config_with_result(result);
 
-        let updated_targets =
-            self.update_transforms_with_result(result, targets, &self.target_start_transforms);
+        Some(result)
+    }
 
-        Some((result, updated_targets))
+    /// Axis constraint of the current drag, if any. See [`GizmoInteraction::axis_key`].
+    pub fn axis_constraint(&self) -> Option<AxisConstraint> {
+        self.axis_constraint
     }
 
-    /// Return all the necessary data to draw the latest gizmo interaction.
-    ///
-    /// The gizmo draw data consists of vertices in viewport coordinates.
-    pub fn draw(&self) -> GizmoDrawData {
-        if !self.config.viewport.is_finite() {
-            return GizmoDrawData::default();
+    /// Constrains the current drag to the axis or plane of the pressed key,
+    /// by switching the dragged handle to the corresponding subgizmo.
+    fn press_axis_key(&mut self, key: AxisKey, ray: Ray) {
+        let Some(active_subgizmo) = self.active_subgizmo_mut() else {
+            return;
+        };
+        if key.direction == GizmoDirection::View {
+            return;
         }
 
-        let mut draw_data = GizmoDrawData::default();
-        for subgizmo in &self.subgizmos {
-            if self.active_subgizmo_id.is_none() || subgizmo.is_active() {
-                draw_data += subgizmo.draw();
+        let active_id = active_subgizmo.id();
+        let mode = match active_subgizmo.handle() {
+            GizmoHandle::Axis { mode, .. } | GizmoHandle::Plane { mode, .. } => mode,
+            GizmoHandle::Arcball => GizmoMode::Rotate,
+        };
+
+        // Rotations can only be constrained to an axis
+        let exclude = key.exclude && mode != GizmoMode::Rotate;
+        let orientation = self.config.configured_orientation();
+        // Scaling only works in local orientation
+        let other_orientation = match (mode, orientation) {
+            (GizmoMode::Scale, _) => None,
+            (_, GizmoOrientation::Global) => Some(GizmoOrientation::Local),
+            _ => Some(GizmoOrientation::Global),
+        };
+
+        // Pressing the same key again switches to the other orientation, and then off
+        let constraint = match self.axis_constraint {
+            Some(constraint)
+                if constraint.direction == key.direction && constraint.exclude == exclude =>
+            {
+                other_orientation
+                    .filter(|_| constraint.orientation == orientation)
+                    .map(|orientation| AxisConstraint {
+                        orientation,
+                        ..constraint
+                    })
+            }
+            _ => Some(AxisConstraint {
+                direction: key.direction,
+                exclude,
+                orientation,
+            }),
+        };
+
+        let start_subgizmo_id = self
+            .handle_switch
+            .map_or(active_id, |handle_switch| handle_switch.start_subgizmo_id);
+        let subgizmo_id = match constraint {
+            Some(constraint) => {
+                let handle = if exclude {
+                    GizmoHandle::Plane {
+                        mode,
+                        direction: constraint.direction,
+                    }
+                } else {
+                    GizmoHandle::Axis {
+                        mode,
+                        direction: constraint.direction,
+                    }
+                };
+
+                // Handles hidden by the gizmo visibility can not be constrained to
+                let Some(subgizmo) = self
+                    .subgizmos
+                    .iter()
+                    .find(|subgizmo| subgizmo.handle() == handle)
+                else {
+                    return;
+                };
+                subgizmo.id()
+            }
+            None => start_subgizmo_id,
+        };
+
+        let start_scale = DVec3::from(self.gizmo_start_transform.scale);
+        self.handle_switch = Some(HandleSwitch {
+            start_subgizmo_id,
+            base_translation: self.config.translation
+                - DVec3::from(self.gizmo_start_transform.translation),
+            base_scale: DVec3::select(
+                start_scale.cmpeq(DVec3::ZERO),
+                DVec3::ONE,
+                self.config.scale / start_scale,
+            ),
+        });
+        self.axis_constraint = constraint;
+        self.config.constraint_orientation = constraint.map(|constraint| constraint.orientation);
+
+        let subgizmo_config = self.subgizmo_config();
+        for subgizmo in &mut self.subgizmos {
+            subgizmo.update_config(subgizmo_config);
+            if subgizmo.id() == active_id {
+                subgizmo.set_active(false);
+                subgizmo.set_focused(false);
             }
         }
 
-        draw_data
+        self.active_subgizmo_id = Some(subgizmo_id);
+        if let Some(subgizmo) = self.active_subgizmo_mut() {
+            subgizmo.resume(ray);
+        }
     }
 
-    fn active_subgizmo_mut(&mut self) -> Option<&mut SubGizmo> {
-        self.active_subgizmo_id.and_then(|id| {
-            self.subgizmos
-                .iter_mut()
-                .find(|subgizmo| subgizmo.id() == id)
-        })
+    /// Continues the totals of the drag from before the latest handle switch.
+    fn switched_result_kind(
+        &self,
+        kind: GizmoResultKind,
+        handle_switch: HandleSwitch,
+    ) -> GizmoResultKind {
+        let base_translation = if self.config.local_space() {
+            self.config.rotation.inverse() * handle_switch.base_translation
+        } else {
+            handle_switch.base_translation
+        };
+
+        match kind {
+            GizmoResultKind::Translation { delta, total } => GizmoResultKind::Translation {
+                delta,
+                total: (base_translation + DVec3::from(total)).into(),
+            },
+            GizmoResultKind::Scale { delta, total } => GizmoResultKind::Scale {
+                delta,
+                total: (handle_switch.base_scale * DVec3::from(total)).into(),
+            },
+            GizmoResultKind::Surface {
+                delta,
+                total,
+                rotation,
+                normal,
+            } => GizmoResultKind::Surface {
+                delta,
+                total: (handle_switch.base_translation + DVec3::from(total)).into(),
+                rotation,
+                normal,
+            },
+            kind => kind,
+        }
+    }
+
+    /// Ends the axis constraint of the current drag, if any.
+    fn clear_axis_constraint(&mut self) {
+        self.axis_constraint = None;
+        self.handle_switch = None;
+        self.config.constraint_orientation = None;
+    }
+
+    /// Configuration given to the subgizmos, with snapping inverted
+    /// by [`GizmoModifiers::invert_snapping`].
+    fn subgizmo_config(&self) -> PreparedGizmoConfig {
+        let mut config = self.config;
+        config.snapping ^= self.invert_snapping;
+        config
+    }
+
+    /// Slows down the pointer while dragging with [`GizmoModifiers::precision`].
+    ///
+    /// The transformation follows a virtual pointer that moves a fraction of the actual pointer
+    /// movement while precision is held, so releasing or pressing it does not make the targets jump.
+    /// With a cursor, the virtual pointer is a cursor position. With a world space
+    /// [`GizmoInteraction::ray`], the origin and direction of the ray are scaled instead.
+    fn precise_pointer_ray(&mut self, ray: Ray, interaction: GizmoInteraction) -> Ray {
+        if !interaction.dragging || interaction.drag_started {
+            self.drag_pointer = interaction.dragging.then_some((ray, ray));
+            return ray;
+        }
+
+        let Some((last_ray, last_virtual)) = self.drag_pointer else {
+            self.drag_pointer = Some((ray, ray));
+            return ray;
+        };
+
+        let factor = if interaction.modifiers.precision {
+            self.config.precision_factor
+        } else {
+            1.0
+        };
+
+        let virtual_ray = if interaction.ray.is_some() {
+            let world_factor = f64::from(factor);
+            let origin = last_virtual.origin + (ray.origin - last_ray.origin) * world_factor;
+            let direction =
+                last_virtual.direction + (ray.direction - last_ray.direction) * world_factor;
+            if origin == ray.origin && direction == ray.direction {
+                ray
+            } else {
+                self.world_ray(origin, direction)
+            }
+        } else {
+            let cursor = last_virtual.screen_pos + (ray.screen_pos - last_ray.screen_pos) * factor;
+            if cursor == ray.screen_pos {
+                ray
+            } else {
+                self.pointer_ray(cursor)
+            }
+        };
+        self.drag_pointer = Some((ray, virtual_ray));
+
+        if self.active_subgizmo_id.is_none() {
+            ray
+        } else {
+            virtual_ray
+        }
+    }
+
+    /// Result of the given transformation, with the current pivot and orientation of the gizmo
+    fn result(&self, handle: Option<GizmoHandle>, kind: GizmoResultKind) -> GizmoResult {
+        let local_frame = |local: bool| {
+            if local {
+                GizmoFrame::Target
+            } else {
+                GizmoFrame::World
+            }
+        };
+
+        let identity = Transform::default();
+        let (frame, delta) = match kind {
+            GizmoResultKind::Rotation {
+                axis,
+                delta,
+                is_view_axis,
+                ..
+            } => (
+                local_frame(self.config.orientation() == GizmoOrientation::Local && !is_view_axis),
+                Transform {
+                    rotation: DQuat::from_axis_angle(axis.into(), delta).into(),
+                    ..identity
+                },
+            ),
+            GizmoResultKind::Translation { delta, .. } => (
+                local_frame(self.config.local_space()),
+                Transform {
+                    translation: delta,
+                    ..identity
+                },
+            ),
+            GizmoResultKind::Scale { delta, .. } => (
+                GizmoFrame::Target,
+                Transform {
+                    scale: delta,
+                    ..identity
+                },
+            ),
+            GizmoResultKind::Arcball { delta, .. } => (
+                GizmoFrame::World,
+                Transform {
+                    rotation: delta,
+                    ..identity
+                },
+            ),
+            GizmoResultKind::Surface {
+                delta, rotation, ..
+            } => {
+                // Rotation since the previous update, which the gizmo already has
+                let previous_rotation = self.config.rotation
+                    * DQuat::from(self.gizmo_start_transform.rotation).inverse();
+
+                (
+                    GizmoFrame::World,
+                    Transform {
+                        rotation: (DQuat::from(rotation) * previous_rotation.inverse()).into(),
+                        translation: delta,
+                        ..identity
+                    },
+                )
+            }
+            // Neither a mirroring nor a per-target arrangement can be expressed as a delta transform
+            GizmoResultKind::Flip { .. } | GizmoResultKind::Arrangement { .. } => {
+                (GizmoFrame::World, identity)
+            }
+        };
+
+        GizmoResult {
+            kind,
+            handle,
+            duplicate: false,
+            pivot_point: self.config.pivot_point,
+            pivot: self.config.translation.into(),
+            orientation: self.config.orientation(),
+            frame,
+            delta,
+        }
+    }
+
+    /// Return all the necessary data to draw the latest gizmo interaction.
+    ///
+    /// The gizmo draw data consists of vertices in viewport coordinates.
+    pub fn draw(&self) -> GizmoDrawData {
+        let mut draw_data = GizmoDrawData::default();
+        self.draw_with_scratch(&mut DrawScratch::default(), &mut draw_data);
+        draw_data
+    }
+
+    /// Writes all the necessary data to draw the latest gizmo interaction
+    /// to the given draw data, like [`Gizmo::draw`].
+    ///
+    /// Previous contents of the draw data are cleared, but its buffers are reused.
+    /// The intermediate buffers used for drawing are kept in the gizmo and reused as well,
+    /// so that redrawing a gizmo does not allocate.
+    pub fn draw_into(&mut self, draw_data: &mut GizmoDrawData) {
+        let mut scratch = std::mem::take(&mut self.draw_scratch);
+        self.draw_with_scratch(&mut scratch, draw_data);
+        self.draw_scratch = scratch;
+    }
+
+    /// Return the data to draw the latest gizmo interaction, like [`Gizmo::draw`],
+    /// but only if it changed since the previous call.
+    ///
+    /// [`None`] is returned when the gizmo would be drawn exactly as before, for example
+    /// when the camera, targets and configuration are unchanged and no transition is
+    /// in progress. Integrations can use this to skip re-uploading buffers for idle gizmos.
+    ///
+    /// Nothing is drawn if the state the gizmo is drawn from is unchanged since the previous call.
+    /// Otherwise the primitives are drawn again, but only tessellated if they changed.
+    pub fn draw_if_changed(&mut self) -> Option<&GizmoDrawData> {
+        if let Some(cache) = &self.draw_cache {
+            if cache.inputs.matches(self) {
+                return None;
+            }
+        }
+
+        let pixels_per_point = self.config.pixels_per_point;
+        let (mut cache, cached) = match self.draw_cache.take() {
+            Some(cache) => (cache, true),
+            None => (DrawCache::default(), false),
+        };
+
+        let mut scratch = std::mem::take(&mut self.draw_scratch);
+        self.draw_screen_primitives(&mut scratch.world, &mut scratch.screen);
+
+        let changed = !cached
+            || cache.pixels_per_point != pixels_per_point
+            || cache.primitives.primitives != scratch.screen.primitives;
+        if changed {
+            std::mem::swap(&mut cache.primitives, &mut scratch.screen);
+            tessellate_into(
+                &cache.primitives.primitives,
+                pixels_per_point,
+                &mut scratch.tessellation,
+                &mut cache.draw_data,
+            );
+            cache.pixels_per_point = pixels_per_point;
+        }
+        self.draw_scratch = scratch;
+        cache.inputs.update(self);
+
+        let cache = self.draw_cache.insert(cache);
+        changed.then_some(&cache.draw_data)
+    }
+
+    /// Return the primitives used to draw the latest gizmo interaction,
+    /// before they are tessellated to triangles.
+    ///
+    /// Useful for renderers that want to draw the lines and polygons
+    /// of the gizmo with their own styling.
+    pub fn draw_primitives(&self, space: PrimitiveSpace) -> Vec<GizmoPrimitive> {
+        let mut world = Primitives::default();
+
+        match space {
+            PrimitiveSpace::World => {
+                self.draw_world_primitives(&mut world);
+                world.primitives
+            }
+            PrimitiveSpace::Screen => {
+                let mut screen = Primitives::default();
+                self.draw_screen_primitives(&mut world, &mut screen);
+                screen.primitives
+            }
+        }
+    }
+
+    /// Writes the world space primitives of the gizmo to `primitives`, replacing its contents.
+    fn draw_world_primitives(&self, primitives: &mut Primitives) {
+        primitives.clear();
+
+        if !self.config.viewport.is_finite() {
+            return;
+        }
+
+        for subgizmo in self.subgizmos.iter().chain(&self.fading_subgizmos) {
+            if subgizmo.opacity() > 0.0 {
+                subgizmo.draw(primitives);
+            }
+        }
+
+        if self.snapped_point.is_some() {
+            draw_snap_point(&self.config, primitives);
+        }
+    }
+
+    /// Draws the gizmo to `draw_data`, replacing its contents, using the given scratch buffers.
+    fn draw_with_scratch(&self, scratch: &mut DrawScratch, draw_data: &mut GizmoDrawData) {
+        self.draw_screen_primitives(&mut scratch.world, &mut scratch.screen);
+
+        tessellate_into(
+            &scratch.screen.primitives,
+            self.config.pixels_per_point,
+            &mut scratch.tessellation,
+            draw_data,
+        );
+    }
+
+    /// Writes the screen space primitives of the gizmo to `screen`, replacing its contents.
+    /// The world space primitives they are converted from are written to `world`.
+    fn draw_screen_primitives(&self, world: &mut Primitives, screen: &mut Primitives) {
+        self.draw_world_primitives(world);
+
+        primitives_to_screen(
+            &world.primitives,
+            self.config.view_projection,
+            self.config.viewport,
+            screen,
+        );
+    }
+
+    /// Advances the transitions of all subgizmos. While a subgizmo is
+    /// active, the other subgizmos are faded out.
+    fn animate(&mut self, delta_time: Option<f32>) {
+        for subgizmo in &mut self.subgizmos {
+            let visible = self.active_subgizmo_id.is_none() || subgizmo.is_active();
+            subgizmo.animate(delta_time, visible);
+        }
+
+        let subgizmo_config = self.subgizmo_config();
+        for subgizmo in &mut self.fading_subgizmos {
+            subgizmo.update_config(subgizmo_config);
+            subgizmo.set_focused(false);
+            subgizmo.set_active(false);
+            subgizmo.animate(delta_time, false);
+        }
+
+        self.fading_subgizmos
+            .retain(|subgizmo| subgizmo.opacity() > 0.0);
+    }
+
+    fn active_subgizmo(&self) -> Option<&SubGizmo> {
+        self.active_subgizmo_id
+            .and_then(|id| self.subgizmos.iter().find(|subgizmo| subgizmo.id() == id))
+    }
+
+    fn active_subgizmo_mut(&mut self) -> Option<&mut SubGizmo> {
+        self.active_subgizmo_id.and_then(|id| {
+            self.subgizmos
+                .iter_mut()
+                .find(|subgizmo| subgizmo.id() == id)
+        })
     }
 
-    fn update_transforms_with_result(
+    fn transform_with_result(
         &self,
         result: GizmoResult,
-        transforms: &[Transform],
-        start_transforms: &[Transform],
-    ) -> Vec<Transform> {
-        transforms
-            .iter()
-            .zip(start_transforms)
-            .map(|(transform, start_transform)| match result {
-                GizmoResult::Rotation {
-                    axis,
-                    delta,
-                    total: _,
-                    is_view_axis,
-                } => self.update_rotation(transform, axis, delta, is_view_axis),
-                GizmoResult::Translation { delta, total: _ } => {
-                    self.update_translation(delta, transform, start_transform)
-                }
-                GizmoResult::Scale { total } => {
-                    Self::update_scale(transform, start_transform, total)
-                }
-                GizmoResult::Arcball { delta, total: _ } => {
-                    self.update_rotation_quat(transform, delta.into())
-                }
-            })
-            .collect()
+        transform: &Transform,
+        start_transform: &Transform,
+    ) -> Transform {
+        match result.kind {
+            GizmoResultKind::Rotation {
+                axis,
+                delta,
+                total: _,
+                is_view_axis,
+            } => self.update_rotation(transform, axis, delta, is_view_axis),
+            GizmoResultKind::Translation { delta, total: _ } => {
+                self.update_translation(delta, transform, start_transform)
+            }
+            GizmoResultKind::Scale { delta: _, total } => {
+                Self::update_scale(transform, start_transform, total)
+            }
+            GizmoResultKind::Arcball { delta, total: _ } => {
+                self.update_rotation_quat(transform, delta.into())
+            }
+            GizmoResultKind::Surface {
+                total, rotation, ..
+            } => self.update_surface(start_transform, total.into(), rotation.into()),
+            GizmoResultKind::Flip { axis } => {
+                mirror_transform(transform, axis.into(), result.pivot.into())
+            }
+            GizmoResultKind::Arrangement { .. } => *transform,
+        }
     }
 
     fn update_rotation(
@@ -275,6 +1318,86 @@ impl Gizmo {
         }
     }
 
+    /// Snaps a translation result to the closest snap point on screen, if it is close enough.
+    ///
+    /// The deltas of the returned result are relative to the current gizmo position,
+    /// so that the gizmo stays consistent whether or not the previous update was snapped.
+    fn snap_to_points(
+        &mut self,
+        kind: GizmoResultKind,
+        snap_points: &dyn SnapPointProvider,
+    ) -> GizmoResultKind {
+        let GizmoResultKind::Translation { total, .. } = kind else {
+            return kind;
+        };
+
+        let start_rotation = DQuat::from(self.gizmo_start_transform.rotation);
+        let to_world = |vector: DVec3| match self.config.orientation() {
+            GizmoOrientation::Global => vector,
+            GizmoOrientation::Local | GizmoOrientation::Gimbal => start_rotation * vector,
+        };
+        let from_world = |vector: DVec3| match self.config.orientation() {
+            GizmoOrientation::Global => vector,
+            GizmoOrientation::Local | GizmoOrientation::Gimbal => start_rotation.inverse() * vector,
+        };
+
+        let start = DVec3::from(self.gizmo_start_transform.translation);
+        let current = self.config.translation;
+        let mut position = start + to_world(total.into());
+
+        let mut candidates = std::mem::take(&mut self.snap_point_candidates);
+        candidates.clear();
+        snap_points.snap_points(&mut candidates);
+
+        let viewport = self.config.viewport;
+        let view_projection = self.config.view_projection;
+        if let Some(screen_pos) = world_to_screen(viewport, view_projection, position) {
+            let max_distance_sq = self.config.snap_point_distance.powi(2);
+
+            let closest = candidates
+                .iter()
+                .map(|&point| DVec3::from(point))
+                .filter_map(|point| {
+                    let distance_sq =
+                        world_to_screen(viewport, view_projection, point)?.distance_sq(screen_pos);
+                    (distance_sq <= max_distance_sq).then_some((point, distance_sq))
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            if let Some((point, _)) = closest {
+                position = point;
+                self.snapped_point = Some(point);
+            }
+        }
+
+        self.snap_point_candidates = candidates;
+
+        GizmoResultKind::Translation {
+            delta: from_world(position - current).into(),
+            total: from_world(position - start).into(),
+        }
+    }
+
+    fn update_surface(
+        &self,
+        start_transform: &Transform,
+        total: DVec3,
+        rotation: DQuat,
+    ) -> Transform {
+        let gizmo_start = DVec3::from(self.gizmo_start_transform.translation);
+        let offset = DVec3::from(start_transform.translation) - gizmo_start;
+        let offset = match self.config.pivot_point {
+            TransformPivotPoint::MedianPoint => rotation * offset,
+            TransformPivotPoint::IndividualOrigins => offset,
+        };
+
+        Transform {
+            scale: start_transform.scale,
+            rotation: (rotation * DQuat::from(start_transform.rotation)).into(),
+            translation: (gizmo_start + total + offset).into(),
+        }
+    }
+
     fn update_translation(
         &self,
         delta: mint::Vector3<f64>,
@@ -283,7 +1406,9 @@ impl Gizmo {
     ) -> Transform {
         let delta = match self.config.orientation() {
             GizmoOrientation::Global => DVec3::from(delta),
-            GizmoOrientation::Local => DQuat::from(start_transform.rotation) * DVec3::from(delta),
+            GizmoOrientation::Local | GizmoOrientation::Gimbal => {
+                DQuat::from(start_transform.rotation) * DVec3::from(delta)
+            }
         };
 
         Transform {
@@ -306,11 +1431,11 @@ impl Gizmo {
     }
 
     fn update_config_with_result(&mut self, result: GizmoResult) {
-        let new_config_transform = self.update_transforms_with_result(
+        let new_config_transform = self.transform_with_result(
             result,
-            &[self.config.as_transform()],
-            &[self.gizmo_start_transform],
-        )[0];
+            &self.config.as_transform(),
+            &self.gizmo_start_transform,
+        );
 
         self.config.update_transform(new_config_transform);
     }
@@ -528,15 +1653,59 @@ impl Gizmo {
             screen_pos,
             origin,
             direction,
+            surface_hit: None,
+        }
+    }
+
+    /// Ray from a world space origin and direction.
+    ///
+    /// Subgizmos that operate in screen space use the projection of a point on the ray
+    /// as a virtual cursor position. The point is where the ray crosses the plane that
+    /// faces the camera at the gizmo's position, or the point closest to the gizmo
+    /// if the ray does not cross the plane.
+    fn world_ray(&self, origin: DVec3, direction: DVec3) -> Ray {
+        let direction = direction.normalize_or_zero();
+        let gizmo_pos = self.config.translation;
+
+        let mut t = 0.0;
+        if !intersect_plane(
+            self.config.view_forward(),
+            gizmo_pos,
+            origin,
+            direction,
+            &mut t,
+        ) {
+            t = (gizmo_pos - origin).dot(direction).max(0.0);
+        }
+
+        let viewport = self.config.viewport;
+        let screen_pos = world_to_screen(
+            viewport,
+            self.config.view_projection,
+            origin + direction * t,
+        )
+        .or_else(|| world_to_screen(viewport, self.config.view_projection, gizmo_pos))
+        .unwrap_or_default();
+
+        Ray {
+            screen_pos,
+            origin,
+            direction,
+            surface_hit: None,
         }
     }
 }
 
 /// Information needed for interacting with the gizmo.
-#[derive(Default, Clone, Copy, Debug)]
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GizmoInteraction {
     /// Current cursor position in window coordinates.
+    /// Ignored if [`GizmoInteraction::ray`] is given.
     pub cursor_pos: (f32, f32),
+    /// World space ray used for interaction instead of the cursor position,
+    /// for example from a VR motion controller or a custom picking system.
+    pub ray: Option<GizmoRay>,
     /// Whether dragging was started this frame.
     /// Usually this is set to true if the primary mouse
     /// button was just pressed.
@@ -545,36 +1714,134 @@ pub struct GizmoInteraction {
     /// Usually this is set to true whenever the primary mouse
     /// button is being pressed.
     pub dragging: bool,
+    /// Time elapsed since the previous update, in seconds.
+    /// Used to animate the transitions of the gizmo, such as
+    /// highlighting and fading. If `None`, the transitions are instant.
+    pub delta_time: Option<f32>,
+    /// Modifier keys held during the interaction.
+    pub modifiers: GizmoModifiers,
+    /// Axis key pressed this frame, if any.
+    ///
+    /// Pressing an axis key during a drag constrains it to that axis, or to the plane
+    /// perpendicular to it, in the orientation of the gizmo. Pressing the same key again
+    /// switches between global and local orientation, and then removes the constraint.
+    /// The transformation made so far is kept when the constraint changes.
+    pub axis_key: Option<AxisKey>,
 }
 
-/// Result of a gizmo transformation
-#[derive(Debug, Copy, Clone)]
-pub enum GizmoResult {
-    Rotation {
-        /// The rotation axis,
-        axis: mint::Vector3<f64>,
-        /// The latest rotation angle delta
-        delta: f64,
-        /// Total rotation angle of the gizmo interaction
-        total: f64,
-        /// Whether we are rotating along the view axis
-        is_view_axis: bool,
+/// Key that constrains a drag to an axis or a plane. See [`GizmoInteraction::axis_key`].
+///
+/// The integrations of this crate map the `X`, `Y` and `Z` keys to the axes,
+/// and exclude the axis when `Shift` is held. While an axis key is held,
+/// `Shift` does not enable [`GizmoModifiers::precision`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisKey {
+    /// Axis of the key. [`GizmoDirection::View`] is ignored.
+    pub direction: GizmoDirection,
+    /// Constrains to the plane perpendicular to the axis instead of the axis.
+    /// Ignored when rotating.
+    pub exclude: bool,
+}
+
+/// Axis or plane constraint of a drag. See [`Gizmo::axis_constraint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisConstraint {
+    /// Constrained axis
+    pub direction: GizmoDirection,
+    /// Whether the drag is constrained to the plane perpendicular to the axis
+    pub exclude: bool,
+    /// Orientation of the axis
+    pub orientation: GizmoOrientation,
+}
+
+/// Modifier keys that change how the gizmo is interacted with.
+///
+/// The integrations of this crate map them to the same keys:
+/// `Ctrl` inverts snapping, `Shift` enables precision and `Alt` duplicates.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GizmoModifiers {
+    /// Inverts [`GizmoConfig::snapping`] while held.
+    pub invert_snapping: bool,
+    /// Slows down the transformation to [`GizmoConfig::precision_factor`]
+    /// of the cursor movement while held.
+    pub precision: bool,
+    /// Requests duplicating the targets when held at the start of a drag.
+    /// Reported in [`GizmoResult::duplicate`].
+    pub duplicate: bool,
+}
+
+/// Ray in world space, used for interacting with a gizmo without a cursor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GizmoRay {
+    /// Origin of the ray
+    pub origin: mint::Vector3<f64>,
+    /// Direction of the ray. Does not need to be normalized.
+    pub direction: mint::Vector3<f64>,
+}
+
+/// Coordinate space of [`GizmoPrimitive`]s.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum PrimitiveSpace {
+    /// Points are in viewport coordinates, `z` being the depth in normalized device coordinates.
+    /// Circles, arcs and arrows are converted to polylines and polygons,
+    /// and parts that are behind the camera are clipped.
+    #[default]
+    Screen,
+    /// Points are in world coordinates.
+    World,
+}
+
+/// Primitive used to draw [`Gizmo`], before tessellation.
+///
+/// Widths are always in viewport units, regardless of the [`PrimitiveSpace`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum GizmoPrimitive {
+    /// Line going through the points.
+    Polyline {
+        points: Vec<mint::Vector3<f64>>,
+        /// Whether the last point connects back to the first one
+        closed: bool,
+        width: f32,
+        color: Color32,
     },
-    Translation {
-        /// The latest translation delta
-        delta: mint::Vector3<f64>,
-        /// Total translation of the gizmo interaction
-        total: mint::Vector3<f64>,
+    /// Filled convex polygon.
+    Polygon {
+        points: Vec<mint::Vector3<f64>>,
+        color: Color32,
     },
-    Scale {
-        /// Total scale of the gizmo interaction
-        total: mint::Vector3<f64>,
+    /// Circle on the plane spanned by `x_axis` and `y_axis`. Lengths of the axes are the radii.
+    /// If `filled`, the circle is filled with `color` and `width` is ignored.
+    Circle {
+        center: mint::Vector3<f64>,
+        x_axis: mint::Vector3<f64>,
+        y_axis: mint::Vector3<f64>,
+        width: f32,
+        color: Color32,
+        filled: bool,
     },
-    Arcball {
-        /// The latest rotation delta
-        delta: mint::Quaternion<f64>,
-        /// Total rotation of the gizmo interaction
-        total: mint::Quaternion<f64>,
+    /// Arc on the plane spanned by `x_axis` and `y_axis`. Lengths of the axes are the radii.
+    /// Points of the arc are `center + x_axis * cos(angle) + y_axis * sin(angle)`,
+    /// with angle going from `start_angle` to `end_angle`.
+    Arc {
+        center: mint::Vector3<f64>,
+        x_axis: mint::Vector3<f64>,
+        y_axis: mint::Vector3<f64>,
+        start_angle: f64,
+        end_angle: f64,
+        width: f32,
+        color: Color32,
+    },
+    /// Flat triangle facing the viewer, with the center of its base at `from`
+    /// and its tip at `to`. `width` is the width of the base.
+    Arrow {
+        from: mint::Vector3<f64>,
+        to: mint::Vector3<f64>,
+        width: f32,
+        color: Color32,
     },
 }
 
@@ -589,15 +1856,16 @@ pub struct GizmoDrawData {
     pub indices: Vec<u32>,
 }
 
-impl From<Mesh> for GizmoDrawData {
-    fn from(mesh: Mesh) -> Self {
+#[cfg(feature = "epaint")]
+impl From<epaint::Mesh> for GizmoDrawData {
+    fn from(mesh: epaint::Mesh) -> Self {
         let (vertices, colors): (Vec<_>, Vec<_>) = mesh
             .vertices
             .iter()
             .map(|vertex| {
                 (
                     [vertex.pos.x, vertex.pos.y],
-                    Rgba::from(vertex.color).to_array(),
+                    crate::color::Rgba::from(Color32::from(vertex.color)).to_array(),
                 )
             })
             .unzip();
@@ -634,4 +1902,1753 @@ pub(crate) struct Ray {
     pub(crate) screen_pos: Pos2,
     pub(crate) origin: DVec3,
     pub(crate) direction: DVec3,
+    /// Scene surface hit by the ray, if surface placement is in use
+    pub(crate) surface_hit: Option<SurfaceHit>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        AdaptiveSnap, DragOverlays, GizmoSizing, HandleTip, RotationInput, SnapSettings,
+        DEFAULT_SNAP_DISTANCE,
+    };
+    use crate::math::{DAffine3, DMat4, DVec2, DVec4};
+    use crate::scene::HierarchyTarget;
+    use crate::test_utils::{drag, hover, screen_pos, test_config, EYE};
+
+    /// Drags the X translation arrow of a gizmo at the origin to the right on screen,
+    /// returning the results and updated targets of each drag update.
+    fn drag_x_arrow(
+        gizmo: &mut Gizmo,
+        targets: &mut Vec<Transform>,
+        scene: GizmoScene<'_>,
+        steps: usize,
+    ) -> Vec<(GizmoResult, Vec<Transform>)> {
+        let start = screen_pos(gizmo.config(), DVec3::new(0.6, 0.0, 0.0));
+        gizmo.update_with_scene(hover(start), targets, scene);
+        assert!(gizmo.is_focused());
+
+        let mut results = Vec::new();
+        for step in 0..steps {
+            let cursor_pos = (start.0 + step as f32 * 5.0, start.1);
+            if let Some((result, new_targets)) =
+                gizmo.update_with_scene(drag(cursor_pos, step == 0), targets, scene)
+            {
+                *targets = new_targets.clone();
+                results.push((result, new_targets));
+            }
+        }
+
+        results
+    }
+
+    #[test]
+    fn update_into_matches_update() {
+        let parents = [Some(
+            DMat4::from_scale_rotation_translation(
+                DVec3::new(2.0, 1.0, 0.5),
+                DQuat::from_rotation_y(0.4),
+                DVec3::new(0.5, 0.0, 0.0),
+            )
+            .into(),
+        )];
+        let constraint = |_: usize, _: &Transform, mut proposed: Transform| {
+            proposed.translation.x = proposed.translation.x.min(0.8);
+            proposed
+        };
+        let scene = GizmoScene::new().parents(&parents).constraint(&constraint);
+
+        let local = HierarchyTarget::new(Transform::default(), parents[0])
+            .local_transform(Transform::default());
+
+        let config = test_config(GizmoMode::Translate);
+        let mut gizmo = Gizmo::new(config);
+        let mut gizmo_into = Gizmo::new(config);
+        let mut targets = vec![local];
+        let mut targets_into = vec![local];
+
+        let start = screen_pos(&config, DVec3::new(0.6, 0.0, 0.0));
+        gizmo.update_with_scene(hover(start), &targets, scene);
+        gizmo_into.update_into_with_scene(hover(start), &mut targets_into, scene);
+
+        for step in 0..6 {
+            let interaction = drag((start.0 + step as f32 * 20.0, start.1), step == 0);
+            let (result, new_targets) = gizmo
+                .update_with_scene(interaction, &targets, scene)
+                .unwrap();
+            targets = new_targets;
+            let result_into = gizmo_into
+                .update_into_with_scene(interaction, &mut targets_into, scene)
+                .unwrap();
+
+            assert_eq!(result, result_into);
+            assert_eq!(targets, targets_into);
+        }
+
+        let world = HierarchyTarget::new(targets[0], parents[0]).world_transform();
+        assert!((world.translation.x - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn scene_queries_combine() {
+        let snap_points: Vec<mint::Vector3<f64>> = vec![[0.5, 0.0, 0.0].into()];
+        let constraint = |_: usize, _: &Transform, mut proposed: Transform| {
+            proposed.translation.x = proposed.translation.x.min(0.3);
+            proposed
+        };
+        let scene = GizmoScene::new()
+            .snap_points(&snap_points)
+            .constraint(&constraint);
+
+        let mut gizmo = Gizmo::new(test_config(GizmoMode::Translate));
+        let mut targets = vec![Transform::default()];
+
+        let mut snapped = false;
+        for (result, targets) in drag_x_arrow(&mut gizmo, &mut targets, scene, 30) {
+            let GizmoResultKind::Translation { total, .. } = result.kind else {
+                panic!("unexpected result {result:?}");
+            };
+            snapped |= total.x == 0.5;
+            assert!(targets[0].translation.x <= 0.3);
+        }
+
+        assert!(snapped);
+    }
+
+    #[test]
+    fn constraint_does_not_accumulate() {
+        let constraint = |_: usize, _: &Transform, mut proposed: Transform| {
+            proposed.translation.x = proposed.translation.x.min(0.3);
+            proposed
+        };
+        let scene = GizmoScene::new().constraint(&constraint);
+
+        let config = test_config(GizmoMode::Translate);
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = vec![Transform::default()];
+
+        let start = screen_pos(&config, DVec3::new(0.6, 0.0, 0.0));
+        gizmo.update_with_scene(hover(start), &targets, scene);
+
+        // Past the constraint and back
+        let mut previous_total = 0.0;
+        for (step, offset) in [0.0, 40.0, 80.0, 120.0, 80.0, 20.0, 10.0]
+            .into_iter()
+            .enumerate()
+        {
+            let interaction = drag((start.0 + offset, start.1), step == 0);
+            let (result, new_targets) = gizmo
+                .update_with_scene(interaction, &targets, scene)
+                .unwrap();
+            targets = new_targets;
+
+            let GizmoResultKind::Translation { total, .. } = result.kind else {
+                panic!("unexpected result {result:?}");
+            };
+            assert!((targets[0].translation.x - total.x.min(0.3)).abs() < 1e-9);
+            assert!((gizmo.config.translation.x - total.x.min(0.3)).abs() < 1e-9);
+            // The result describes the unconstrained transformation
+            assert!((result.pivot.x - previous_total).abs() < 1e-9);
+            previous_total = total.x;
+        }
+    }
+
+    #[test]
+    fn constrained_gizmo_is_oriented_like_the_last_target() {
+        // Rotates the first target, and leaves the last one as it is
+        let constraint = |index: usize, _: &Transform, mut proposed: Transform| {
+            if index == 0 {
+                proposed.rotation = DQuat::from_rotation_z(1.0).into();
+            }
+            proposed
+        };
+        let scene = GizmoScene::new().constraint(&constraint);
+
+        let mut gizmo = Gizmo::new(test_config(GizmoMode::Translate));
+        let mut targets = vec![Transform::default(); 2];
+
+        let results = drag_x_arrow(&mut gizmo, &mut targets, scene, 4);
+        assert!(!results.is_empty());
+        assert_eq!(targets[0].rotation, DQuat::from_rotation_z(1.0).into());
+        assert_eq!(gizmo.config.rotation, DQuat::IDENTITY);
+    }
+
+    #[test]
+    fn snap_point_off_the_axis_is_left_behind() {
+        let snap_points: Vec<mint::Vector3<f64>> = vec![[0.5, 0.05, 0.0].into()];
+        let scene = GizmoScene::new().snap_points(&snap_points);
+
+        let mut gizmo = Gizmo::new(test_config(GizmoMode::Translate));
+        let mut targets = vec![Transform::default()];
+
+        let results = drag_x_arrow(&mut gizmo, &mut targets, scene, 30);
+
+        let snapped = results
+            .iter()
+            .position(|(_, targets)| targets[0].translation.y == 0.05)
+            .unwrap();
+        let (_, left) = results[snapped..]
+            .iter()
+            .find(|(_, targets)| targets[0].translation.y != 0.05)
+            .unwrap();
+
+        assert!(left[0].translation.x > 0.5);
+        assert!(left[0].translation.y.abs() < 1e-9);
+        assert!(targets[0].translation.y.abs() < 1e-9);
+        assert!(targets[0].translation.z.abs() < 1e-9);
+    }
+
+    #[test]
+    fn surface_placement_stays_when_leaving_surfaces() {
+        // Tilted ground patch that ends at x = 0.5
+        let raycast = |ray: GizmoRay| {
+            let (origin, direction) = (DVec3::from(ray.origin), DVec3::from(ray.direction));
+            let t = -origin.y / direction.y;
+            let point = origin + direction * t;
+            (t >= 0.0 && point.x < 0.5).then(|| SurfaceHit {
+                point: point.into(),
+                normal: [0.3, 1.0, 0.0].into(),
+            })
+        };
+        let scene = GizmoScene::new().surface(&raycast);
+
+        let mut config = test_config(GizmoMode::Translate);
+        config.surface_placement = SurfacePlacement::AlignToNormal;
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = vec![Transform::default()];
+
+        let center = screen_pos(&config, DVec3::ZERO);
+        let start = (center.0 + 2.0, center.1 + 2.0);
+        gizmo.update_with_scene(hover(start), &targets, scene);
+
+        let mut previous = None;
+        let mut left_surface = false;
+        for step in 0..20 {
+            let cursor_pos = (start.0 + step as f32 * 10.0, start.1);
+            let (result, new_targets) = gizmo
+                .update_with_scene(drag(cursor_pos, step == 0), &targets, scene)
+                .unwrap();
+
+            // Surfaces are only queried once the drag has started
+            let GizmoResultKind::Surface {
+                delta,
+                total,
+                rotation,
+                ..
+            } = result.kind
+            else {
+                assert_eq!(step, 0);
+                continue;
+            };
+
+            if let Some((previous_total, previous_rotation)) = previous {
+                if DVec3::from(delta) == DVec3::ZERO {
+                    left_surface = true;
+                    assert_eq!(total, previous_total);
+                    assert_eq!(rotation, previous_rotation);
+                    assert_eq!(new_targets, targets);
+                }
+            }
+
+            previous = Some((total, rotation));
+            targets = new_targets;
+        }
+
+        assert!(left_surface);
+        assert!(targets[0].translation.x > 0.3 && targets[0].translation.x < 0.5);
+        assert_ne!(targets[0].rotation, Transform::default().rotation);
+    }
+
+    #[test]
+    fn surfaces_are_only_raycast_for_the_view_plane() {
+        let raycasts = std::cell::Cell::new(0);
+        let raycast = |_: GizmoRay| {
+            raycasts.set(raycasts.get() + 1);
+            None
+        };
+        let scene = GizmoScene::new().surface(&raycast);
+
+        let mut config = test_config(GizmoMode::Translate);
+        config.surface_placement = SurfacePlacement::AlignToNormal;
+
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = vec![Transform::default()];
+        let results = drag_x_arrow(&mut gizmo, &mut targets, scene, 4);
+        assert!(!results.is_empty());
+        assert_eq!(raycasts.get(), 0);
+
+        let mut gizmo = Gizmo::new(config);
+        let targets = vec![Transform::default()];
+        let center = screen_pos(&config, DVec3::ZERO);
+        let start = (center.0 + 2.0, center.1 + 2.0);
+        gizmo.update_with_scene(hover(start), &targets, scene);
+        for step in 0..4 {
+            let cursor_pos = (start.0 + step as f32 * 5.0, start.1);
+            gizmo.update_with_scene(drag(cursor_pos, step == 0), &targets, scene);
+        }
+        assert!(raycasts.get() > 0);
+    }
+
+    /// Addresses of the buffers of the draw data, including the point buffers of the primitives
+    /// it was drawn from
+    fn draw_buffers(gizmo: &Gizmo, draw_data: &GizmoDrawData) -> Vec<usize> {
+        let scratch = &gizmo.draw_scratch;
+        let mut buffers = vec![
+            draw_data.vertices.as_ptr() as usize,
+            draw_data.colors.as_ptr() as usize,
+            draw_data.indices.as_ptr() as usize,
+        ];
+        for primitive in scratch
+            .world
+            .primitives
+            .iter()
+            .chain(&scratch.screen.primitives)
+        {
+            if let GizmoPrimitive::Polyline { points, .. }
+            | GizmoPrimitive::Polygon { points, .. } = primitive
+            {
+                buffers.push(points.as_ptr() as usize);
+            }
+        }
+        buffers.sort_unstable();
+        buffers
+    }
+
+    #[test]
+    fn redrawing_reuses_buffers() {
+        let mut config = test_config(GizmoMode::Translate | GizmoMode::Rotate);
+        config.visuals.drag_overlays = DragOverlays {
+            guides: true,
+            start_ghost: true,
+            snap_ticks: true,
+        };
+        config.visuals.translation_handle.tip = HandleTip::Cone;
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = vec![Transform::default()];
+        drag_x_arrow(&mut gizmo, &mut targets, GizmoScene::default(), 3);
+
+        let mut draw_data = GizmoDrawData::default();
+        gizmo.draw_into(&mut draw_data);
+        gizmo.draw_into(&mut draw_data);
+        let buffers = draw_buffers(&gizmo, &draw_data);
+        assert!(buffers.len() > 10);
+
+        gizmo.draw_into(&mut draw_data);
+        assert_eq!(draw_buffers(&gizmo, &draw_data), buffers);
+
+        let drawn = gizmo.draw();
+        assert_eq!(draw_data.vertices, drawn.vertices);
+        assert_eq!(draw_data.indices, drawn.indices);
+    }
+
+    #[test]
+    fn draw_if_changed_skips_unchanged_gizmo() {
+        let config = test_config(GizmoMode::Translate);
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+        let cursor_pos = screen_pos(&config, DVec3::new(0.6, 0.0, 0.0));
+
+        // Highlights follow the focus on the next update
+        for _ in 0..2 {
+            gizmo.update(hover(cursor_pos), &targets);
+        }
+        assert!(gizmo.draw_if_changed().is_some());
+        assert!(gizmo.draw_if_changed().is_none());
+
+        // Updated, but drawn as before
+        gizmo.update(hover(cursor_pos), &targets);
+        assert!(gizmo.draw_if_changed().is_none());
+
+        // Reconfigured with the same configuration
+        gizmo.update_config(config);
+        gizmo.update(hover(cursor_pos), &targets);
+        assert!(gizmo.draw_if_changed().is_none());
+
+        let mut changed_config = config;
+        changed_config.visuals.stroke_width *= 2.0;
+        gizmo.update_config(changed_config);
+        gizmo.update(hover(cursor_pos), &targets);
+        assert!(gizmo.draw_if_changed().is_some());
+        assert!(gizmo.draw_if_changed().is_none());
+
+        // Moved camera
+        let mut moved_camera = changed_config;
+        moved_camera.view_matrix = DMat4::look_at_rh(EYE + DVec3::X, DVec3::ZERO, DVec3::Y).into();
+        gizmo.update_config(moved_camera);
+        gizmo.update(hover(cursor_pos), &targets);
+        assert!(gizmo.draw_if_changed().is_some());
+
+        // Moved target
+        targets[0].translation.y += 0.1;
+        gizmo.update(hover(cursor_pos), &targets);
+        assert!(gizmo.draw_if_changed().is_some());
+        gizmo.update(hover(cursor_pos), &targets);
+        assert!(gizmo.draw_if_changed().is_none());
+
+        // Drawn on each update while the focus transition runs, and no longer once it is done
+        let unfocus = GizmoInteraction {
+            delta_time: Some(0.02),
+            ..hover((0.0, 0.0))
+        };
+        gizmo.update(unfocus, &targets);
+        assert!(gizmo.is_animating());
+        while gizmo.is_animating() {
+            assert!(gizmo.draw_if_changed().is_some());
+            gizmo.update(unfocus, &targets);
+        }
+        gizmo.draw_if_changed();
+        gizmo.update(unfocus, &targets);
+        assert!(gizmo.draw_if_changed().is_none());
+    }
+
+    /// Largest stroke width of the screen space primitives, which are tessellated with it
+    fn max_stroke_width(gizmo: &Gizmo) -> f32 {
+        gizmo
+            .draw_primitives(PrimitiveSpace::Screen)
+            .iter()
+            .filter_map(|primitive| match primitive {
+                GizmoPrimitive::Polyline { width, .. } => Some(*width),
+                _ => None,
+            })
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn world_sizing_scales_strokes() {
+        for sizing in [
+            GizmoSizing::Screen,
+            GizmoSizing::World {
+                size: 1.0,
+                min_pixels: None,
+                max_pixels: None,
+            },
+        ] {
+            let mut widths = Vec::new();
+            let mut pixel_sizes = Vec::new();
+            for distance in [1.0, 2.0] {
+                let mut config = test_config(GizmoMode::Translate | GizmoMode::Rotate);
+                config.view_matrix =
+                    DMat4::look_at_rh(EYE * distance, DVec3::ZERO, DVec3::Y).into();
+                config.visuals.sizing = sizing;
+                let mut gizmo = Gizmo::new(config);
+                gizmo.update(hover((0.0, 0.0)), &[Transform::default()]);
+
+                widths.push(max_stroke_width(&gizmo));
+                pixel_sizes.push(gizmo.config.pixel_size);
+            }
+
+            let width_ratio = widths[0] / widths[1];
+            if sizing == GizmoSizing::Screen {
+                assert_eq!(width_ratio, 1.0);
+            } else {
+                let size_ratio = pixel_sizes[1] / pixel_sizes[0];
+                assert!((width_ratio - size_ratio).abs() < 1e-3, "{widths:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn arrangements_pivot_around_the_given_targets() {
+        let mut gizmo = Gizmo::new(test_config(GizmoMode::Translate));
+        // The gizmo was last updated elsewhere
+        gizmo.update(hover((0.0, 0.0)), &[Transform::default()]);
+
+        let targets = [2.0, 4.0, 9.0].map(|x| Transform {
+            translation: [x, 1.0, 0.0].into(),
+            ..Default::default()
+        });
+        let median = DVec3::new(5.0, 1.0, 0.0);
+
+        let (aligned, _) = gizmo
+            .align(&targets, GizmoDirection::X, Alignment::Min)
+            .unwrap();
+        let (distributed, _) = gizmo.distribute(&targets, GizmoDirection::X).unwrap();
+        let (flipped, _) = gizmo.flip(&targets, GizmoDirection::X).unwrap();
+
+        for result in [aligned, distributed, flipped] {
+            assert_eq!(DVec3::from(result.pivot), median);
+        }
+    }
+
+    #[test]
+    fn flip_result_mirrors_targets() {
+        let mut config = test_config(GizmoMode::Translate);
+        config.orientation = GizmoOrientation::Local;
+        let gizmo = Gizmo::new(config);
+
+        let targets = [
+            Transform::from_scale_rotation_translation(
+                [1.0, 2.0, 3.0],
+                DQuat::from_rotation_y(0.3),
+                [1.0, 0.0, 2.0],
+            ),
+            Transform::from_scale_rotation_translation(
+                [0.5, 1.0, 1.0],
+                DQuat::from_rotation_x(1.2),
+                [-2.0, 1.0, 0.0],
+            ),
+        ];
+
+        let (result, new_targets) = gizmo.flip(&targets, GizmoDirection::X).unwrap();
+        let GizmoResultKind::Flip { axis } = result.kind else {
+            panic!("unexpected result {result:?}");
+        };
+        // Oriented like the last target
+        assert!(DVec3::from(axis).abs_diff_eq(DQuat::from_rotation_x(1.2) * DVec3::X, 1e-9));
+        assert_eq!(result.delta, Transform::default());
+
+        for (target, new_target) in targets.iter().zip(&new_targets) {
+            assert_eq!(result.apply_to(target), *new_target);
+            assert_eq!(
+                DVec3::from(new_target.scale).x,
+                -DVec3::from(target.scale).x
+            );
+        }
+    }
+
+    /// Parent world matrix and local transform of a target two levels down a hierarchy,
+    /// whose ancestors are rotated and scaled non-uniformly. The target is at the world origin.
+    fn nested_target() -> (mint::RowMatrix4<f64>, Transform) {
+        let grandparent = DMat4::from_scale_rotation_translation(
+            DVec3::ONE,
+            DQuat::from_rotation_y(0.5),
+            DVec3::new(1.0, 0.5, -2.0),
+        );
+        let parent = grandparent
+            * DMat4::from_scale_rotation_translation(
+                DVec3::new(2.0, 1.0, 0.5),
+                DQuat::from_rotation_z(0.3),
+                DVec3::new(0.0, 1.0, 1.0),
+            );
+        let local = Transform {
+            translation: parent.inverse().transform_point3(DVec3::ZERO).into(),
+            ..Default::default()
+        };
+
+        (parent.into(), local)
+    }
+
+    /// Drags from the point at `offset` from the origin to the right on screen,
+    /// updating the targets and returning the last result.
+    fn drag_from(
+        gizmo: &mut Gizmo,
+        targets: &mut [Transform],
+        scene: GizmoScene<'_>,
+        offset: DVec3,
+    ) -> GizmoResult {
+        let start = screen_pos(gizmo.config(), offset);
+        gizmo.update_with_scene(hover(start), targets, scene);
+        assert!(gizmo.is_focused());
+
+        let mut last_result = None;
+        for step in 0..6 {
+            let interaction = drag((start.0 + step as f32 * 10.0, start.1), step == 0);
+            if let Some(result) = gizmo.update_into_with_scene(interaction, targets, scene) {
+                last_result = Some(result);
+            }
+        }
+        last_result.unwrap()
+    }
+
+    #[test]
+    fn nested_hierarchy_translation() {
+        let (parent, local) = nested_target();
+        let parents = [Some(parent)];
+        let scene = GizmoScene::new().parents(&parents);
+
+        let mut gizmo = Gizmo::new(test_config(GizmoMode::Translate));
+        let mut targets = [local];
+        let result = drag_from(&mut gizmo, &mut targets, scene, DVec3::new(0.6, 0.0, 0.0));
+
+        let GizmoResultKind::Translation { total, .. } = result.kind else {
+            panic!("unexpected result {result:?}");
+        };
+        assert!(total.x > 0.1);
+
+        // Moved along the world X axis, by the total translation
+        let world_translation = DMat4::from(parent).transform_point3(targets[0].translation.into());
+        assert!(world_translation.abs_diff_eq(total.into(), 1e-9));
+        assert!(DQuat::from(targets[0].rotation).abs_diff_eq(DQuat::IDENTITY, 1e-9));
+        assert!(DVec3::from(targets[0].scale).abs_diff_eq(DVec3::ONE, 1e-9));
+    }
+
+    #[test]
+    fn nested_hierarchy_local_scale() {
+        let (parent, local) = nested_target();
+        let parents = [Some(parent)];
+        let scene = GizmoScene::new().parents(&parents);
+
+        let mut config = test_config(GizmoMode::Scale);
+        config.orientation = GizmoOrientation::Local;
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [local];
+
+        let world = HierarchyTarget::new(local, Some(parent)).world_transform();
+        let x_axis = DQuat::from(world.rotation) * DVec3::X;
+        let result = drag_from(&mut gizmo, &mut targets, scene, x_axis * 0.6);
+
+        let GizmoResultKind::Scale { total, .. } = result.kind else {
+            panic!("unexpected result {result:?}");
+        };
+        assert!(total.x > 1.1);
+
+        // Scaled along the local X axis only, staying in place
+        let scale = DVec3::from(targets[0].scale);
+        assert!(scale.abs_diff_eq(DVec3::new(total.x, 1.0, 1.0), 1e-9));
+        assert!(DMat4::from(parent)
+            .transform_point3(targets[0].translation.into())
+            .abs_diff_eq(DVec3::ZERO, 1e-9));
+
+        let new_world = HierarchyTarget::new(targets[0], Some(parent)).world_transform();
+        assert!(DQuat::from(new_world.rotation).abs_diff_eq(world.rotation.into(), 1e-9));
+        assert!((new_world.scale.x / world.scale.x - total.x).abs() < 1e-9);
+    }
+
+    /// World matrix of a target transform, allowing negative scales
+    fn transform_matrix(transform: Transform) -> DMat4 {
+        DMat4::from_scale_rotation_translation(
+            transform.scale.into(),
+            transform.rotation.into(),
+            transform.translation.into(),
+        )
+    }
+
+    #[test]
+    fn mirrored_parent_world_transform() {
+        let parent = DMat4::from_scale(DVec3::new(-1.0, 1.0, 1.0));
+        let target = HierarchyTarget::new(Transform::default(), Some(parent.into()));
+        let world = target.world_transform();
+
+        assert!(transform_matrix(world).abs_diff_eq(parent, 1e-9));
+        assert!((transform_matrix(world) * DVec4::Z).abs_diff_eq(DVec4::Z, 1e-9));
+
+        // Rotating in world space rotates the mirrored child the same way
+        let rotation = DQuat::from_rotation_y(0.5);
+        let mut rotated = world;
+        rotated.rotation = (rotation * DQuat::from(world.rotation)).into();
+        let new_local = target.local_transform(rotated);
+
+        let x_axis = parent * transform_matrix(new_local) * DVec4::X;
+        let expected = rotation * DVec3::NEG_X;
+        assert!(x_axis.truncate().abs_diff_eq(expected, 1e-9));
+        assert!(expected.abs_diff_eq(DVec3::new(-(0.5f64.cos()), 0.0, 0.5f64.sin()), 1e-9));
+    }
+
+    #[test]
+    fn mirrored_hierarchy_rotation() {
+        // A chain of parents, one of which mirrors, with a mirrored target at the world origin
+        let parent = [
+            (
+                DVec3::ONE,
+                DQuat::from_rotation_y(0.3),
+                DVec3::new(1.0, 0.0, 0.0),
+            ),
+            (
+                DVec3::new(-1.0, 1.0, 1.0),
+                DQuat::from_rotation_z(0.2),
+                DVec3::new(0.0, 1.0, 0.0),
+            ),
+            (
+                DVec3::splat(2.0),
+                DQuat::from_rotation_x(0.4),
+                DVec3::new(0.0, 0.0, 1.0),
+            ),
+        ]
+        .into_iter()
+        .fold(DMat4::IDENTITY, |parent, (scale, rotation, translation)| {
+            parent * DMat4::from_scale_rotation_translation(scale, rotation, translation)
+        });
+        let local = Transform {
+            scale: DVec3::new(1.0, -1.0, 1.0).into(),
+            rotation: DQuat::from_rotation_y(0.7).into(),
+            translation: parent.inverse().transform_point3(DVec3::ZERO).into(),
+        };
+        let parents = [Some(parent.into())];
+        let scene = GizmoScene::new().parents(&parents);
+
+        let mut gizmo = Gizmo::new(test_config(GizmoMode::Rotate));
+        let world = HierarchyTarget::new(local, parents[0]).world_transform();
+        assert!(transform_matrix(world).abs_diff_eq(parent * transform_matrix(local), 1e-9));
+
+        let handle = GizmoHandle::Axis {
+            mode: GizmoMode::Rotate,
+            direction: GizmoDirection::Y,
+        };
+        let start = handle_pos(&mut gizmo, &[world], handle);
+
+        let mut targets = [local];
+        let mut angle = 0.0;
+        for step in 0..5 {
+            let cursor_pos = (start.0 + step as f32 * 6.0, start.1 + step as f32 * 4.0);
+            let interaction = drag(cursor_pos, step == 0);
+            if let Some(result) = gizmo.update_into_with_scene(interaction, &mut targets, scene) {
+                let GizmoResultKind::Rotation { axis, delta, .. } = result.kind else {
+                    panic!("unexpected result {result:?}");
+                };
+                assert!(DVec3::from(axis).abs_diff_eq(DVec3::Y, 1e-9));
+                angle += delta;
+            }
+        }
+        assert!(f64::abs(angle) > 0.05);
+
+        // The whole world matrix of the target turned around the world Y axis
+        let rotation = DMat4::from_rotation_y(angle);
+        let new_world = parent * transform_matrix(targets[0]);
+        assert!(new_world.abs_diff_eq(rotation * parent * transform_matrix(local), 1e-9));
+    }
+
+    /// Handle of the focused subgizmo
+    fn focused_handle(gizmo: &Gizmo) -> Option<GizmoHandle> {
+        gizmo
+            .subgizmos
+            .iter()
+            .find(|subgizmo| subgizmo.is_focused())
+            .map(|subgizmo| subgizmo.handle())
+    }
+
+    /// Screen position from which the handle is focused, searched around the gizmo origin.
+    /// Leaves the handle focused.
+    fn handle_pos(gizmo: &mut Gizmo, targets: &[Transform], handle: GizmoHandle) -> (f32, f32) {
+        let center = screen_pos(gizmo.config(), targets[0].translation.into());
+        for radius in (0..200).step_by(2) {
+            for step in 0..64 {
+                let angle = step as f32 / 64.0 * std::f32::consts::TAU;
+                let cursor_pos = (
+                    center.0 + radius as f32 * angle.cos(),
+                    center.1 + radius as f32 * angle.sin(),
+                );
+                gizmo.update(hover(cursor_pos), targets);
+                if focused_handle(gizmo) == Some(handle) {
+                    return cursor_pos;
+                }
+            }
+        }
+        panic!("handle {handle:?} not found");
+    }
+
+    /// Drags the handle by each of the cursor offsets in turn, returning the results
+    /// of the drag start and each offset.
+    fn drag_handle(
+        gizmo: &mut Gizmo,
+        targets: &mut [Transform],
+        handle: GizmoHandle,
+        offsets: &[(f32, f32)],
+    ) -> Vec<GizmoResult> {
+        let start = handle_pos(gizmo, targets, handle);
+
+        std::iter::once((0.0, 0.0))
+            .chain(offsets.iter().copied())
+            .enumerate()
+            .map(|(step, offset)| {
+                let cursor_pos = (start.0 + offset.0, start.1 + offset.1);
+                gizmo
+                    .update_into(drag(cursor_pos, step == 0), targets)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    /// Total rotation angles of dragging a rotation ring by growing cursor offsets,
+    /// with the camera at `eye`
+    fn rotation_totals(eye: DVec3, input: RotationInput, direction: GizmoDirection) -> Vec<f64> {
+        let mut config = test_config(GizmoMode::Rotate);
+        config.view_matrix = DMat4::look_at_rh(eye, DVec3::ZERO, DVec3::Y).into();
+        config.rotation_input = input;
+        let mut gizmo = Gizmo::new(config);
+
+        let handle = GizmoHandle::Axis {
+            mode: GizmoMode::Rotate,
+            direction,
+        };
+        let offsets = (1..5)
+            .map(|k| (k as f32 * 6.0, k as f32 * 4.0))
+            .collect::<Vec<_>>();
+
+        drag_handle(&mut gizmo, &mut [Transform::default()], handle, &offsets)
+            .into_iter()
+            .skip(1)
+            .map(|result| {
+                let GizmoResultKind::Rotation { total, .. } = result.kind else {
+                    panic!("unexpected result {result:?}");
+                };
+                total
+            })
+            .collect()
+    }
+
+    #[test]
+    fn linear_rotation_follows_cursor_distance() {
+        let totals = rotation_totals(EYE, RotationInput::Linear, GizmoDirection::Z);
+
+        assert!(totals[0].abs() > 1e-3);
+        for (k, total) in totals.iter().enumerate() {
+            assert!(
+                (total - totals[0] * (k + 1) as f64).abs() < 1e-9,
+                "{totals:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn linear_rotation_turns_like_circular_rotation() {
+        // The Z ring faces the camera
+        let eye = DVec3::new(0.0, 0.0, 5.0);
+        let handle = GizmoHandle::Axis {
+            mode: GizmoMode::Rotate,
+            direction: GizmoDirection::Z,
+        };
+
+        for distance in [5.0, -5.0] {
+            let totals = [RotationInput::Linear, RotationInput::Circular].map(|input| {
+                let mut config = test_config(GizmoMode::Rotate);
+                config.view_matrix = DMat4::look_at_rh(eye, DVec3::ZERO, DVec3::Y).into();
+                config.rotation_input = input;
+                let mut gizmo = Gizmo::new(config);
+                let mut targets = [Transform::default()];
+
+                // Small drag along the ring, perpendicular to the grab point offset from the center
+                let center = screen_pos(&config, DVec3::ZERO);
+                let start = handle_pos(&mut gizmo, &targets, handle);
+                let offset =
+                    DVec2::new(f64::from(start.0 - center.0), f64::from(start.1 - center.1))
+                        .perp()
+                        .normalize()
+                        * distance;
+
+                let results = drag_handle(
+                    &mut gizmo,
+                    &mut targets,
+                    handle,
+                    &[(offset.x as f32, offset.y as f32)],
+                );
+                let GizmoResultKind::Rotation { total, .. } = results[1].kind else {
+                    panic!("unexpected result {:?}", results[1]);
+                };
+                total
+            });
+
+            assert!(totals[0].abs() > 1e-3, "{totals:?}");
+            assert_eq!(totals[0].signum(), totals[1].signum(), "{totals:?}");
+        }
+    }
+
+    #[test]
+    fn auto_rotation_input_switches_when_edge_on() {
+        let eye = DVec3::new(0.0, 0.1, 5.0);
+        let auto = RotationInput::default();
+
+        // The Y ring is seen edge-on
+        assert_eq!(
+            rotation_totals(eye, auto, GizmoDirection::Y),
+            rotation_totals(eye, RotationInput::Linear, GizmoDirection::Y)
+        );
+        assert_eq!(
+            rotation_totals(eye, auto, GizmoDirection::Z),
+            rotation_totals(eye, RotationInput::Circular, GizmoDirection::Z)
+        );
+        assert_ne!(
+            rotation_totals(eye, auto, GizmoDirection::Z),
+            rotation_totals(eye, RotationInput::Linear, GizmoDirection::Z)
+        );
+    }
+
+    #[test]
+    fn mode_switch_fades_subgizmos() {
+        let mut config = test_config(GizmoMode::Translate);
+        config.visuals.fade_duration = 0.2;
+        let mut gizmo = Gizmo::new(config);
+        let targets = [Transform::default()];
+
+        let update = |gizmo: &mut Gizmo, delta_time| {
+            let interaction = GizmoInteraction {
+                delta_time: Some(delta_time),
+                ..Default::default()
+            };
+            gizmo.update(interaction, &targets);
+        };
+        let opacities = |subgizmos: &[SubGizmo]| {
+            subgizmos
+                .iter()
+                .map(|subgizmo| subgizmo.opacity())
+                .collect::<Vec<_>>()
+        };
+
+        update(&mut gizmo, 0.1);
+        assert!(gizmo.is_animating());
+        update(&mut gizmo, 0.1);
+        assert!(opacities(&gizmo.subgizmos)
+            .iter()
+            .all(|&opacity| opacity == 1.0));
+        assert!(!gizmo.is_animating());
+
+        config.modes = GizmoMode::Rotate.into();
+        gizmo.update_config(config);
+        assert!(!gizmo.fading_subgizmos.is_empty());
+
+        update(&mut gizmo, 0.1);
+        assert!(opacities(&gizmo.subgizmos)
+            .iter()
+            .all(|&opacity| opacity == 0.5));
+        assert!(opacities(&gizmo.fading_subgizmos)
+            .iter()
+            .all(|&opacity| opacity == 0.5));
+
+        assert!(gizmo.is_animating());
+
+        update(&mut gizmo, 0.1);
+        assert!(opacities(&gizmo.subgizmos)
+            .iter()
+            .all(|&opacity| opacity == 1.0));
+        assert!(gizmo.fading_subgizmos.is_empty());
+        assert!(!gizmo.is_animating());
+    }
+
+    /// World space primitives after dragging the X translation arrow with the given overlays
+    fn primitives_after_drag(overlays: DragOverlays, snapping: bool) -> Vec<GizmoPrimitive> {
+        let mut config = test_config(GizmoMode::Translate);
+        config.visuals.drag_overlays = overlays;
+        config.snapping = snapping;
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = vec![Transform::default()];
+
+        drag_x_arrow(&mut gizmo, &mut targets, GizmoScene::default(), 20);
+        assert!(targets[0].translation.x > 0.3);
+
+        gizmo.draw_primitives(PrimitiveSpace::World)
+    }
+
+    /// Primitives of `primitives` that are not in `baseline`
+    fn added_primitives(
+        primitives: Vec<GizmoPrimitive>,
+        baseline: &[GizmoPrimitive],
+    ) -> Vec<GizmoPrimitive> {
+        primitives
+            .into_iter()
+            .filter(|primitive| !baseline.contains(primitive))
+            .collect()
+    }
+
+    #[test]
+    fn drag_overlays() {
+        let baseline = primitives_after_drag(DragOverlays::default(), true);
+
+        let guides = DragOverlays {
+            guides: true,
+            ..Default::default()
+        };
+        let added = added_primitives(primitives_after_drag(guides, true), &baseline);
+        // Infinite line along the X axis
+        let [GizmoPrimitive::Polyline { points, .. }] = added.as_slice() else {
+            panic!("unexpected guides {added:?}");
+        };
+        assert!(points[0].x < -1e5 && points[points.len() - 1].x > 1e5);
+        assert!(points.iter().all(|point| point.y == 0.0 && point.z == 0.0));
+
+        let start_ghost = DragOverlays {
+            start_ghost: true,
+            ..Default::default()
+        };
+        let added = added_primitives(primitives_after_drag(start_ghost, true), &baseline);
+        // Filled and outlined circle at the drag start
+        let [GizmoPrimitive::Polygon { points, .. }, GizmoPrimitive::Polyline { .. }] =
+            added.as_slice()
+        else {
+            panic!("unexpected start ghost {added:?}");
+        };
+        let center = points
+            .iter()
+            .map(|&point| DVec3::from(point))
+            .sum::<DVec3>()
+            / points.len() as f64;
+        assert!(center.abs_diff_eq(DVec3::ZERO, 1e-9));
+
+        let snap_ticks = DragOverlays {
+            snap_ticks: true,
+            ..Default::default()
+        };
+        let added = added_primitives(primitives_after_drag(snap_ticks, true), &baseline);
+        // Ticks at the snap distance increments along the X axis
+        assert!(added.len() >= 3);
+        for primitive in &added {
+            let GizmoPrimitive::Polyline { points, .. } = primitive else {
+                panic!("unexpected snap tick {primitive:?}");
+            };
+            let center = (DVec3::from(points[0]) + DVec3::from(points[1])) / 2.0;
+            assert!((center.x / 0.1 - (center.x / 0.1).round()).abs() < 1e-6);
+            assert!(center.y.abs() < 1e-9 && center.z.abs() < 1e-9);
+        }
+        // Only drawn when snapping
+        assert_eq!(
+            primitives_after_drag(snap_ticks, false),
+            primitives_after_drag(DragOverlays::default(), false)
+        );
+    }
+
+    #[test]
+    fn screen_primitives_project_world_primitives() {
+        let config = test_config(GizmoMode::Translate | GizmoMode::Rotate);
+        let mut gizmo = Gizmo::new(config);
+        gizmo.update(hover((0.0, 0.0)), &[Transform::default()]);
+
+        let world = gizmo.draw_primitives(PrimitiveSpace::World);
+        let screen = gizmo.draw_primitives(PrimitiveSpace::Screen);
+        assert!(world
+            .iter()
+            .any(|primitive| matches!(primitive, GizmoPrimitive::Arc { .. })));
+        assert!(world
+            .iter()
+            .any(|primitive| matches!(primitive, GizmoPrimitive::Arrow { .. })));
+
+        // Curves and arrows are converted to polylines and polygons on screen
+        for primitive in &screen {
+            let (GizmoPrimitive::Polyline { points, .. } | GizmoPrimitive::Polygon { points, .. }) =
+                primitive
+            else {
+                panic!("unexpected screen primitive {primitive:?}");
+            };
+            for point in points {
+                assert!(gizmo
+                    .config
+                    .viewport
+                    .contains(Pos2::new(point.x as f32, point.y as f32)));
+            }
+        }
+
+        // World space polylines map to screen space polylines point by point
+        let world_polyline = world
+            .iter()
+            .find_map(|primitive| match primitive {
+                GizmoPrimitive::Polyline { points, .. } => Some(points),
+                _ => None,
+            })
+            .unwrap();
+        let projected = world_polyline
+            .iter()
+            .map(|&point| screen_pos(&config, point.into()))
+            .collect::<Vec<_>>();
+        assert!(screen.iter().any(|primitive| match primitive {
+            GizmoPrimitive::Polyline { points, .. } => points
+                .iter()
+                .map(|point| (point.x as f32, point.y as f32))
+                .eq(projected.iter().copied()),
+            _ => false,
+        }));
+    }
+
+    /// Ray from the camera through the cursor position
+    fn cursor_ray(config: &GizmoConfig, cursor_pos: (f32, f32)) -> GizmoRay {
+        let view_projection =
+            DMat4::from(config.projection_matrix) * DMat4::from(config.view_matrix);
+        let point = screen_to_world(
+            config.viewport,
+            view_projection.inverse(),
+            Pos2::new(cursor_pos.0, cursor_pos.1),
+            0.5,
+        );
+
+        GizmoRay {
+            origin: EYE.into(),
+            direction: (point - EYE).into(),
+        }
+    }
+
+    #[test]
+    fn ray_input_matches_cursor_input() {
+        for mode in [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale] {
+            let config = test_config(mode);
+            let handle = GizmoHandle::Axis {
+                mode,
+                direction: GizmoDirection::X,
+            };
+
+            let mut cursor_gizmo = Gizmo::new(config);
+            let mut ray_gizmo = Gizmo::new(config);
+            let mut cursor_targets = [Transform::default()];
+            let mut ray_targets = [Transform::default()];
+
+            let start = handle_pos(&mut cursor_gizmo, &cursor_targets, handle);
+            for step in 0..8 {
+                let cursor_pos = (start.0 + step as f32 * 4.0, start.1 - step as f32 * 3.0);
+                let interaction = drag(cursor_pos, step == 0);
+                let ray_interaction = GizmoInteraction {
+                    cursor_pos: (0.0, 0.0),
+                    ray: Some(cursor_ray(&config, cursor_pos)),
+                    ..interaction
+                };
+
+                let result = cursor_gizmo.update_into(interaction, &mut cursor_targets);
+                let ray_result = ray_gizmo.update_into(ray_interaction, &mut ray_targets);
+                assert_eq!(result.is_some(), ray_result.is_some());
+                assert_eq!(
+                    result.map(|result| result.handle),
+                    ray_result.map(|result| result.handle)
+                );
+            }
+
+            let affine = |transform: Transform| DAffine3::from(transform);
+            assert!(affine(cursor_targets[0]).abs_diff_eq(affine(ray_targets[0]), 1e-4));
+            assert_ne!(cursor_targets[0], Transform::default());
+        }
+    }
+
+    #[test]
+    fn world_space_ray_drags_targets() {
+        let mut gizmo = Gizmo::new(test_config(GizmoMode::Translate));
+        let mut targets = [Transform::default()];
+
+        // Ray pointing down from above the X arrow
+        let ray = |x: f64, z: f64| GizmoInteraction {
+            ray: Some(GizmoRay {
+                origin: [x, 5.0, z].into(),
+                direction: [0.0, -1.0, 0.0].into(),
+            }),
+            ..Default::default()
+        };
+
+        gizmo.update(ray(0.6, 0.0), &targets);
+        assert_eq!(
+            focused_handle(&gizmo),
+            Some(GizmoHandle::Axis {
+                mode: GizmoMode::Translate,
+                direction: GizmoDirection::X
+            })
+        );
+
+        for (step, (x, z)) in [(0.6, 0.0), (0.7, 0.2), (0.9, 0.3)].into_iter().enumerate() {
+            let interaction = GizmoInteraction {
+                drag_started: step == 0,
+                dragging: true,
+                ..ray(x, z)
+            };
+            gizmo.update_into(interaction, &mut targets);
+        }
+
+        assert!(DVec3::from(targets[0].translation).abs_diff_eq(DVec3::new(0.3, 0.0, 0.0), 1e-9));
+    }
+
+    fn translations(targets: &[Transform]) -> Vec<DVec3> {
+        targets
+            .iter()
+            .map(|target| DVec3::from(target.translation))
+            .collect()
+    }
+
+    #[test]
+    fn align_targets() {
+        let gizmo = Gizmo::new(test_config(GizmoMode::Translate));
+        let targets =
+            [[1.0, 0.0, 2.0], [-1.0, 3.0, 0.0], [4.0, 1.0, -1.0]].map(|translation| Transform {
+                translation: translation.into(),
+                ..Default::default()
+            });
+
+        for (alignment, x) in [
+            (Alignment::Min, -1.0),
+            (Alignment::Center, 1.5),
+            (Alignment::Max, 4.0),
+        ] {
+            let (result, aligned) = gizmo.align(&targets, GizmoDirection::X, alignment).unwrap();
+            for (translation, original) in translations(&aligned).iter().zip(translations(&targets))
+            {
+                assert_eq!(*translation, DVec3::new(x, original.y, original.z));
+            }
+
+            let GizmoResultKind::Arrangement { axis } = result.kind else {
+                panic!("unexpected result {result:?}");
+            };
+            assert_eq!(DVec3::from(axis), DVec3::X);
+            // Each target moved differently, so applying the result does not move any of them
+            for target in &targets {
+                assert_eq!(result.apply_to(target), *target);
+            }
+        }
+
+        assert!(gizmo
+            .align(&targets[..1], GizmoDirection::X, Alignment::Min)
+            .is_none());
+    }
+
+    #[test]
+    fn distribute_targets_along_local_axis() {
+        let mut config = test_config(GizmoMode::Translate);
+        config.orientation = GizmoOrientation::Local;
+        let gizmo = Gizmo::new(config);
+
+        // Targets on a rotated line, oriented along it
+        let rotation = DQuat::from_rotation_z(0.5);
+        let targets = [0.0, 4.0, 1.0, 3.5].map(|position| Transform {
+            rotation: rotation.into(),
+            translation: (rotation * DVec3::new(position, 0.0, 0.0) + DVec3::Y).into(),
+            ..Default::default()
+        });
+
+        let (_, distributed) = gizmo.distribute(&targets, GizmoDirection::X).unwrap();
+        let positions = translations(&distributed)
+            .iter()
+            .map(|&translation| rotation.inverse() * (translation - DVec3::Y))
+            .collect::<Vec<_>>();
+
+        // The extremes stay in place and the order is kept
+        for (position, expected) in positions.iter().zip([0.0, 4.0, 4.0 / 3.0, 8.0 / 3.0]) {
+            assert!(position.abs_diff_eq(DVec3::new(expected, 0.0, 0.0), 1e-9));
+        }
+
+        assert!(gizmo.distribute(&targets[..2], GizmoDirection::X).is_none());
+    }
+
+    #[test]
+    fn arrange_targets_in_scene() {
+        let gizmo = Gizmo::new(test_config(GizmoMode::Translate));
+
+        let parents = [
+            None,
+            Some(DMat4::from_translation(DVec3::new(3.0, 0.0, 0.0)).into()),
+            Some(
+                DMat4::from_scale_rotation_translation(
+                    DVec3::new(-2.0, 1.0, 1.0),
+                    DQuat::from_rotation_y(0.4),
+                    DVec3::new(0.0, 1.0, -1.0),
+                )
+                .into(),
+            ),
+        ];
+        let targets =
+            [[1.0, 0.0, 2.0], [-1.0, 3.0, 0.0], [4.0, 1.0, -1.0]].map(|translation| Transform {
+                translation: translation.into(),
+                ..Default::default()
+            });
+        let world_translations = |targets: &[Transform]| {
+            targets
+                .iter()
+                .zip(parents)
+                .map(|(&target, parent)| {
+                    DVec3::from(
+                        HierarchyTarget::new(target, parent)
+                            .world_transform()
+                            .translation,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Aligned in world space
+        let scene = GizmoScene::new().parents(&parents);
+        let (_, aligned) = gizmo
+            .align_with_scene(&targets, GizmoDirection::X, Alignment::Max, scene)
+            .unwrap();
+        let max = world_translations(&targets)
+            .iter()
+            .map(|translation| translation.x)
+            .fold(f64::MIN, f64::max);
+        for (translation, original) in world_translations(&aligned)
+            .iter()
+            .zip(world_translations(&targets))
+        {
+            assert!(translation.abs_diff_eq(DVec3::new(max, original.y, original.z), 1e-9));
+        }
+
+        // Constrained in world space, starting from the world transforms
+        let constraint = |_: usize, start: &Transform, mut proposed: Transform| {
+            proposed.translation.x = proposed.translation.x.min(start.translation.x + 1.0);
+            proposed
+        };
+        let scene = scene.constraint(&constraint);
+        let (_, aligned) = gizmo
+            .align_with_scene(&targets, GizmoDirection::X, Alignment::Max, scene)
+            .unwrap();
+        for (translation, original) in world_translations(&aligned)
+            .iter()
+            .zip(world_translations(&targets))
+        {
+            let x = max.min(original.x + 1.0);
+            assert!(translation.abs_diff_eq(DVec3::new(x, original.y, original.z), 1e-9));
+        }
+
+        // Flipping a target twice under a mirrored parent restores it
+        let scene = GizmoScene::new().parents(&parents);
+        let (_, flipped) = gizmo
+            .flip_with_scene(&targets, GizmoDirection::Z, scene)
+            .unwrap();
+        let (_, restored) = gizmo
+            .flip_with_scene(&flipped, GizmoDirection::Z, scene)
+            .unwrap();
+        for (target, restored) in targets.iter().zip(&restored) {
+            assert!(DVec3::from(restored.translation).abs_diff_eq(target.translation.into(), 1e-9));
+            assert!(DVec3::from(restored.scale).abs_diff_eq(target.scale.into(), 1e-9));
+        }
+    }
+
+    /// Scale of a target after dragging the X scale handle past the gizmo origin
+    fn scale_past_origin(negative_scaling: bool) -> DVec3 {
+        let mut config = test_config(GizmoMode::Scale);
+        config.negative_scaling = negative_scaling;
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+
+        let origin = screen_pos(&config, DVec3::ZERO);
+        let x = screen_pos(&config, DVec3::X);
+        let direction = DVec2::new((x.0 - origin.0) as f64, (x.1 - origin.1) as f64).normalize();
+        let offsets = (1..30)
+            .map(|step| {
+                let offset = direction * -10.0 * step as f64;
+                (offset.x as f32, offset.y as f32)
+            })
+            .collect::<Vec<_>>();
+
+        let handle = GizmoHandle::Axis {
+            mode: GizmoMode::Scale,
+            direction: GizmoDirection::X,
+        };
+        drag_handle(&mut gizmo, &mut targets, handle, &offsets);
+
+        targets[0].scale.into()
+    }
+
+    #[test]
+    fn negative_scaling_mirrors_past_origin() {
+        let scale = scale_past_origin(true);
+        assert!(scale.x < -0.5);
+        assert_eq!((scale.y, scale.z), (1.0, 1.0));
+
+        // The distance from the origin grows again past it
+        let scale = scale_past_origin(false);
+        assert!(scale.x > 0.5);
+        assert_eq!((scale.y, scale.z), (1.0, 1.0));
+    }
+
+    #[test]
+    fn results_apply_like_updates() {
+        let start_targets = [
+            Transform::from_scale_rotation_translation(
+                [1.0, 2.0, 1.0],
+                DQuat::from_rotation_y(0.4),
+                [0.5, 0.0, -0.5],
+            ),
+            Transform::from_scale_rotation_translation(
+                [0.5, 0.5, 1.5],
+                DQuat::from_rotation_x(-0.7),
+                [-0.5, 0.5, 0.0],
+            ),
+        ];
+
+        let handles = [
+            GizmoHandle::Axis {
+                mode: GizmoMode::Translate,
+                direction: GizmoDirection::X,
+            },
+            GizmoHandle::Plane {
+                mode: GizmoMode::Translate,
+                direction: GizmoDirection::Y,
+            },
+            GizmoHandle::Axis {
+                mode: GizmoMode::Rotate,
+                direction: GizmoDirection::Z,
+            },
+            GizmoHandle::Axis {
+                mode: GizmoMode::Rotate,
+                direction: GizmoDirection::View,
+            },
+            GizmoHandle::Arcball,
+            GizmoHandle::Axis {
+                mode: GizmoMode::Scale,
+                direction: GizmoDirection::X,
+            },
+        ];
+        let offsets = (1..6)
+            .map(|step| (step as f32 * 7.0, step as f32 * 3.0))
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            for orientation in [GizmoOrientation::Global, GizmoOrientation::Local] {
+                for pivot_point in [
+                    TransformPivotPoint::MedianPoint,
+                    TransformPivotPoint::IndividualOrigins,
+                ] {
+                    let mode = match handle {
+                        GizmoHandle::Axis { mode, .. } | GizmoHandle::Plane { mode, .. } => mode,
+                        GizmoHandle::Arcball => GizmoMode::Rotate,
+                    };
+                    let mut config = test_config(mode);
+                    config.orientation = orientation;
+                    config.pivot_point = pivot_point;
+                    let mut gizmo = Gizmo::new(config);
+
+                    let mut targets = start_targets;
+                    let mut applied = start_targets;
+                    for result in drag_handle(&mut gizmo, &mut targets, handle, &offsets) {
+                        assert_eq!(result.handle, Some(handle));
+                        for target in &mut applied {
+                            *target = result.apply_to(target);
+                        }
+                    }
+
+                    for (target, applied) in targets.iter().zip(&applied) {
+                        assert!(
+                            DAffine3::from(*target).abs_diff_eq(DAffine3::from(*applied), 1e-9),
+                            "{handle:?} {orientation:?} {pivot_point:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Results of dragging the X translation arrow by growing cursor offsets,
+    /// with the modifiers of each update given by `modifiers`
+    fn drag_with_modifiers(
+        step_size: f32,
+        modifiers: impl Fn(usize) -> GizmoModifiers,
+    ) -> Vec<GizmoResult> {
+        let config = test_config(GizmoMode::Translate);
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = [Transform::default()];
+
+        let start = screen_pos(&config, DVec3::new(0.6, 0.0, 0.0));
+        gizmo.update(hover(start), &targets);
+
+        (0..8)
+            .map(|step| {
+                let cursor_pos = (start.0 + step as f32 * step_size, start.1 + 1.0);
+                let interaction = GizmoInteraction {
+                    modifiers: modifiers(step),
+                    ..drag(cursor_pos, step == 0)
+                };
+                gizmo.update_into(interaction, &mut targets).unwrap()
+            })
+            .collect()
+    }
+
+    fn translation_total(result: &GizmoResult) -> f64 {
+        let GizmoResultKind::Translation { total, .. } = result.kind else {
+            panic!("unexpected result {result:?}");
+        };
+        total.x
+    }
+
+    #[test]
+    fn precision_modifier_slows_down_drags() {
+        let precision = GizmoModifiers {
+            precision: true,
+            ..Default::default()
+        };
+        let precise = drag_with_modifiers(20.0, |_| precision);
+        // The default precision factor is 0.1
+        let slow = drag_with_modifiers(2.0, |_| GizmoModifiers::default());
+
+        for (precise, slow) in precise.iter().zip(&slow) {
+            assert!((translation_total(precise) - translation_total(slow)).abs() < 1e-6);
+        }
+        assert!(translation_total(&precise[7]) > 0.01);
+    }
+
+    #[test]
+    fn precision_modifier_slows_down_ray_drags() {
+        // Controller ray pointing down from above the X arrow, moving along it
+        let drag_ray = |step_size: f64, precision: bool| {
+            let mut gizmo = Gizmo::new(test_config(GizmoMode::Translate));
+            let mut targets = [Transform::default()];
+
+            (0..8)
+                .map(|step| {
+                    let interaction = GizmoInteraction {
+                        ray: Some(GizmoRay {
+                            origin: [0.6 + step as f64 * step_size, 5.0, 0.0].into(),
+                            direction: [0.0, -1.0, 0.0].into(),
+                        }),
+                        drag_started: step == 0,
+                        dragging: true,
+                        modifiers: GizmoModifiers {
+                            precision,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    };
+                    let result = gizmo.update_into(interaction, &mut targets).unwrap();
+                    translation_total(&result)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let precise = drag_ray(0.1, true);
+        let slow = drag_ray(0.01, false);
+        for (precise, slow) in precise.iter().zip(&slow) {
+            assert!((precise - slow).abs() < 1e-6, "{precise} {slow}");
+        }
+        assert!((precise[7] - 0.07).abs() < 1e-6);
+    }
+
+    #[test]
+    fn snapping_modifier_inverts_snapping() {
+        let invert_snapping = GizmoModifiers {
+            invert_snapping: true,
+            ..Default::default()
+        };
+        let snapped = drag_with_modifiers(7.0, |_| invert_snapping);
+        let free = drag_with_modifiers(7.0, |_| GizmoModifiers::default());
+
+        for result in &snapped {
+            let increments = translation_total(result) / f64::from(DEFAULT_SNAP_DISTANCE);
+            assert!((increments - increments.round()).abs() < 1e-6);
+        }
+        assert!(translation_total(&snapped[7]) > 0.0);
+        assert_ne!(
+            snapped.iter().map(translation_total).collect::<Vec<_>>(),
+            free.iter().map(translation_total).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn duplicate_modifier_is_kept_for_the_drag() {
+        // Only held when the drag starts
+        let duplicated = drag_with_modifiers(5.0, |step| GizmoModifiers {
+            duplicate: step == 0,
+            ..Default::default()
+        });
+        assert!(duplicated.iter().all(|result| result.duplicate));
+
+        // Not held when the drag starts
+        let moved = drag_with_modifiers(5.0, |step| GizmoModifiers {
+            duplicate: step > 0,
+            ..Default::default()
+        });
+        assert!(moved.iter().all(|result| !result.duplicate));
+    }
+
+    /// Interaction that presses an axis key in the middle of a drag.
+    fn press_key(
+        cursor_pos: (f32, f32),
+        direction: GizmoDirection,
+        exclude: bool,
+    ) -> GizmoInteraction {
+        GizmoInteraction {
+            axis_key: Some(AxisKey { direction, exclude }),
+            ..drag(cursor_pos, false)
+        }
+    }
+
+    #[test]
+    fn axis_key_cycles_constraint() {
+        let config = test_config(GizmoMode::Translate);
+        let mut gizmo = Gizmo::new(config);
+        let targets = [Transform::default()];
+
+        let start = screen_pos(&config, DVec3::new(0.6, 0.0, 0.0));
+        gizmo.update(hover(start), &targets);
+        gizmo.update(drag(start, true), &targets);
+        assert_eq!(gizmo.axis_constraint(), None);
+
+        let axis_y = |orientation| {
+            Some(AxisConstraint {
+                direction: GizmoDirection::Y,
+                exclude: false,
+                orientation,
+            })
+        };
+        let translate_y = GizmoHandle::Axis {
+            mode: GizmoMode::Translate,
+            direction: GizmoDirection::Y,
+        };
+        let translate_x = GizmoHandle::Axis {
+            mode: GizmoMode::Translate,
+            direction: GizmoDirection::X,
+        };
+
+        // Configured orientation first, then the other one, then off
+        gizmo.update(press_key(start, GizmoDirection::Y, false), &targets);
+        assert_eq!(gizmo.axis_constraint(), axis_y(GizmoOrientation::Global));
+        assert_eq!(focused_handle(&gizmo), Some(translate_y));
+
+        gizmo.update(press_key(start, GizmoDirection::Y, false), &targets);
+        assert_eq!(gizmo.axis_constraint(), axis_y(GizmoOrientation::Local));
+        assert_eq!(focused_handle(&gizmo), Some(translate_y));
+
+        gizmo.update(press_key(start, GizmoDirection::Y, false), &targets);
+        assert_eq!(gizmo.axis_constraint(), None);
+        assert_eq!(focused_handle(&gizmo), Some(translate_x));
+
+        // Releasing ends the constraint
+        gizmo.update(press_key(start, GizmoDirection::Z, false), &targets);
+        assert!(gizmo.axis_constraint().is_some());
+        gizmo.update(hover(start), &targets);
+        assert_eq!(gizmo.axis_constraint(), None);
+    }
+
+    #[test]
+    fn axis_key_excludes_axis() {
+        let config = test_config(GizmoMode::Translate | GizmoMode::Rotate);
+        let mut gizmo = Gizmo::new(config);
+        let targets = [Transform::default()];
+
+        let translate_x = GizmoHandle::Axis {
+            mode: GizmoMode::Translate,
+            direction: GizmoDirection::X,
+        };
+        let start = handle_pos(&mut gizmo, &targets, translate_x);
+        gizmo.update(drag(start, true), &targets);
+        gizmo.update(press_key(start, GizmoDirection::Z, true), &targets);
+        assert!(gizmo
+            .axis_constraint()
+            .is_some_and(|constraint| constraint.exclude));
+        assert_eq!(
+            focused_handle(&gizmo),
+            Some(GizmoHandle::Plane {
+                mode: GizmoMode::Translate,
+                direction: GizmoDirection::Z,
+            })
+        );
+        gizmo.update(hover(start), &targets);
+
+        // Rotations are constrained to the axis instead
+        let rotate_x = GizmoHandle::Axis {
+            mode: GizmoMode::Rotate,
+            direction: GizmoDirection::X,
+        };
+        let start = handle_pos(&mut gizmo, &targets, rotate_x);
+        gizmo.update(drag(start, true), &targets);
+        gizmo.update(press_key(start, GizmoDirection::Z, true), &targets);
+        assert!(gizmo
+            .axis_constraint()
+            .is_some_and(|constraint| !constraint.exclude));
+        assert_eq!(
+            focused_handle(&gizmo),
+            Some(GizmoHandle::Axis {
+                mode: GizmoMode::Rotate,
+                direction: GizmoDirection::Z,
+            })
+        );
+    }
+
+    #[test]
+    fn axis_key_keeps_accumulated_translation() {
+        let config = test_config(GizmoMode::Translate);
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = vec![Transform::default()];
+
+        let results = drag_x_arrow(&mut gizmo, &mut targets, GizmoScene::default(), 6);
+        let (result, _) = results.last().unwrap();
+        let GizmoResultKind::Translation { total, .. } = result.kind else {
+            panic!("unexpected result {result:?}");
+        };
+        let moved = total.x;
+        assert!(moved > 0.1);
+
+        let start = screen_pos(&config, DVec3::new(0.6, 0.0, 0.0));
+        let cursor_pos = (start.0 + 25.0, start.1);
+        gizmo.update_into(
+            press_key(cursor_pos, GizmoDirection::Y, false),
+            &mut targets,
+        );
+        let result = gizmo
+            .update_into(
+                drag((cursor_pos.0, cursor_pos.1 - 40.0), false),
+                &mut targets,
+            )
+            .unwrap();
+
+        let GizmoResultKind::Translation { total, .. } = result.kind else {
+            panic!("unexpected result {result:?}");
+        };
+        assert!((total.x - moved).abs() < 1e-9);
+        assert!(total.y > 0.1);
+        assert!((targets[0].translation.x - moved).abs() < 1e-9);
+        assert!((targets[0].translation.y - total.y).abs() < 1e-9);
+    }
+
+    #[test]
+    fn translation_snaps_per_axis() {
+        let config = GizmoConfig {
+            snapping: true,
+            snap_settings: SnapSettings {
+                distance: [0.25, 2.0, 2.0].into(),
+                ..Default::default()
+            },
+            ..test_config(GizmoMode::Translate)
+        };
+        let mut gizmo = Gizmo::new(config);
+        let mut targets = vec![Transform::default()];
+
+        let results = drag_x_arrow(&mut gizmo, &mut targets, GizmoScene::default(), 12);
+        let totals: Vec<f64> = results
+            .iter()
+            .map(|(result, _)| translation_total(result))
+            .collect();
+
+        for total in &totals {
+            let increments = total / 0.25;
+            assert!((increments - increments.round()).abs() < 1e-6);
+        }
+        assert!(totals.last().is_some_and(|&total| total > 0.0));
+        assert_eq!(gizmo.snap_distance(), mint::Vector3::from([0.25, 2.0, 2.0]));
+    }
+
+    /// Snapping configuration with adaptive distance increments, viewed from `eye`
+    fn adaptive_snap_config(eye: DVec3) -> GizmoConfig {
+        GizmoConfig {
+            view_matrix: DMat4::look_at_rh(eye, DVec3::ZERO, DVec3::Y).into(),
+            snapping: true,
+            snap_settings: SnapSettings {
+                adaptive_distance: Some(AdaptiveSnap::default()),
+                ..Default::default()
+            },
+            ..test_config(GizmoMode::Translate)
+        }
+    }
+
+    #[test]
+    fn adaptive_snap_follows_zoom() {
+        let min_pixels = f64::from(AdaptiveSnap::default().min_pixels);
+
+        let mut increments = Vec::new();
+        for zoom in [0.1, 1.0, 10.0] {
+            let gizmo = Gizmo::new(adaptive_snap_config(EYE * zoom));
+            let increment = gizmo.snap_distance().x;
+            assert_eq!(gizmo.snap_distance(), [increment; 3].into());
+
+            // A power of ten at least a few pixels long
+            assert!((increment.log10() - increment.log10().round()).abs() < 1e-9);
+            let pixels = increment / f64::from(gizmo.config.pixel_size);
+            assert!(pixels >= min_pixels - 1e-6 && pixels < 10.0 * min_pixels);
+            increments.push(increment);
+        }
+        assert!(increments[0] < increments[1] && increments[1] < increments[2]);
+    }
+
+    #[test]
+    fn adaptive_snap_at_exact_power_of_base() {
+        let mut config = adaptive_snap_config(EYE);
+        config.snap_settings.adaptive_distance = Some(AdaptiveSnap {
+            min_pixels: 25.0,
+            base: 5.0,
+        });
+        let mut gizmo = Gizmo::new(config);
+
+        // The minimum distance is exactly 5^3, which the logarithm puts slightly above 3
+        gizmo.config.pixel_size = 5.0;
+        assert_eq!(gizmo.snap_distance(), [125.0; 3].into());
+
+        gizmo.config.pixel_size = 5.5;
+        assert_eq!(gizmo.snap_distance(), [625.0; 3].into());
+    }
+
+    #[test]
+    fn adaptive_snap_is_kept_during_drag() {
+        let mut gizmo = Gizmo::new(adaptive_snap_config(EYE));
+        let mut targets = vec![Transform::default()];
+        let increment = gizmo.snap_distance().x;
+
+        let results = drag_x_arrow(&mut gizmo, &mut targets, GizmoScene::default(), 12);
+        for (result, _) in &results {
+            let increments = translation_total(result) / increment;
+            assert!((increments - increments.round()).abs() < 1e-6);
+        }
+        assert!(translation_total(&results.last().unwrap().0) > 0.0);
+
+        // Zooming out in the middle of the drag
+        gizmo.update_config(adaptive_snap_config(EYE * 10.0));
+        assert_eq!(gizmo.snap_distance().x, increment);
+
+        gizmo.update(hover((0.0, 0.0)), &targets);
+        assert!(gizmo.snap_distance().x > increment);
+    }
 }
@@ -0,0 +1,306 @@
+//! Conversions between [`Transform`] and the transform types of other math libraries.
+//!
+//! Conversions to types that only support uniform scale fail with a [`TransformConversionError`]
+//! if the scale of the [`Transform`] differs between the axes, or is zero along some axis.
+//! Conversions to types without a scale fail unless the scale is one along every axis.
+
+use crate::math::{DQuat, DVec3, Transform, TransformConversionError};
+
+/// Largest relative difference between the scale components of a uniform scale,
+/// which allows for rounding errors of earlier transformations
+const UNIFORM_SCALE_TOLERANCE: f64 = 1e-9;
+
+/// Uniform scale of the transform, if it has one
+fn uniform_scale(transform: &Transform) -> Result<f64, TransformConversionError> {
+    let scale = DVec3::from(transform.scale);
+    if !scale.is_finite() || scale.cmpeq(DVec3::ZERO).any() {
+        return Err(TransformConversionError::InvalidScale);
+    }
+
+    let uniform = scale.element_sum() / 3.0;
+    if (scale - uniform).abs().max_element() > uniform.abs() * UNIFORM_SCALE_TOLERANCE {
+        return Err(TransformConversionError::NonUniformScale);
+    }
+
+    Ok(uniform)
+}
+
+/// Checks that the transform has no scale, within [`UNIFORM_SCALE_TOLERANCE`]
+fn unit_scale(transform: &Transform) -> Result<(), TransformConversionError> {
+    let scale = uniform_scale(transform)?;
+    if (scale - 1.0).abs() > UNIFORM_SCALE_TOLERANCE {
+        return Err(TransformConversionError::NonUnitScale);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_interop {
+    use nalgebra::{
+        Isometry3, Quaternion, RealField, Similarity3, Translation3, UnitQuaternion, Vector3,
+    };
+
+    use super::{uniform_scale, unit_scale, DQuat, DVec3, Transform, TransformConversionError};
+
+    fn vector<T: RealField + Copy>(vector: Vector3<T>) -> DVec3 {
+        DVec3::new(
+            nalgebra::convert_unchecked(vector.x),
+            nalgebra::convert_unchecked(vector.y),
+            nalgebra::convert_unchecked(vector.z),
+        )
+    }
+
+    fn quaternion<T: RealField + Copy>(quaternion: UnitQuaternion<T>) -> DQuat {
+        DQuat::from_xyzw(
+            nalgebra::convert_unchecked(quaternion.i),
+            nalgebra::convert_unchecked(quaternion.j),
+            nalgebra::convert_unchecked(quaternion.k),
+            nalgebra::convert_unchecked(quaternion.w),
+        )
+    }
+
+    fn isometry<T: RealField + Copy>(transform: &Transform) -> Isometry3<T> {
+        let translation = transform.translation;
+        let rotation = transform.rotation;
+
+        Isometry3::from_parts(
+            Translation3::new(
+                nalgebra::convert(translation.x),
+                nalgebra::convert(translation.y),
+                nalgebra::convert(translation.z),
+            ),
+            UnitQuaternion::new_normalize(Quaternion::new(
+                nalgebra::convert(rotation.s),
+                nalgebra::convert(rotation.v.x),
+                nalgebra::convert(rotation.v.y),
+                nalgebra::convert(rotation.v.z),
+            )),
+        )
+    }
+
+    impl<T: RealField + Copy> From<Isometry3<T>> for Transform {
+        fn from(isometry: Isometry3<T>) -> Self {
+            Self::from_scale_rotation_translation(
+                DVec3::ONE,
+                quaternion(isometry.rotation),
+                vector(isometry.translation.vector),
+            )
+        }
+    }
+
+    impl<T: RealField + Copy> TryFrom<Transform> for Isometry3<T> {
+        type Error = TransformConversionError;
+
+        fn try_from(transform: Transform) -> Result<Self, Self::Error> {
+            unit_scale(&transform)?;
+            Ok(isometry(&transform))
+        }
+    }
+
+    impl<T: RealField + Copy> From<Similarity3<T>> for Transform {
+        fn from(similarity: Similarity3<T>) -> Self {
+            Self::from_scale_rotation_translation(
+                DVec3::splat(nalgebra::convert_unchecked(similarity.scaling())),
+                quaternion(similarity.isometry.rotation),
+                vector(similarity.isometry.translation.vector),
+            )
+        }
+    }
+
+    impl<T: RealField + Copy> TryFrom<Transform> for Similarity3<T> {
+        type Error = TransformConversionError;
+
+        fn try_from(transform: Transform) -> Result<Self, Self::Error> {
+            let scale = uniform_scale(&transform)?;
+            Ok(Self::from_isometry(
+                isometry(&transform),
+                nalgebra::convert(scale),
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "cgmath")]
+mod cgmath_interop {
+    use cgmath::num_traits::NumCast;
+    use cgmath::{BaseFloat, Decomposed, Quaternion, Vector3};
+
+    use super::{uniform_scale, DQuat, DVec3, Transform, TransformConversionError};
+
+    fn to_f64<S: BaseFloat>(value: S) -> f64 {
+        value.to_f64().unwrap_or(f64::NAN)
+    }
+
+    fn from_f64<S: BaseFloat>(value: f64) -> S {
+        <S as NumCast>::from(value).unwrap_or_else(S::nan)
+    }
+
+    impl<S: BaseFloat> From<Decomposed<Vector3<S>, Quaternion<S>>> for Transform {
+        fn from(decomposed: Decomposed<Vector3<S>, Quaternion<S>>) -> Self {
+            let rotation = decomposed.rot;
+            let translation = decomposed.disp;
+
+            Self::from_scale_rotation_translation(
+                DVec3::splat(to_f64(decomposed.scale)),
+                DQuat::from_xyzw(
+                    to_f64(rotation.v.x),
+                    to_f64(rotation.v.y),
+                    to_f64(rotation.v.z),
+                    to_f64(rotation.s),
+                ),
+                DVec3::new(
+                    to_f64(translation.x),
+                    to_f64(translation.y),
+                    to_f64(translation.z),
+                ),
+            )
+        }
+    }
+
+    impl<S: BaseFloat> TryFrom<Transform> for Decomposed<Vector3<S>, Quaternion<S>> {
+        type Error = TransformConversionError;
+
+        fn try_from(transform: Transform) -> Result<Self, Self::Error> {
+            let scale = uniform_scale(&transform)?;
+            let rotation = transform.rotation;
+            let translation = transform.translation;
+
+            Ok(Self {
+                scale: from_f64(scale),
+                rot: Quaternion::new(
+                    from_f64(rotation.s),
+                    from_f64(rotation.v.x),
+                    from_f64(rotation.v.y),
+                    from_f64(rotation.v.z),
+                ),
+                disp: Vector3::new(
+                    from_f64(translation.x),
+                    from_f64(translation.y),
+                    from_f64(translation.z),
+                ),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(scale: [f64; 3]) -> Transform {
+        Transform::from_scale_rotation_translation(
+            scale,
+            DQuat::from_rotation_y(0.5),
+            [1.0, 2.0, 3.0],
+        )
+    }
+
+    #[test]
+    fn uniform_scale_is_required() {
+        assert_eq!(uniform_scale(&transform([2.0; 3])), Ok(2.0));
+        assert_eq!(
+            uniform_scale(&transform([2.0, 2.0, 2.0 + 1e-12])),
+            Ok(2.0 + 1e-12 / 3.0)
+        );
+        assert_eq!(
+            uniform_scale(&transform([1.0, 0.0, 1.0])),
+            Err(TransformConversionError::InvalidScale)
+        );
+        assert_eq!(
+            uniform_scale(&transform([1.0, f64::NAN, 1.0])),
+            Err(TransformConversionError::InvalidScale)
+        );
+        assert_eq!(
+            uniform_scale(&transform([1.0, 2.0, 1.0])),
+            Err(TransformConversionError::NonUniformScale)
+        );
+        // A single mirrored axis
+        assert_eq!(
+            uniform_scale(&transform([-1.0, 1.0, 1.0])),
+            Err(TransformConversionError::NonUniformScale)
+        );
+    }
+
+    #[test]
+    fn unit_scale_is_required() {
+        assert_eq!(unit_scale(&transform([1.0; 3])), Ok(()));
+        assert_eq!(unit_scale(&transform([1.0 + 1e-12; 3])), Ok(()));
+        assert_eq!(
+            unit_scale(&transform([2.0; 3])),
+            Err(TransformConversionError::NonUnitScale)
+        );
+        assert_eq!(
+            unit_scale(&transform([-1.0; 3])),
+            Err(TransformConversionError::NonUnitScale)
+        );
+        assert_eq!(
+            unit_scale(&transform([1.0, 2.0, 1.0])),
+            Err(TransformConversionError::NonUniformScale)
+        );
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn isometry_conversion() {
+        use nalgebra::Isometry3;
+
+        let original = transform([1.0; 3]);
+        let isometry = Isometry3::<f64>::try_from(original).unwrap();
+        let converted = Transform::from(isometry);
+        assert_eq!(converted.scale, original.scale);
+        assert!(DQuat::from(converted.rotation).abs_diff_eq(original.rotation.into(), 1e-12));
+        assert_eq!(converted.translation, original.translation);
+
+        assert_eq!(
+            Isometry3::<f32>::try_from(transform([2.0; 3])),
+            Err(TransformConversionError::NonUnitScale)
+        );
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn similarity_conversion() {
+        use nalgebra::Similarity3;
+
+        let original = transform([2.0; 3]);
+        let similarity = Similarity3::<f64>::try_from(original).unwrap();
+        let converted = Transform::from(similarity);
+        assert!(DVec3::from(converted.scale).abs_diff_eq(DVec3::splat(2.0), 1e-12));
+        assert!(DQuat::from(converted.rotation).abs_diff_eq(original.rotation.into(), 1e-12));
+        assert_eq!(converted.translation, original.translation);
+
+        assert_eq!(
+            Similarity3::<f32>::try_from(transform([0.0; 3])),
+            Err(TransformConversionError::InvalidScale)
+        );
+        assert_eq!(
+            Similarity3::<f32>::try_from(transform([1.0, 2.0, 3.0])),
+            Err(TransformConversionError::NonUniformScale)
+        );
+    }
+
+    #[cfg(feature = "cgmath")]
+    #[test]
+    fn decomposed_conversion() {
+        use cgmath::{Decomposed, Quaternion, Vector3};
+
+        type Decomposed3 = Decomposed<Vector3<f64>, Quaternion<f64>>;
+
+        let original = transform([2.0; 3]);
+        let decomposed = Decomposed3::try_from(original).unwrap();
+        let converted = Transform::from(decomposed);
+        assert_eq!(converted.scale, original.scale);
+        assert!(DQuat::from(converted.rotation).abs_diff_eq(original.rotation.into(), 1e-12));
+        assert_eq!(converted.translation, original.translation);
+
+        assert_eq!(
+            Decomposed3::try_from(transform([0.0; 3])).err(),
+            Some(TransformConversionError::InvalidScale)
+        );
+        assert_eq!(
+            Decomposed3::try_from(transform([1.0, 2.0, 3.0])).err(),
+            Some(TransformConversionError::NonUniformScale)
+        );
+    }
+}
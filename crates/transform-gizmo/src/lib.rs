@@ -15,16 +15,42 @@
 //! For rendering the gizmo, [`Gizmo::draw`] provides vertices in viewport coordinates that can be easily rendered
 //! with your favorite graphics APIs.
 //!
+//! # Features
+//!
+//! - `epaint` *(enabled by default)*: Tessellates the gizmo with the [egui](https://github.com/emilk/egui) painting
+//!   crate `epaint`, and implements conversions between [`Rect`], [`math::Pos2`], [`math::Vec2`] and [`Color32`]
+//!   and the corresponding types of `emath` and `ecolor`. When disabled, the crate has no egui dependencies
+//!   and uses a lightweight built-in tessellator instead. The public types are the same either way.
+//! - `serde`: Implements `Serialize` and `Deserialize` for the configuration, interaction, result and transform
+//!   types, so that [`GizmoRecording`]s of interactions can be saved and replayed later.
+//! - `nalgebra`: Implements conversions between [`math::Transform`] and the `Isometry3` and `Similarity3` types of
+//!   [nalgebra](https://nalgebra.org).
+//! - `cgmath`: Implements conversions between [`math::Transform`] and the `Decomposed` type of
+//!   [cgmath](https://github.com/rustgd/cgmath).
+//!
+//! Conversions between [`math::Transform`] and the `DAffine3` and `Affine3A` types of `glam` are always available.
+//!
 //! For a more complete example, see the online demo at <https://urholaukkarinen.github.io/transform-gizmo/>.
 //! The demo sources can be found at <https://github.com/urholaukkarinen/transform-gizmo/blob/main/examples/bevy/src/main.rs>.
 
+mod color;
+#[cfg(any(feature = "nalgebra", feature = "cgmath"))]
+mod interop;
+mod screen;
 mod shape;
 mod subgizmo;
+#[cfg(not(feature = "epaint"))]
+mod tessellator;
+#[cfg(test)]
+mod test_utils;
 
 pub mod config;
 pub mod gizmo;
 pub mod math;
 
 pub mod prelude;
+pub mod recording;
+pub mod result;
+pub mod scene;
 
 pub use prelude::*;
@@ -1,7 +1,12 @@
-pub use emath::{Pos2, Rect, Vec2};
-pub use glam::{DMat3, DMat4, DQuat, DVec2, DVec3, DVec4, Mat4, Quat, Vec3, Vec4Swizzles};
+use std::f64::consts::{PI, TAU};
+
+pub use crate::screen::{Pos2, Rect, Vec2};
+pub use glam::{
+    Affine3A, DAffine3, DMat3, DMat4, DQuat, DVec2, DVec3, DVec4, Mat4, Quat, Vec3, Vec4Swizzles,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transform {
     pub scale: mint::Vector3<f64>,
     pub rotation: mint::Quaternion<f64>,
@@ -30,6 +35,258 @@ impl Transform {
             translation: translation.into(),
         }
     }
+
+    /// Creates a transform from single precision scale, rotation and translation.
+    pub fn from_scale_rotation_translation_f32(
+        scale: impl Into<mint::Vector3<f32>>,
+        rotation: impl Into<mint::Quaternion<f32>>,
+        translation: impl Into<mint::Vector3<f32>>,
+    ) -> Self {
+        Self::from_scale_rotation_translation(
+            Vec3::from(scale.into()).as_dvec3(),
+            Quat::from(rotation.into()).as_dquat(),
+            Vec3::from(translation.into()).as_dvec3(),
+        )
+    }
+
+    /// Scale, rotation and translation of the transform in single precision.
+    pub fn to_scale_rotation_translation_f32(
+        self,
+    ) -> (
+        mint::Vector3<f32>,
+        mint::Quaternion<f32>,
+        mint::Vector3<f32>,
+    ) {
+        (
+            DVec3::from(self.scale).as_vec3().into(),
+            DQuat::from(self.rotation).as_quat().into(),
+            DVec3::from(self.translation).as_vec3().into(),
+        )
+    }
+}
+
+impl From<DAffine3> for Transform {
+    fn from(affine: DAffine3) -> Self {
+        let (scale, rotation, translation) = affine.to_scale_rotation_translation();
+        Self::from_scale_rotation_translation(scale, rotation, translation)
+    }
+}
+
+impl From<Transform> for DAffine3 {
+    fn from(transform: Transform) -> Self {
+        Self::from_scale_rotation_translation(
+            transform.scale.into(),
+            transform.rotation.into(),
+            transform.translation.into(),
+        )
+    }
+}
+
+impl From<Affine3A> for Transform {
+    fn from(affine: Affine3A) -> Self {
+        let (scale, rotation, translation) = affine.to_scale_rotation_translation();
+        Self::from_scale_rotation_translation_f32(scale, rotation, translation)
+    }
+}
+
+impl From<Transform> for Affine3A {
+    fn from(transform: Transform) -> Self {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation_f32();
+        Self::from_scale_rotation_translation(scale.into(), rotation.into(), translation.into())
+    }
+}
+
+/// A [`Transform`] that can not be converted to the transform type of another math library.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TransformConversionError {
+    /// The scale is zero or not finite along some axis
+    InvalidScale,
+    /// The scale differs between the axes, but the converted type only supports a uniform scale
+    NonUniformScale,
+    /// The scale is not one along every axis, but the converted type has no scale
+    NonUnitScale,
+}
+
+impl std::fmt::Display for TransformConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::InvalidScale => "scale must be finite and non-zero along every axis",
+            Self::NonUniformScale => "scale must be the same along every axis",
+            Self::NonUnitScale => "scale must be one along every axis",
+        };
+
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for TransformConversionError {}
+
+/// Order in which the rotations of Euler angles are applied.
+///
+/// Orders are named in the order the rotations are applied, as in most animation software.
+/// For example with [`EulerOrder::XYZ`], the rotation around X is applied first and
+/// the rotation around Z last, so that the combined rotation is `Rz * Ry * Rx`.
+///
+/// Euler angles are always given per axis, in radians, regardless of the order.
+///
+/// # Examples
+///
+/// ```
+/// # use transform_gizmo::math::{DQuat, EulerOrder};
+/// let order = EulerOrder::XYZ;
+/// let angles = [0.1, 0.2, 3.0].into();
+/// let rotation = DQuat::from(order.rotation(angles));
+///
+/// // Rotating past 180 degrees continues from the previous angles instead of wrapping around
+/// let rotated = DQuat::from_rotation_z(0.3) * rotation;
+/// let deltas = order.angle_deltas(angles, rotated.into());
+/// assert!((deltas.z - 0.3).abs() < 1e-9);
+/// assert!(deltas.x.abs() < 1e-9 && deltas.y.abs() < 1e-9);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EulerOrder {
+    #[default]
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+impl EulerOrder {
+    /// Indices of the axes, in the order their rotations are applied
+    fn axes(self) -> [usize; 3] {
+        match self {
+            Self::XYZ => [0, 1, 2],
+            Self::XZY => [0, 2, 1],
+            Self::YXZ => [1, 0, 2],
+            Self::YZX => [1, 2, 0],
+            Self::ZXY => [2, 0, 1],
+            Self::ZYX => [2, 1, 0],
+        }
+    }
+
+    /// Rotation of the given Euler angles.
+    pub fn rotation(self, angles: mint::Vector3<f64>) -> mint::Quaternion<f64> {
+        let angles = DVec3::from(angles);
+
+        self.axes()
+            .iter()
+            .fold(DQuat::IDENTITY, |rotation, &axis| {
+                axis_rotation(axis, angles[axis]) * rotation
+            })
+            .into()
+    }
+
+    /// Euler angles of the given rotation.
+    ///
+    /// The angle of the second applied axis is within `[-PI/2, PI/2]`,
+    /// the others within `[-PI, PI]`.
+    pub fn angles(self, rotation: mint::Quaternion<f64>) -> mint::Vector3<f64> {
+        let [first, second, last] = self.decompose(DQuat::from(rotation));
+
+        let [a, b, c] = self.axes();
+        let mut angles = DVec3::ZERO;
+        angles[a] = first;
+        angles[b] = second;
+        angles[c] = last;
+        angles.into()
+    }
+
+    /// Euler angles of the given rotation, closest to the `reference` angles.
+    ///
+    /// Each rotation can be expressed with several sets of Euler angles. Picking the set closest
+    /// to the previous angles of a target keeps its angles continuous while it is rotated, instead
+    /// of flipping between equivalent sets or wrapping around at 180 degrees.
+    pub fn angles_near(
+        self,
+        rotation: mint::Quaternion<f64>,
+        reference: mint::Vector3<f64>,
+    ) -> mint::Vector3<f64> {
+        let reference = DVec3::from(reference);
+        let angles = DVec3::from(self.angles(rotation));
+
+        // The same rotation, with the second angle mirrored around 90 degrees
+        let [a, b, c] = self.axes();
+        let mut alternative = angles;
+        alternative[a] += PI;
+        alternative[b] = PI - alternative[b];
+        alternative[c] += PI;
+
+        let unwrap = |angles: DVec3| angles + ((reference - angles) / TAU).round() * TAU;
+        let angles = unwrap(angles);
+        let alternative = unwrap(alternative);
+
+        if alternative.distance_squared(reference) < angles.distance_squared(reference) {
+            alternative.into()
+        } else {
+            angles.into()
+        }
+    }
+
+    /// Change of the Euler `angles` of a target, when it is rotated to `rotation`.
+    ///
+    /// `angles` are the Euler angles of the target before it was rotated. Adding the returned
+    /// deltas to them gives the angles of `rotation`, closest to `angles`.
+    /// See [`EulerOrder::angles_near`].
+    pub fn angle_deltas(
+        self,
+        angles: mint::Vector3<f64>,
+        rotation: mint::Quaternion<f64>,
+    ) -> mint::Vector3<f64> {
+        (DVec3::from(self.angles_near(rotation, angles)) - DVec3::from(angles)).into()
+    }
+
+    /// Orientation of the gimbal ring of the given axis, for a target with the given rotation.
+    ///
+    /// Rotating around the returned axes only changes the Euler angle of that axis.
+    pub(crate) fn gimbal_rotation(self, rotation: DQuat, axis: usize) -> DQuat {
+        let [first, second, last] = self.axes();
+
+        if axis == first {
+            rotation
+        } else if axis == second {
+            axis_rotation(last, self.decompose(rotation)[2])
+        } else {
+            DQuat::IDENTITY
+        }
+    }
+
+    /// Euler angles of the given rotation, in the order they are applied
+    fn decompose(self, rotation: DQuat) -> [f64; 3] {
+        let [c, b, a] = self.axes();
+        let m = DMat3::from_quat(rotation.normalize());
+        let m = |row: usize, col: usize| m.col(col)[row];
+
+        // Rotation is Ra * Rb * Rc. The sign depends on the handedness of the axis permutation.
+        let sign = if (b + 3 - a) % 3 == 1 { 1.0 } else { -1.0 };
+
+        let sin_b = (sign * m(a, c)).clamp(-1.0, 1.0);
+        let angle_b = sin_b.asin();
+
+        let (angle_a, angle_c) = if sin_b.abs() < 1.0 - 1e-12 {
+            (
+                f64::atan2(-sign * m(b, c), m(c, c)),
+                f64::atan2(-sign * m(a, b), m(a, a)),
+            )
+        } else {
+            // Gimbal lock, only the sum or difference of the first and last angle is known
+            (f64::atan2(sign * m(c, b), m(b, b)), 0.0)
+        };
+
+        [angle_c, angle_b, angle_a]
+    }
+}
+
+/// Rotation around the axis with the given index
+fn axis_rotation(axis: usize, angle: f64) -> DQuat {
+    match axis {
+        0 => DQuat::from_rotation_x(angle),
+        1 => DQuat::from_rotation_y(angle),
+        _ => DQuat::from_rotation_z(angle),
+    }
 }
 
 /// Creates a matrix that represents rotation between two 3d vectors
@@ -221,3 +478,70 @@ pub(crate) fn screen_to_world(viewport: Rect, mat: DMat4, pos: Pos2, z: f64) ->
 
     world_pos.xyz()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDERS: [EulerOrder; 6] = [
+        EulerOrder::XYZ,
+        EulerOrder::XZY,
+        EulerOrder::YXZ,
+        EulerOrder::YZX,
+        EulerOrder::ZXY,
+        EulerOrder::ZYX,
+    ];
+
+    fn rotations_eq(a: DQuat, b: DQuat) -> bool {
+        a.dot(b).abs() > 1.0 - 1e-12
+    }
+
+    #[test]
+    fn euler_angles_roundtrip() {
+        let rotation = DQuat::from_euler(glam::EulerRot::XYZ, 0.4, -1.1, 2.5);
+        for order in ORDERS {
+            let angles = order.angles(rotation.into());
+            assert!(
+                rotations_eq(order.rotation(angles).into(), rotation),
+                "{order:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn euler_angles_stay_continuous() {
+        for order in ORDERS {
+            // Past 180 degrees, and past the 90 degree limit of the second axis
+            for axis in 0..3 {
+                let mut previous = DVec3::new(0.2, -0.3, 0.1);
+                for _ in 0..20 {
+                    let mut angles = previous;
+                    angles[axis] += 0.3;
+
+                    let rotation = order.rotation(angles.into());
+                    let near = DVec3::from(order.angles_near(rotation, previous.into()));
+                    assert!(near.abs_diff_eq(angles, 1e-9), "{order:?} {axis}");
+                    previous = angles;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gimbal_rings_change_a_single_angle() {
+        // Within the range of the second angle of each order
+        let angles = DVec3::new(0.7, -0.4, 1.2);
+        for order in ORDERS {
+            let rotation = DQuat::from(order.rotation(angles.into()));
+            for axis in 0..3 {
+                let ring = order.gimbal_rotation(rotation, axis);
+                let rotated = DQuat::from_axis_angle(ring * DVec3::AXES[axis], 0.25) * rotation;
+
+                let mut expected = DVec3::ZERO;
+                expected[axis] = 0.25;
+                let deltas = DVec3::from(order.angle_deltas(angles.into(), rotated.into()));
+                assert!(deltas.abs_diff_eq(expected, 1e-9), "{order:?} {axis}");
+            }
+        }
+    }
+}
@@ -1,11 +1,20 @@
 pub use crate::config::{
-    GizmoConfig, GizmoDirection, GizmoMode, GizmoOrientation, GizmoVisibility, GizmoVisuals,
+    AdaptiveSnap, AngleSnap, GizmoConfig, GizmoConfigError, GizmoDirection, GizmoMode,
+    GizmoOrientation, GizmoVisibility, GizmoVisuals, SnapAngles, SnapSettings,
+};
+pub use crate::gizmo::{
+    AxisConstraint, AxisKey, Gizmo, GizmoDrawData, GizmoInteraction, GizmoModifiers,
+    GizmoPrimitive, GizmoRay, PrimitiveSpace,
+};
+pub use crate::recording::{GizmoRecorder, GizmoRecording};
+pub use crate::result::{GizmoFrame, GizmoHandle, GizmoResult, GizmoResultKind};
+pub use crate::scene::{
+    GizmoScene, HierarchyTarget, SnapPointProvider, SurfaceHit, SurfaceRaycast, TransformConstraint,
 };
-pub use crate::gizmo::{Gizmo, GizmoDrawData, GizmoInteraction, GizmoResult};
 
 pub use enumset::{enum_set, EnumSet};
 
 pub use mint;
 
-pub use ecolor::Color32;
-pub use emath::Rect;
+pub use crate::config::Color32;
+pub use crate::math::{EulerOrder, Rect};
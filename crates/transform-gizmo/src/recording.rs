@@ -0,0 +1,823 @@
+//! Recording and deterministic replay of gizmo interactions.
+//!
+//! A [`GizmoRecorder`] wraps a [`Gizmo`] and records every configuration change, update
+//! and arrangement of targets, along with the results produced. The answers of the
+//! [`GizmoScene`] queries made during the updates are recorded too, so that no scene is needed
+//! for the replay. The resulting [`GizmoRecording`] can be replayed later
+//! to reproduce the exact same results, for example to reproduce bug reports or to build
+//! regression tests from real sessions.
+//!
+//! With the `serde` feature enabled, recordings can be serialized with any `serde` format.
+//! For replays to be exact, the format must preserve floating point values exactly, for example
+//! `serde_json` with its `float_roundtrip` feature enabled.
+
+use std::cell::{Cell, RefCell};
+use std::ops::Deref;
+
+use crate::config::{Alignment, GizmoConfig, GizmoDirection};
+use crate::gizmo::{Gizmo, GizmoInteraction, GizmoRay};
+use crate::math::Transform;
+use crate::result::GizmoResult;
+use crate::scene::{
+    GizmoScene, SnapPointProvider, SurfaceHit, SurfaceRaycast, TransformConstraint,
+};
+
+/// Result of a single [`Gizmo::update`] call.
+pub type GizmoUpdateResult = Option<(GizmoResult, Vec<Transform>)>;
+
+/// A [`Gizmo`] that records all of its inputs and results.
+///
+/// Dereferences to the recorded [`Gizmo`], for drawing and inspecting it.
+///
+/// # Examples
+///
+/// ```
+/// # use transform_gizmo::prelude::*;
+/// # use transform_gizmo::math::Transform;
+/// # let config = GizmoConfig::default();
+/// # let interaction = GizmoInteraction::default();
+/// let mut recorder = GizmoRecorder::new(config);
+/// let targets = [Transform::default()];
+///
+/// recorder.update(interaction, &targets);
+///
+/// let recording = recorder.into_recording();
+/// assert_eq!(recording.verify(), Ok(()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct GizmoRecorder {
+    gizmo: Gizmo,
+    recording: GizmoRecording,
+}
+
+impl GizmoRecorder {
+    /// Creates a new gizmo from given configuration, and starts recording it.
+    pub fn new(config: GizmoConfig) -> Self {
+        Self {
+            gizmo: Gizmo::new(config),
+            recording: GizmoRecording {
+                events: vec![RecordedEvent::Config(Box::new(config))],
+            },
+        }
+    }
+
+    /// Updates the configuration used by the gizmo. See [`Gizmo::update_config`].
+    pub fn update_config(&mut self, config: GizmoConfig) {
+        self.gizmo.update_config(config);
+        self.recording
+            .events
+            .push(RecordedEvent::Config(Box::new(config)));
+    }
+
+    /// Updates the gizmo based on given interaction information. See [`Gizmo::update`].
+    pub fn update(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &[Transform],
+    ) -> GizmoUpdateResult {
+        self.update_with_scene(interaction, targets, GizmoScene::default())
+    }
+
+    /// Updates the gizmo using the queries and constraints of the scene.
+    /// See [`Gizmo::update_with_scene`].
+    ///
+    /// The answers of the scene queries are recorded along with the update.
+    pub fn update_with_scene(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &[Transform],
+        scene: GizmoScene<'_>,
+    ) -> GizmoUpdateResult {
+        let scene_recorder = SceneRecorder::new(scene);
+        let result = self
+            .gizmo
+            .update_with_scene(interaction, targets, scene_recorder.scene());
+
+        self.recording.events.push(RecordedEvent::Update {
+            interaction,
+            targets: targets.to_vec(),
+            result: result.clone(),
+            scene: scene_recorder.into_recorded(),
+        });
+
+        result
+    }
+
+    /// Updates the gizmo based on given interaction information, updating the targets in place.
+    /// See [`Gizmo::update_into`].
+    ///
+    /// Recorded like [`GizmoRecorder::update`].
+    pub fn update_into(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &mut [Transform],
+    ) -> Option<GizmoResult> {
+        self.update_into_with_scene(interaction, targets, GizmoScene::default())
+    }
+
+    /// Updates the targets in place using the queries and constraints of the scene.
+    /// See [`Gizmo::update_into_with_scene`].
+    ///
+    /// Recorded like [`GizmoRecorder::update_with_scene`].
+    pub fn update_into_with_scene(
+        &mut self,
+        interaction: GizmoInteraction,
+        targets: &mut [Transform],
+        scene: GizmoScene<'_>,
+    ) -> Option<GizmoResult> {
+        let start_targets = targets.to_vec();
+        let scene_recorder = SceneRecorder::new(scene);
+        let result =
+            self.gizmo
+                .update_into_with_scene(interaction, targets, scene_recorder.scene());
+
+        self.recording.events.push(RecordedEvent::Update {
+            interaction,
+            targets: start_targets,
+            result: result.map(|result| (result, targets.to_vec())),
+            scene: scene_recorder.into_recorded(),
+        });
+
+        result
+    }
+
+    /// Aligns the targets along an axis. See [`Gizmo::align`].
+    pub fn align(
+        &mut self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+        alignment: Alignment,
+    ) -> GizmoUpdateResult {
+        self.align_with_scene(targets, axis, alignment, GizmoScene::default())
+    }
+
+    /// Aligns the targets along an axis using the parents and constraint of the scene.
+    /// See [`Gizmo::align_with_scene`].
+    pub fn align_with_scene(
+        &mut self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+        alignment: Alignment,
+        scene: GizmoScene<'_>,
+    ) -> GizmoUpdateResult {
+        let scene_recorder = SceneRecorder::new(scene);
+        let result = self
+            .gizmo
+            .align_with_scene(targets, axis, alignment, scene_recorder.scene());
+
+        self.recording.events.push(RecordedEvent::Align {
+            targets: targets.to_vec(),
+            axis,
+            alignment,
+            result: result.clone(),
+            scene: scene_recorder.into_recorded(),
+        });
+
+        result
+    }
+
+    /// Distributes the targets along an axis. See [`Gizmo::distribute`].
+    pub fn distribute(&mut self, targets: &[Transform], axis: GizmoDirection) -> GizmoUpdateResult {
+        self.distribute_with_scene(targets, axis, GizmoScene::default())
+    }
+
+    /// Distributes the targets along an axis using the parents and constraint of the scene.
+    /// See [`Gizmo::distribute_with_scene`].
+    pub fn distribute_with_scene(
+        &mut self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+        scene: GizmoScene<'_>,
+    ) -> GizmoUpdateResult {
+        let scene_recorder = SceneRecorder::new(scene);
+        let result = self
+            .gizmo
+            .distribute_with_scene(targets, axis, scene_recorder.scene());
+
+        self.recording.events.push(RecordedEvent::Distribute {
+            targets: targets.to_vec(),
+            axis,
+            result: result.clone(),
+            scene: scene_recorder.into_recorded(),
+        });
+
+        result
+    }
+
+    /// Mirrors the targets along an axis. See [`Gizmo::flip`].
+    pub fn flip(&mut self, targets: &[Transform], axis: GizmoDirection) -> GizmoUpdateResult {
+        self.flip_with_scene(targets, axis, GizmoScene::default())
+    }
+
+    /// Mirrors the targets along an axis using the parents and constraint of the scene.
+    /// See [`Gizmo::flip_with_scene`].
+    pub fn flip_with_scene(
+        &mut self,
+        targets: &[Transform],
+        axis: GizmoDirection,
+        scene: GizmoScene<'_>,
+    ) -> GizmoUpdateResult {
+        let scene_recorder = SceneRecorder::new(scene);
+        let result = self
+            .gizmo
+            .flip_with_scene(targets, axis, scene_recorder.scene());
+
+        self.recording.events.push(RecordedEvent::Flip {
+            targets: targets.to_vec(),
+            axis,
+            result: result.clone(),
+            scene: scene_recorder.into_recorded(),
+        });
+
+        result
+    }
+
+    /// Recording of the gizmo so far.
+    pub fn recording(&self) -> &GizmoRecording {
+        &self.recording
+    }
+
+    /// Stops recording, returning the recording.
+    pub fn into_recording(self) -> GizmoRecording {
+        self.recording
+    }
+}
+
+impl Deref for GizmoRecorder {
+    type Target = Gizmo;
+
+    fn deref(&self) -> &Self::Target {
+        &self.gizmo
+    }
+}
+
+/// Inputs and results of a gizmo, recorded with a [`GizmoRecorder`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GizmoRecording {
+    /// Recorded events, in the order they happened.
+    pub events: Vec<RecordedEvent>,
+}
+
+/// A single event of a [`GizmoRecording`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::large_enum_variant)]
+pub enum RecordedEvent {
+    /// The gizmo was created or its configuration was updated.
+    Config(Box<GizmoConfig>),
+    /// The gizmo was updated.
+    Update {
+        /// Interaction given to the gizmo
+        interaction: GizmoInteraction,
+        /// Targets given to the gizmo
+        targets: Vec<Transform>,
+        /// Result returned by the gizmo
+        result: GizmoUpdateResult,
+        /// Answers of the scene queries made during the update
+        #[cfg_attr(feature = "serde", serde(default))]
+        scene: RecordedScene,
+    },
+    /// The targets were aligned with [`Gizmo::align`].
+    Align {
+        /// Targets given to the gizmo
+        targets: Vec<Transform>,
+        /// Axis the targets were aligned along
+        axis: GizmoDirection,
+        /// Position the targets were aligned to
+        alignment: Alignment,
+        /// Result returned by the gizmo
+        result: GizmoUpdateResult,
+        /// Answers of the scene queries made during the arrangement
+        #[cfg_attr(feature = "serde", serde(default))]
+        scene: RecordedScene,
+    },
+    /// The targets were distributed with [`Gizmo::distribute`].
+    Distribute {
+        /// Targets given to the gizmo
+        targets: Vec<Transform>,
+        /// Axis the targets were distributed along
+        axis: GizmoDirection,
+        /// Result returned by the gizmo
+        result: GizmoUpdateResult,
+        /// Answers of the scene queries made during the arrangement
+        #[cfg_attr(feature = "serde", serde(default))]
+        scene: RecordedScene,
+    },
+    /// The targets were mirrored with [`Gizmo::flip`].
+    Flip {
+        /// Targets given to the gizmo
+        targets: Vec<Transform>,
+        /// Axis the targets were mirrored along
+        axis: GizmoDirection,
+        /// Result returned by the gizmo
+        result: GizmoUpdateResult,
+        /// Answers of the scene queries made during the arrangement
+        #[cfg_attr(feature = "serde", serde(default))]
+        scene: RecordedScene,
+    },
+}
+
+/// Scene of a recorded update, with the answers of the queries the gizmo made to it.
+///
+/// Each query is [`None`] if it was not part of the [`GizmoScene`] of the update.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedScene {
+    /// Hits returned by the [`GizmoScene::surface`] raycast, in order
+    pub surface_hits: Option<Vec<Option<SurfaceHit>>>,
+    /// Points added by the [`GizmoScene::snap_points`] provider
+    pub snap_points: Option<Vec<mint::Vector3<f64>>>,
+    /// Transforms returned by the [`GizmoScene::constraint`], in order
+    pub constrained: Option<Vec<Transform>>,
+    /// Parents given with [`GizmoScene::parents`]
+    pub parents: Option<Vec<Option<mint::RowMatrix4<f64>>>>,
+}
+
+/// Mismatch between a recorded and a replayed result, found by [`GizmoRecording::verify`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayMismatch {
+    /// Index of the mismatching event in [`GizmoRecording::events`]
+    pub event_index: usize,
+    /// Result that was recorded
+    pub recorded: GizmoUpdateResult,
+    /// Result that was produced by the replay
+    pub replayed: GizmoUpdateResult,
+}
+
+impl std::fmt::Display for ReplayMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "replayed result of event {} does not match the recorded result",
+            self.event_index
+        )
+    }
+}
+
+impl std::error::Error for ReplayMismatch {}
+
+impl GizmoRecording {
+    /// Replays the recording on a new gizmo.
+    ///
+    /// Returns the replayed gizmo and the results of each event other than
+    /// [`RecordedEvent::Config`], in order.
+    pub fn replay(&self) -> (Gizmo, Vec<GizmoUpdateResult>) {
+        let mut gizmo = Gizmo::default();
+        let mut results = Vec::new();
+
+        for event in &self.events {
+            if let Some(result) = replay_event(&mut gizmo, event) {
+                results.push(result);
+            }
+        }
+
+        (gizmo, results)
+    }
+
+    /// Replays the recording on a new gizmo, and checks that the replayed results
+    /// are exactly the same as the recorded ones.
+    ///
+    /// Returns the first mismatch found.
+    pub fn verify(&self) -> Result<(), Box<ReplayMismatch>> {
+        let mut gizmo = Gizmo::default();
+
+        for (event_index, event) in self.events.iter().enumerate() {
+            let Some(replayed) = replay_event(&mut gizmo, event) else {
+                continue;
+            };
+
+            let recorded = match event {
+                RecordedEvent::Config(_) => unreachable!(),
+                RecordedEvent::Update { result, .. }
+                | RecordedEvent::Align { result, .. }
+                | RecordedEvent::Distribute { result, .. }
+                | RecordedEvent::Flip { result, .. } => result,
+            };
+
+            if replayed != *recorded {
+                return Err(Box::new(ReplayMismatch {
+                    event_index,
+                    recorded: recorded.clone(),
+                    replayed,
+                }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Replays a single event on the gizmo, returning its result unless it is a configuration change.
+fn replay_event(gizmo: &mut Gizmo, event: &RecordedEvent) -> Option<GizmoUpdateResult> {
+    match event {
+        RecordedEvent::Config(config) => {
+            gizmo.update_config(**config);
+            None
+        }
+        RecordedEvent::Update {
+            interaction,
+            targets,
+            scene,
+            ..
+        } => {
+            let scene_replay = SceneReplay::new(scene);
+            Some(gizmo.update_with_scene(*interaction, targets, scene_replay.scene()))
+        }
+        RecordedEvent::Align {
+            targets,
+            axis,
+            alignment,
+            scene,
+            ..
+        } => {
+            let scene_replay = SceneReplay::new(scene);
+            Some(gizmo.align_with_scene(targets, *axis, *alignment, scene_replay.scene()))
+        }
+        RecordedEvent::Distribute {
+            targets,
+            axis,
+            scene,
+            ..
+        } => {
+            let scene_replay = SceneReplay::new(scene);
+            Some(gizmo.distribute_with_scene(targets, *axis, scene_replay.scene()))
+        }
+        RecordedEvent::Flip {
+            targets,
+            axis,
+            scene,
+            ..
+        } => {
+            let scene_replay = SceneReplay::new(scene);
+            Some(gizmo.flip_with_scene(targets, *axis, scene_replay.scene()))
+        }
+    }
+}
+
+/// Records the answers of the queries made to a scene.
+struct SceneRecorder<'a> {
+    scene: GizmoScene<'a>,
+    surface_hits: RefCell<Vec<Option<SurfaceHit>>>,
+    snap_points: RefCell<Vec<mint::Vector3<f64>>>,
+    constrained: RefCell<Vec<Transform>>,
+}
+
+impl<'a> SceneRecorder<'a> {
+    fn new(scene: GizmoScene<'a>) -> Self {
+        Self {
+            scene,
+            surface_hits: RefCell::default(),
+            snap_points: RefCell::default(),
+            constrained: RefCell::default(),
+        }
+    }
+
+    /// Scene that forwards the queries to the recorded scene.
+    fn scene(&self) -> GizmoScene<'_> {
+        GizmoScene {
+            surface: self.scene.surface.map(|_| self as &dyn SurfaceRaycast),
+            snap_points: self
+                .scene
+                .snap_points
+                .map(|_| self as &dyn SnapPointProvider),
+            constraint: self
+                .scene
+                .constraint
+                .map(|_| self as &dyn TransformConstraint),
+            parents: self.scene.parents,
+        }
+    }
+
+    fn into_recorded(self) -> RecordedScene {
+        RecordedScene {
+            surface_hits: self.scene.surface.map(|_| self.surface_hits.into_inner()),
+            snap_points: self
+                .scene
+                .snap_points
+                .map(|_| self.snap_points.into_inner()),
+            constrained: self.scene.constraint.map(|_| self.constrained.into_inner()),
+            parents: self.scene.parents.map(<[_]>::to_vec),
+        }
+    }
+}
+
+impl SurfaceRaycast for SceneRecorder<'_> {
+    fn raycast(&self, ray: GizmoRay) -> Option<SurfaceHit> {
+        let hit = self.scene.surface?.raycast(ray);
+        self.surface_hits.borrow_mut().push(hit);
+        hit
+    }
+}
+
+impl SnapPointProvider for SceneRecorder<'_> {
+    fn snap_points(&self, points: &mut Vec<mint::Vector3<f64>>) {
+        let Some(snap_points) = self.scene.snap_points else {
+            return;
+        };
+
+        let start = points.len();
+        snap_points.snap_points(points);
+        self.snap_points
+            .borrow_mut()
+            .extend_from_slice(&points[start..]);
+    }
+}
+
+impl TransformConstraint for SceneRecorder<'_> {
+    fn constrain(&self, index: usize, start: &Transform, proposed: Transform) -> Transform {
+        let Some(constraint) = self.scene.constraint else {
+            return proposed;
+        };
+
+        let constrained = constraint.constrain(index, start, proposed);
+        self.constrained.borrow_mut().push(constrained);
+        constrained
+    }
+}
+
+/// Answers the queries made to a scene with the recorded answers, in order.
+struct SceneReplay<'a> {
+    recorded: &'a RecordedScene,
+    surface_hit_index: Cell<usize>,
+    constrained_index: Cell<usize>,
+}
+
+impl<'a> SceneReplay<'a> {
+    fn new(recorded: &'a RecordedScene) -> Self {
+        Self {
+            recorded,
+            surface_hit_index: Cell::new(0),
+            constrained_index: Cell::new(0),
+        }
+    }
+
+    /// Scene that answers the queries with the recorded answers.
+    fn scene(&self) -> GizmoScene<'_> {
+        GizmoScene {
+            surface: self
+                .recorded
+                .surface_hits
+                .as_ref()
+                .map(|_| self as &dyn SurfaceRaycast),
+            snap_points: self
+                .recorded
+                .snap_points
+                .as_ref()
+                .map(|_| self as &dyn SnapPointProvider),
+            constraint: self
+                .recorded
+                .constrained
+                .as_ref()
+                .map(|_| self as &dyn TransformConstraint),
+            parents: self.recorded.parents.as_deref(),
+        }
+    }
+}
+
+impl SurfaceRaycast for SceneReplay<'_> {
+    fn raycast(&self, _ray: GizmoRay) -> Option<SurfaceHit> {
+        let index = self
+            .surface_hit_index
+            .replace(self.surface_hit_index.get() + 1);
+        self.recorded.surface_hits.as_ref()?.get(index).copied()?
+    }
+}
+
+impl SnapPointProvider for SceneReplay<'_> {
+    fn snap_points(&self, points: &mut Vec<mint::Vector3<f64>>) {
+        if let Some(snap_points) = &self.recorded.snap_points {
+            points.extend_from_slice(snap_points);
+        }
+    }
+}
+
+impl TransformConstraint for SceneReplay<'_> {
+    fn constrain(&self, _index: usize, _start: &Transform, proposed: Transform) -> Transform {
+        let index = self
+            .constrained_index
+            .replace(self.constrained_index.get() + 1);
+        self.recorded
+            .constrained
+            .as_ref()
+            .and_then(|constrained| constrained.get(index).copied())
+            .unwrap_or(proposed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use crate::config::{AngleSnap, SnapAngles};
+    use crate::config::{GizmoMode, SurfacePlacement};
+    use crate::math::{DMat4, DQuat, DVec3};
+    use crate::test_utils::{drag, hover, screen_pos, test_config};
+
+    fn config() -> GizmoConfig {
+        test_config(GizmoMode::Translate)
+    }
+
+    #[test]
+    fn replays_drag() {
+        let config = config();
+        let mut recorder = GizmoRecorder::new(config);
+        let mut targets = vec![Transform::default()];
+
+        let start = screen_pos(&config, DVec3::new(0.6, 0.0, 0.0));
+        recorder.update(hover(start), &targets);
+
+        let mut results = Vec::new();
+        for step in 0..8 {
+            let cursor_pos = (start.0 + step as f32 * 5.0, start.1);
+            let result = recorder.update_into(drag(cursor_pos, step == 0), &mut targets);
+            results.push(result.map(|result| (result, targets.clone())));
+        }
+        assert!(results.iter().all(Option::is_some));
+
+        let recording = recorder.into_recording();
+        assert_eq!(recording.verify(), Ok(()));
+        assert_eq!(recording.replay().1[1..], results);
+    }
+
+    #[test]
+    fn replays_drag_with_scene() {
+        let parents = [Some(
+            DMat4::from_scale_rotation_translation(
+                DVec3::new(2.0, 1.0, 0.5),
+                DQuat::from_rotation_y(0.4),
+                DVec3::ZERO,
+            )
+            .into(),
+        )];
+        let snap_points: Vec<mint::Vector3<f64>> = vec![[0.5, 0.0, 0.0].into()];
+        let constraint = |_: usize, _: &Transform, mut proposed: Transform| {
+            proposed.translation.y = proposed.translation.y.min(0.0);
+            proposed
+        };
+        let scene = GizmoScene::new()
+            .parents(&parents)
+            .snap_points(&snap_points)
+            .constraint(&constraint);
+
+        let config = config();
+        let mut recorder = GizmoRecorder::new(config);
+        let mut targets = vec![Transform::default()];
+
+        let start = screen_pos(&config, DVec3::new(0.6, 0.0, 0.0));
+        recorder.update_with_scene(hover(start), &targets, scene);
+
+        let mut results = Vec::new();
+        for step in 0..12 {
+            let cursor_pos = (start.0 + step as f32 * 5.0, start.1);
+            let result = if step < 6 {
+                recorder.update_with_scene(drag(cursor_pos, step == 0), &targets, scene)
+            } else {
+                recorder
+                    .update_into_with_scene(drag(cursor_pos, false), &mut targets, scene)
+                    .map(|result| (result, targets.clone()))
+            };
+
+            let (_, new_targets) = result.clone().unwrap();
+            targets = new_targets;
+            results.push(result);
+        }
+        recorder.update_with_scene(hover(start), &targets, scene);
+
+        let recording = recorder.into_recording();
+        assert_eq!(recording.verify(), Ok(()));
+
+        let (_, replayed) = recording.replay();
+        assert_eq!(replayed[1..replayed.len() - 1], results);
+
+        // The recorded scene is needed for the replay
+        let mut without_scene = recording.clone();
+        for event in &mut without_scene.events {
+            if let RecordedEvent::Update { scene, .. } = event {
+                assert!(scene.snap_points.is_some() && scene.constrained.is_some());
+                *scene = RecordedScene::default();
+            }
+        }
+        assert!(without_scene.verify().is_err());
+    }
+
+    #[test]
+    fn replays_surface_placement() {
+        // Ground patch that ends at x = 0.5
+        let raycast = |ray: GizmoRay| {
+            let (origin, direction) = (DVec3::from(ray.origin), DVec3::from(ray.direction));
+            let t = -origin.y / direction.y;
+            let point = origin + direction * t;
+            (t >= 0.0 && point.x < 0.5).then(|| SurfaceHit {
+                point: point.into(),
+                normal: [0.3, 1.0, 0.0].into(),
+            })
+        };
+        let scene = GizmoScene::new().surface(&raycast);
+
+        let mut config = config();
+        config.surface_placement = SurfacePlacement::AlignToNormal;
+        let mut recorder = GizmoRecorder::new(config);
+        let mut targets = vec![Transform::default()];
+
+        let center = screen_pos(&config, DVec3::ZERO);
+        let start = (center.0 + 2.0, center.1 + 2.0);
+        recorder.update_with_scene(hover(start), &targets, scene);
+        for step in 0..20 {
+            let cursor_pos = (start.0 + step as f32 * 10.0, start.1);
+            recorder.update_into_with_scene(drag(cursor_pos, step == 0), &mut targets, scene);
+        }
+
+        let recording = recorder.into_recording();
+        let surface_hits = recording
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                RecordedEvent::Update { scene, .. } => scene.surface_hits.clone(),
+                _ => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        assert!(surface_hits.contains(&None));
+        assert!(surface_hits.iter().any(Option::is_some));
+
+        assert_eq!(recording.verify(), Ok(()));
+    }
+
+    #[test]
+    fn replays_arrangements() {
+        let mut recorder = GizmoRecorder::new(config());
+        let targets = [
+            Transform::default(),
+            Transform::from_scale_rotation_translation(
+                [1.0; 3],
+                [0.0, 0.0, 0.0, 1.0],
+                [1.0, 2.0, 0.0],
+            ),
+            Transform::from_scale_rotation_translation(
+                [1.0; 3],
+                [0.0, 0.0, 0.0, 1.0],
+                [4.0, 1.0, 0.0],
+            ),
+        ];
+
+        let parents = [None, Some(DMat4::from_translation(DVec3::Z).into()), None];
+        let constraint = |_: usize, _: &Transform, mut proposed: Transform| {
+            proposed.translation.y = proposed.translation.y.min(1.5);
+            proposed
+        };
+        let scene = GizmoScene::new().parents(&parents).constraint(&constraint);
+
+        let results = [
+            recorder.align(&targets, GizmoDirection::Y, Alignment::Max),
+            recorder.distribute(&targets, GizmoDirection::X),
+            recorder.flip(&targets, GizmoDirection::Z),
+            recorder.align_with_scene(&targets, GizmoDirection::Y, Alignment::Max, scene),
+        ];
+        assert!(results.iter().all(Option::is_some));
+
+        let recording = recorder.into_recording();
+        assert_eq!(recording.events.len(), 5);
+        assert_eq!(recording.verify(), Ok(()));
+        assert_eq!(recording.replay().1, results);
+
+        // The constraint is replayed from the recorded scene
+        let Some(RecordedEvent::Align { scene, .. }) = recording.events.last() else {
+            panic!("unexpected events {:?}", recording.events);
+        };
+        assert_eq!(scene.constrained.as_ref().map(Vec::len), Some(3));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn replays_after_serde_round_trip() {
+        let snap_points: Vec<mint::Vector3<f64>> = vec![[0.55, 0.0, 0.0].into()];
+        let scene = GizmoScene::new().snap_points(&snap_points);
+
+        let mut config = config();
+        config.snap_settings.angle =
+            AngleSnap::Angles(SnapAngles::from_degrees(&[15.0, 45.0, 90.0]).unwrap());
+        let mut recorder = GizmoRecorder::new(config);
+        let mut targets = vec![Transform::default()];
+
+        let start = screen_pos(&config, DVec3::new(0.6, 0.0, 0.0));
+        recorder.update_with_scene(hover(start), &targets, scene);
+        for step in 0..12 {
+            // Snapping is toggled halfway through the drag
+            if step == 6 {
+                config.snapping = true;
+                recorder.update_config(config);
+            }
+            let cursor_pos = (start.0 + step as f32 * 3.7, start.1 + step as f32 * 0.3);
+            recorder.update_into_with_scene(drag(cursor_pos, step == 0), &mut targets, scene);
+        }
+        recorder.flip(&[targets[0], Transform::default()], GizmoDirection::Z);
+
+        let recording = recorder.into_recording();
+        let json = serde_json::to_string(&recording).unwrap();
+        let deserialized: GizmoRecording = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized, recording);
+        assert_eq!(deserialized.verify(), Ok(()));
+    }
+}
@@ -0,0 +1,223 @@
+//! Results of gizmo transformations.
+
+use glam::{DMat3, DQuat, DVec3};
+
+use crate::config::{GizmoDirection, GizmoMode, GizmoOrientation, TransformPivotPoint};
+use crate::math::Transform;
+
+/// Result of a gizmo transformation.
+///
+/// Besides the kind and amount of the transformation, the result describes the handle,
+/// pivot and orientation it was made with, so that it can be applied to any transform
+/// with [`GizmoResult::apply_to`], for example in deferred or networked updates.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GizmoResult {
+    /// Kind and amount of the transformation
+    pub kind: GizmoResultKind,
+    /// Handle that was dragged. `None` for results of operations that are not done
+    /// by dragging a handle, such as [`Gizmo::align`](crate::Gizmo::align).
+    pub handle: Option<GizmoHandle>,
+    /// Whether the targets should be duplicated, and the duplicates transformed instead.
+    ///
+    /// Set for every result of a drag that was started with
+    /// [`GizmoModifiers::duplicate`](crate::GizmoModifiers::duplicate) held.
+    /// The targets are typically duplicated on the first result of such a drag.
+    pub duplicate: bool,
+    /// Pivot point used for rotations
+    pub pivot_point: TransformPivotPoint,
+    /// World space position of the gizmo before the transformation.
+    /// Rotations are done around it with [`TransformPivotPoint::MedianPoint`].
+    pub pivot: mint::Vector3<f64>,
+    /// Orientation of the gizmo
+    pub orientation: GizmoOrientation,
+    /// Coordinate frame of [`GizmoResult::delta`]
+    pub frame: GizmoFrame,
+    /// The latest change as a transform, in [`GizmoResult::frame`].
+    ///
+    /// The rotation is done around the pivot, after which the translation is added.
+    /// The scale is applied along the axes of each target.
+    pub delta: Transform,
+}
+
+impl GizmoResult {
+    /// Applies the latest change of the result to the given transform.
+    ///
+    /// Applying the result of each update in order to a world space transform gives the same
+    /// transform as [`Gizmo::update`](crate::Gizmo::update) does without a
+    /// [`GizmoScene::constraint`](crate::GizmoScene::constraint), up to floating point precision.
+    /// Results of [`Gizmo::flip`](crate::Gizmo::flip) mirror the transform like the flip does.
+    /// [`GizmoResultKind::Arrangement`] results leave the transform unchanged,
+    /// as they move each target differently. Use the transforms returned with them instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use transform_gizmo::prelude::*;
+    /// # use transform_gizmo::math::Transform;
+    /// # let mut gizmo = Gizmo::default();
+    /// # let interaction = GizmoInteraction::default();
+    /// let mut transforms = vec![Transform::default()];
+    ///
+    /// if let Some((result, _)) = gizmo.update(interaction, &transforms) {
+    ///     // Apply the result later, or send it elsewhere to be applied
+    ///     for transform in &mut transforms {
+    ///         *transform = result.apply_to(transform);
+    ///     }
+    /// }
+    /// ```
+    pub fn apply_to(&self, transform: &Transform) -> Transform {
+        match self.kind {
+            GizmoResultKind::Flip { axis } => {
+                return mirror_transform(transform, axis.into(), self.pivot.into());
+            }
+            GizmoResultKind::Arrangement { .. } => return *transform,
+            _ => {}
+        }
+
+        let rotation = DQuat::from(transform.rotation);
+        let translation = DVec3::from(transform.translation);
+
+        let frame = match self.frame {
+            GizmoFrame::World => DQuat::IDENTITY,
+            GizmoFrame::Target => rotation,
+        };
+        let rotation_delta = frame * DQuat::from(self.delta.rotation) * frame.inverse();
+
+        let pivot = match self.pivot_point {
+            TransformPivotPoint::MedianPoint => DVec3::from(self.pivot),
+            TransformPivotPoint::IndividualOrigins => translation,
+        };
+
+        Transform {
+            scale: (DVec3::from(transform.scale) * DVec3::from(self.delta.scale)).into(),
+            rotation: (rotation_delta * rotation).into(),
+            translation: (pivot
+                + rotation_delta * (translation - pivot)
+                + frame * DVec3::from(self.delta.translation))
+            .into(),
+        }
+    }
+}
+
+/// Mirrors the transform through the plane that passes through `pivot` and is perpendicular
+/// to the unit vector `axis`, negating a scale component as described in
+/// [`Gizmo::flip`](crate::Gizmo::flip).
+pub(crate) fn mirror_transform(transform: &Transform, axis: DVec3, pivot: DVec3) -> Transform {
+    let mirror =
+        DMat3::IDENTITY - 2.0 * DMat3::from_cols(axis * axis.x, axis * axis.y, axis * axis.z);
+    let translation = DVec3::from(transform.translation);
+    let rotation = DQuat::from(transform.rotation);
+
+    // Local axis closest to the mirroring axis
+    let local_axis = (rotation.inverse() * axis).abs();
+    let flip = if local_axis.x >= local_axis.y && local_axis.x >= local_axis.z {
+        DVec3::new(-1.0, 1.0, 1.0)
+    } else if local_axis.y >= local_axis.z {
+        DVec3::new(1.0, -1.0, 1.0)
+    } else {
+        DVec3::new(1.0, 1.0, -1.0)
+    };
+
+    // The mirrored rotation and scale M * R * S equal (M * R * F) * (F * S),
+    // where F flips the chosen local axis. M * R * F is a proper rotation,
+    // as both M and F are reflections.
+    let new_rotation = mirror * DMat3::from_quat(rotation) * DMat3::from_diagonal(flip);
+
+    Transform {
+        scale: (DVec3::from(transform.scale) * flip).into(),
+        rotation: DQuat::from_mat3(&new_rotation).normalize().into(),
+        translation: (pivot + mirror * (translation - pivot)).into(),
+    }
+}
+
+/// Kind and amount of a gizmo transformation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GizmoResultKind {
+    Rotation {
+        /// The rotation axis. In local space of the targets with [`GizmoOrientation::Local`],
+        /// otherwise in world space.
+        axis: mint::Vector3<f64>,
+        /// The latest rotation angle delta
+        delta: f64,
+        /// Total rotation angle of the gizmo interaction
+        total: f64,
+        /// Whether we are rotating along the view axis
+        is_view_axis: bool,
+    },
+    Translation {
+        /// The latest translation delta
+        delta: mint::Vector3<f64>,
+        /// Total translation of the gizmo interaction
+        total: mint::Vector3<f64>,
+    },
+    Scale {
+        /// The latest scale delta, as a factor of the previous scale
+        delta: mint::Vector3<f64>,
+        /// Total scale of the gizmo interaction
+        total: mint::Vector3<f64>,
+    },
+    Arcball {
+        /// The latest rotation delta
+        delta: mint::Quaternion<f64>,
+        /// Total rotation of the gizmo interaction
+        total: mint::Quaternion<f64>,
+    },
+    Surface {
+        /// The latest translation delta
+        delta: mint::Vector3<f64>,
+        /// Total translation of the gizmo interaction
+        total: mint::Vector3<f64>,
+        /// Total rotation of the gizmo interaction.
+        /// Identity, unless
+        /// [`SurfacePlacement::AlignToNormal`](crate::config::SurfacePlacement::AlignToNormal) is used.
+        rotation: mint::Quaternion<f64>,
+        /// Normal of the surface the gizmo is placed on
+        normal: mint::Vector3<f64>,
+    },
+    /// Mirroring of [`Gizmo::flip`](crate::Gizmo::flip), through the plane that passes through
+    /// [`GizmoResult::pivot`] and is perpendicular to `axis`.
+    /// The delta of the result is the identity, as a mirroring can not be expressed as a transform.
+    Flip {
+        /// The mirroring axis, in world space
+        axis: mint::Vector3<f64>,
+    },
+    /// Arrangement of [`Gizmo::align`](crate::Gizmo::align) or
+    /// [`Gizmo::distribute`](crate::Gizmo::distribute), moving each target along `axis`
+    /// by a different amount. The delta of the result is the identity.
+    Arrangement {
+        /// The axis the targets were moved along, in world space
+        axis: mint::Vector3<f64>,
+    },
+}
+
+/// Handle of a gizmo that can be dragged.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GizmoHandle {
+    /// Arrow or ring that transforms along or around a single axis.
+    /// Rotation around [`GizmoDirection::View`] is done with the outer ring.
+    Axis {
+        mode: GizmoMode,
+        direction: GizmoDirection,
+    },
+    /// Plane that transforms along the two axes perpendicular to `direction`.
+    /// The [`GizmoDirection::View`] plane is the circle at the center of the gizmo.
+    Plane {
+        mode: GizmoMode,
+        direction: GizmoDirection,
+    },
+    /// Free rotation with the arcball.
+    Arcball,
+}
+
+/// Coordinate frame of the delta of a [`GizmoResult`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GizmoFrame {
+    /// The delta is in world space.
+    World,
+    /// The delta is in the local space of each target.
+    Target,
+}
@@ -0,0 +1,357 @@
+//! Scene queries, constraints and parent hierarchies used during gizmo updates.
+//! See [`GizmoScene`].
+
+use glam::{DMat3, DMat4, DQuat, DVec3};
+
+use crate::gizmo::GizmoRay;
+use crate::math::Transform;
+
+/// Target of a gizmo in a parent hierarchy, given with [`GizmoScene::parents`].
+///
+/// # Examples
+///
+/// ```
+/// # use transform_gizmo::prelude::*;
+/// # use transform_gizmo::math::{DMat4, DQuat, DVec3, Transform};
+/// // Parents with rotations and non-uniform scales
+/// let parent = (0..4).fold(DMat4::IDENTITY, |parent, i| {
+///     parent
+///         * DMat4::from_scale_rotation_translation(
+///             DVec3::new(1.0, 2.0, 0.5 + i as f64),
+///             DQuat::from_rotation_y(0.3 * i as f64),
+///             DVec3::new(1.0, 0.0, -2.0),
+///         )
+/// });
+///
+/// let target = HierarchyTarget::new(Transform::default(), Some(parent.into()));
+///
+/// // Move the target to a new position in world space
+/// let mut world_transform = target.world_transform();
+/// world_transform.translation = [3.0, 4.0, 5.0].into();
+///
+/// let local_transform = target.local_transform(world_transform);
+/// let position = parent.transform_point3(local_transform.translation.into());
+/// assert!(position.distance(DVec3::new(3.0, 4.0, 5.0)) < 1e-9);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HierarchyTarget {
+    /// Transform of the target, relative to its parent
+    pub transform: Transform,
+    /// World matrix of the parent of the target. Must be invertible.
+    /// If `None`, the target has no parent and its transform is in world space.
+    pub parent: Option<mint::RowMatrix4<f64>>,
+}
+
+impl HierarchyTarget {
+    pub fn new(transform: Transform, parent: Option<mint::RowMatrix4<f64>>) -> Self {
+        Self { transform, parent }
+    }
+
+    /// Transform of the target in world space.
+    ///
+    /// The scale is the length of each axis of the target in world space. If the parent
+    /// is scaled non-uniformly, the axes of the target may not be perpendicular in world space.
+    /// The rotation then follows the orthonormalized axes of the parent.
+    ///
+    /// If the parent mirrors, the world space Z scale of the target is negated,
+    /// so that the rotation stays a proper rotation.
+    pub fn world_transform(&self) -> Transform {
+        let Some(parent) = self.parent.map(DMat4::from) else {
+            return self.transform;
+        };
+
+        let rotation = DQuat::from(self.transform.rotation);
+
+        Transform {
+            scale: (axis_scales(parent, rotation)
+                * mirror_scale(parent)
+                * DVec3::from(self.transform.scale))
+            .into(),
+            rotation: (parent_rotation(parent) * mirror_rotation(parent, rotation)).into(),
+            translation: parent
+                .transform_point3(DVec3::from(self.transform.translation))
+                .into(),
+        }
+    }
+
+    /// Transform of the target relative to its parent, that results in the given world
+    /// space transform. Inverse of [`HierarchyTarget::world_transform`].
+    ///
+    /// The translation is exact. Rotations are applied in the orthonormalized space of the parent,
+    /// and scales along the axes of the target.
+    pub fn local_transform(&self, world_transform: Transform) -> Transform {
+        let Some(parent) = self.parent.map(DMat4::from) else {
+            return world_transform;
+        };
+
+        let rotation = mirror_rotation(
+            parent,
+            parent_rotation(parent).inverse() * DQuat::from(world_transform.rotation),
+        );
+
+        // Scale along the original axes, so that rotating does not change the scale
+        let axis_scales =
+            axis_scales(parent, DQuat::from(self.transform.rotation)) * mirror_scale(parent);
+
+        Transform {
+            scale: (DVec3::from(world_transform.scale) / axis_scales).into(),
+            rotation: rotation.into(),
+            translation: parent
+                .inverse()
+                .transform_point3(DVec3::from(world_transform.translation))
+                .into(),
+        }
+    }
+}
+
+impl From<Transform> for HierarchyTarget {
+    fn from(transform: Transform) -> Self {
+        Self::new(transform, None)
+    }
+}
+
+/// Rotation of the orthonormalized axes of the parent matrix.
+///
+/// If the parent mirrors, its Z axis is flipped to keep the axes right-handed.
+/// The reflection is then applied with [`mirror_rotation`] and [`mirror_scale`].
+fn parent_rotation(parent: DMat4) -> DQuat {
+    let x = parent.x_axis.truncate().normalize();
+    let y = parent
+        .y_axis
+        .truncate()
+        .reject_from_normalized(x)
+        .normalize();
+
+    DQuat::from_mat3(&DMat3::from_cols(x, y, x.cross(y)))
+}
+
+/// Reflects a rotation through the XY plane if the parent mirrors.
+/// A mirrored rotation turns around the reflected axis in the opposite direction.
+fn mirror_rotation(parent: DMat4, rotation: DQuat) -> DQuat {
+    if parent.determinant() < 0.0 {
+        DQuat::from_xyzw(-rotation.x, -rotation.y, rotation.z, rotation.w)
+    } else {
+        rotation
+    }
+}
+
+/// Sign of each world space axis scale of a child. The Z axis is negated if the parent mirrors.
+fn mirror_scale(parent: DMat4) -> DVec3 {
+    if parent.determinant() < 0.0 {
+        DVec3::new(1.0, 1.0, -1.0)
+    } else {
+        DVec3::ONE
+    }
+}
+
+/// World space lengths of the unit axes of a child with the given rotation
+fn axis_scales(parent: DMat4, rotation: DQuat) -> DVec3 {
+    let parent = DMat3::from_mat4(parent);
+
+    DVec3::new(
+        (parent * (rotation * DVec3::X)).length(),
+        (parent * (rotation * DVec3::Y)).length(),
+        (parent * (rotation * DVec3::Z)).length(),
+    )
+}
+
+/// Scene surface hit by a [`GizmoRay`], returned by a [`SurfaceRaycast`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SurfaceHit {
+    /// Point where the ray hit the surface
+    pub point: mint::Vector3<f64>,
+    /// Normal of the surface at the hit point. Does not need to be normalized.
+    pub normal: mint::Vector3<f64>,
+}
+
+/// Raycast against the scene, used for placing targets on surfaces.
+/// See [`GizmoScene::surface`].
+///
+/// Implemented for closures that take a [`GizmoRay`] and return an optional [`SurfaceHit`].
+pub trait SurfaceRaycast {
+    /// Returns the closest surface hit by the given ray, if any.
+    fn raycast(&self, ray: GizmoRay) -> Option<SurfaceHit>;
+}
+
+impl<F> SurfaceRaycast for F
+where
+    F: Fn(GizmoRay) -> Option<SurfaceHit>,
+{
+    fn raycast(&self, ray: GizmoRay) -> Option<SurfaceHit> {
+        self(ray)
+    }
+}
+
+/// Provides candidate points that a translated gizmo can snap to.
+/// See [`GizmoScene::snap_points`].
+///
+/// Implemented for slices and vectors of points, and for closures that
+/// add points to the given vector.
+pub trait SnapPointProvider {
+    /// Adds candidate snap points, in world space, to `points`.
+    fn snap_points(&self, points: &mut Vec<mint::Vector3<f64>>);
+}
+
+impl<F> SnapPointProvider for F
+where
+    F: Fn(&mut Vec<mint::Vector3<f64>>),
+{
+    fn snap_points(&self, points: &mut Vec<mint::Vector3<f64>>) {
+        self(points);
+    }
+}
+
+impl SnapPointProvider for [mint::Vector3<f64>] {
+    fn snap_points(&self, points: &mut Vec<mint::Vector3<f64>>) {
+        points.extend_from_slice(self);
+    }
+}
+
+impl SnapPointProvider for Vec<mint::Vector3<f64>> {
+    fn snap_points(&self, points: &mut Vec<mint::Vector3<f64>>) {
+        points.extend_from_slice(self);
+    }
+}
+
+/// Constrains the transforms of targets while they are dragged.
+/// See [`GizmoScene::constraint`].
+///
+/// Implemented for closures that take the index of the target, its transform at the start
+/// of the drag and its proposed transform, and return the constrained transform.
+pub trait TransformConstraint {
+    /// Returns the constrained transform of the target at `index`,
+    /// given its transform at the start of the drag and the proposed new transform.
+    fn constrain(&self, index: usize, start: &Transform, proposed: Transform) -> Transform;
+}
+
+impl<F> TransformConstraint for F
+where
+    F: Fn(usize, &Transform, Transform) -> Transform,
+{
+    fn constrain(&self, index: usize, start: &Transform, proposed: Transform) -> Transform {
+        self(index, start, proposed)
+    }
+}
+
+/// Scene information used during a gizmo update, given to
+/// [`Gizmo::update_with_scene`](crate::Gizmo::update_with_scene) and
+/// [`Gizmo::update_into_with_scene`](crate::Gizmo::update_into_with_scene).
+///
+/// All of the queries are optional, and they can be combined freely.
+/// The default scene has none of them.
+///
+/// # Examples
+///
+/// ```
+/// # use transform_gizmo::prelude::*;
+/// # use transform_gizmo::math::Transform;
+/// # let mut gizmo = Gizmo::default();
+/// # let interaction = GizmoInteraction::default();
+/// # let transforms = vec![];
+/// let snap_points: Vec<mint::Vector3<f64>> = vec![[1.0, 0.0, 0.0].into(), [0.0, 2.0, 0.0].into()];
+/// // Keep the targets above the ground
+/// let constraint = |_index: usize, _start: &Transform, mut proposed: Transform| {
+///     proposed.translation.y = proposed.translation.y.max(0.0);
+///     proposed
+/// };
+///
+/// let scene = GizmoScene::new()
+///     .snap_points(&snap_points)
+///     .constraint(&constraint);
+///
+/// let result = gizmo.update_with_scene(interaction, &transforms, scene);
+/// ```
+#[derive(Default, Clone, Copy)]
+pub struct GizmoScene<'a> {
+    pub(crate) surface: Option<&'a dyn SurfaceRaycast>,
+    pub(crate) snap_points: Option<&'a dyn SnapPointProvider>,
+    pub(crate) constraint: Option<&'a dyn TransformConstraint>,
+    pub(crate) parents: Option<&'a [Option<mint::RowMatrix4<f64>>]>,
+}
+
+impl<'a> GizmoScene<'a> {
+    /// Creates a scene without any queries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the raycast used for placing the targets on scene surfaces.
+    ///
+    /// When [`GizmoConfig::surface_placement`](crate::GizmoConfig::surface_placement) is enabled,
+    /// dragging the view plane translation handle places the targets on the scene surface
+    /// under the cursor, found with the raycast.
+    /// The raycast should ignore the targets themselves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use transform_gizmo::prelude::*;
+    /// # let mut gizmo = Gizmo::default();
+    /// # let interaction = GizmoInteraction::default();
+    /// # let transforms = vec![];
+    /// // Ground plane at y = 0
+    /// let raycast = |ray: GizmoRay| {
+    ///     let t = -ray.origin.y / ray.direction.y;
+    ///     (t >= 0.0).then(|| SurfaceHit {
+    ///         point: [ray.origin.x + ray.direction.x * t, 0.0, ray.origin.z + ray.direction.z * t].into(),
+    ///         normal: [0.0, 1.0, 0.0].into(),
+    ///     })
+    /// };
+    ///
+    /// let scene = GizmoScene::new().surface(&raycast);
+    /// let result = gizmo.update_with_scene(interaction, &transforms, scene);
+    /// ```
+    pub fn surface(mut self, surface: &'a dyn SurfaceRaycast) -> Self {
+        self.surface = Some(surface);
+        self
+    }
+
+    /// Sets the points that a translated gizmo snaps to.
+    ///
+    /// While translating, the gizmo snaps to the snap point that is closest to it on screen,
+    /// if it is within [`GizmoConfig::snap_point_distance`](crate::GizmoConfig::snap_point_distance)
+    /// pixels. Snap points can be, for example, mesh vertices, edge midpoints
+    /// or origins of other objects.
+    pub fn snap_points(mut self, snap_points: &'a dyn SnapPointProvider) -> Self {
+        self.snap_points = Some(snap_points);
+        self
+    }
+
+    /// Sets the constraint of the target transforms.
+    ///
+    /// Each updated target transform, in world space, is passed through the constraint before
+    /// it is returned, and the gizmo follows the constrained targets.
+    /// The returned [`GizmoResult`](crate::GizmoResult) still describes
+    /// the unconstrained transformation.
+    ///
+    /// During a drag, the targets are transformed from where they would be without the constraint,
+    /// so that the constraint does not accumulate over the updates of the drag.
+    pub fn constraint(mut self, constraint: &'a dyn TransformConstraint) -> Self {
+        self.constraint = Some(constraint);
+        self
+    }
+
+    /// Sets the world matrices of the parents of the targets, one for each target.
+    ///
+    /// The targets are then given relative to their parents. The gizmo operates on their world
+    /// space transforms, and the updated transforms are returned relative to the parents.
+    /// Targets without a parent entry, or with `None`, are in world space. See [`HierarchyTarget`].
+    ///
+    /// Targets should not include descendants of other targets, since their parent matrices
+    /// would not include the changes made to their ancestors.
+    pub fn parents(mut self, parents: &'a [Option<mint::RowMatrix4<f64>>]) -> Self {
+        self.parents = Some(parents);
+        self
+    }
+
+    /// The target at `index` with its parent in the scene.
+    pub(crate) fn hierarchy_target(&self, index: usize, transform: Transform) -> HierarchyTarget {
+        let parent = self
+            .parents
+            .and_then(|parents| parents.get(index).copied().flatten());
+
+        HierarchyTarget::new(transform, parent)
+    }
+}
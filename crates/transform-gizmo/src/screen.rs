@@ -0,0 +1,404 @@
+//! Minimal 2d screen space types.
+//!
+//! The API mirrors the corresponding types of `emath`. With the `epaint` feature,
+//! the types convert to and from them.
+
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Position in 2d space.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pos2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Pos2 {
+    pub const ZERO: Self = Self { x: 0.0, y: 0.0 };
+
+    #[inline]
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    #[inline]
+    pub fn distance(self, other: Self) -> f32 {
+        (self - other).length()
+    }
+
+    #[inline]
+    pub fn distance_sq(self, other: Self) -> f32 {
+        (self - other).length_sq()
+    }
+
+    #[inline]
+    pub const fn to_vec2(self) -> Vec2 {
+        Vec2::new(self.x, self.y)
+    }
+
+    #[inline]
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+}
+
+impl From<(f32, f32)> for Pos2 {
+    #[inline]
+    fn from((x, y): (f32, f32)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<[f32; 2]> for Pos2 {
+    #[inline]
+    fn from([x, y]: [f32; 2]) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<Pos2> for (f32, f32) {
+    #[inline]
+    fn from(pos: Pos2) -> Self {
+        (pos.x, pos.y)
+    }
+}
+
+impl From<Pos2> for [f32; 2] {
+    #[inline]
+    fn from(pos: Pos2) -> Self {
+        [pos.x, pos.y]
+    }
+}
+
+impl Sub for Pos2 {
+    type Output = Vec2;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Vec2 {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Add<Vec2> for Pos2 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Vec2) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub<Vec2> for Pos2 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Vec2) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl AddAssign<Vec2> for Pos2 {
+    #[inline]
+    fn add_assign(&mut self, rhs: Vec2) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign<Vec2> for Pos2 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Vec2) {
+        *self = *self - rhs;
+    }
+}
+
+/// Vector in 2d space.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Self = Self { x: 0.0, y: 0.0 };
+
+    #[inline]
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    #[inline]
+    pub fn length(self) -> f32 {
+        self.length_sq().sqrt()
+    }
+
+    #[inline]
+    pub fn length_sq(self) -> f32 {
+        self.x * self.x + self.y * self.y
+    }
+
+    /// Returns the vector with a length of one, or zero if the length is zero.
+    #[inline]
+    pub fn normalized(self) -> Self {
+        let length = self.length();
+        if length == 0.0 {
+            self
+        } else {
+            self * (1.0 / length)
+        }
+    }
+
+    /// Rotates the vector by 90°.
+    #[inline]
+    pub fn rot90(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    #[inline]
+    pub const fn to_pos2(self) -> Pos2 {
+        Pos2::new(self.x, self.y)
+    }
+}
+
+impl From<(f32, f32)> for Vec2 {
+    #[inline]
+    fn from((x, y): (f32, f32)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<[f32; 2]> for Vec2 {
+    #[inline]
+    fn from([x, y]: [f32; 2]) -> Self {
+        Self { x, y }
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: f32) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+/// Axis-aligned rectangle, defined by its minimum and maximum corners.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    pub min: Pos2,
+    pub max: Pos2,
+}
+
+impl Rect {
+    /// Infinite rectangle that contains every point.
+    pub const EVERYTHING: Self = Self {
+        min: Pos2::new(-f32::INFINITY, -f32::INFINITY),
+        max: Pos2::new(f32::INFINITY, f32::INFINITY),
+    };
+
+    /// The inverse of [`Self::EVERYTHING`], containing no points.
+    pub const NOTHING: Self = Self {
+        min: Pos2::new(f32::INFINITY, f32::INFINITY),
+        max: Pos2::new(-f32::INFINITY, -f32::INFINITY),
+    };
+
+    #[inline]
+    pub const fn from_min_max(min: Pos2, max: Pos2) -> Self {
+        Self { min, max }
+    }
+
+    #[inline]
+    pub fn from_min_size(min: Pos2, size: Vec2) -> Self {
+        Self {
+            min,
+            max: min + size,
+        }
+    }
+
+    #[inline]
+    pub fn left(&self) -> f32 {
+        self.min.x
+    }
+
+    #[inline]
+    pub fn right(&self) -> f32 {
+        self.max.x
+    }
+
+    #[inline]
+    pub fn top(&self) -> f32 {
+        self.min.y
+    }
+
+    #[inline]
+    pub fn bottom(&self) -> f32 {
+        self.max.y
+    }
+
+    #[inline]
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    #[inline]
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    #[inline]
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    #[inline]
+    pub fn center(&self) -> Pos2 {
+        Pos2::new(
+            (self.min.x + self.max.x) / 2.0,
+            (self.min.y + self.max.y) / 2.0,
+        )
+    }
+
+    #[inline]
+    pub fn contains(&self, pos: Pos2) -> bool {
+        self.min.x <= pos.x && pos.x <= self.max.x && self.min.y <= pos.y && pos.y <= self.max.y
+    }
+
+    #[inline]
+    pub fn is_finite(&self) -> bool {
+        self.min.is_finite() && self.max.is_finite()
+    }
+}
+
+#[cfg(feature = "epaint")]
+impl From<emath::Pos2> for Pos2 {
+    #[inline]
+    fn from(pos: emath::Pos2) -> Self {
+        Self::new(pos.x, pos.y)
+    }
+}
+
+#[cfg(feature = "epaint")]
+impl From<Pos2> for emath::Pos2 {
+    #[inline]
+    fn from(pos: Pos2) -> Self {
+        Self::new(pos.x, pos.y)
+    }
+}
+
+#[cfg(feature = "epaint")]
+impl From<emath::Vec2> for Vec2 {
+    #[inline]
+    fn from(vec: emath::Vec2) -> Self {
+        Self::new(vec.x, vec.y)
+    }
+}
+
+#[cfg(feature = "epaint")]
+impl From<Vec2> for emath::Vec2 {
+    #[inline]
+    fn from(vec: Vec2) -> Self {
+        Self::new(vec.x, vec.y)
+    }
+}
+
+#[cfg(feature = "epaint")]
+impl From<emath::Rect> for Rect {
+    #[inline]
+    fn from(rect: emath::Rect) -> Self {
+        Self::from_min_max(rect.min.into(), rect.max.into())
+    }
+}
+
+#[cfg(feature = "epaint")]
+impl From<Rect> for emath::Rect {
+    #[inline]
+    fn from(rect: Rect) -> Self {
+        Self::from_min_max(rect.min.into(), rect.max.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector_math() {
+        let a = Pos2::new(1.0, 2.0);
+        let b = Pos2::new(4.0, 6.0);
+        assert_eq!(b - a, Vec2::new(3.0, 4.0));
+        assert_eq!(a + (b - a), b);
+        assert_eq!(a.distance(b), 5.0);
+        assert_eq!(a.distance_sq(b), 25.0);
+
+        let v = Vec2::new(3.0, 4.0);
+        assert_eq!(v.normalized(), Vec2::new(0.6, 0.8));
+        assert_eq!(Vec2::ZERO.normalized(), Vec2::ZERO);
+        assert_eq!(v.rot90(), Vec2::new(4.0, -3.0));
+        assert_eq!(v.dot(v.rot90()), 0.0);
+        assert_eq!(-v * 2.0, Vec2::new(-6.0, -8.0));
+    }
+
+    #[test]
+    fn rect_contains_points() {
+        let rect = Rect::from_min_size(Pos2::new(10.0, 20.0), Vec2::new(100.0, 50.0));
+        assert_eq!((rect.width(), rect.height()), (100.0, 50.0));
+        assert_eq!(rect.center(), Pos2::new(60.0, 45.0));
+        assert!(rect.contains(rect.min) && rect.contains(rect.max));
+        assert!(!rect.contains(Pos2::new(9.0, 30.0)));
+        assert!(rect.is_finite());
+
+        assert!(Rect::EVERYTHING.contains(Pos2::new(-1e30, 1e30)));
+        assert!(!Rect::NOTHING.contains(Pos2::ZERO));
+        assert!(!Rect::NOTHING.is_finite());
+    }
+
+    #[cfg(feature = "epaint")]
+    #[test]
+    fn egui_conversions() {
+        let rect = Rect::from_min_max(Pos2::new(10.0, 20.0), Pos2::new(110.0, 70.0));
+        let egui_rect = emath::Rect::from(rect);
+        assert_eq!(
+            egui_rect,
+            emath::Rect::from_min_max((10.0, 20.0).into(), (110.0, 70.0).into())
+        );
+        assert_eq!(Rect::from(egui_rect), rect);
+
+        let vec = Vec2::new(3.0, -4.0);
+        assert_eq!(Vec2::from(emath::Vec2::from(vec)), vec);
+        assert_eq!(Pos2::from(emath::Pos2::new(1.0, 2.0)), Pos2::new(1.0, 2.0));
+    }
+}
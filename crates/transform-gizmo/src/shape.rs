@@ -1,228 +1,717 @@
 use std::f64::consts::TAU;
 
+#[cfg(feature = "epaint")]
+use crate::color::Rgba;
+use crate::config::{Color32, PreparedGizmoConfig};
+#[cfg(feature = "epaint")]
+use crate::gizmo::GizmoDrawData;
+use crate::gizmo::GizmoPrimitive;
 use crate::math::{Pos2, Rect};
-use ecolor::Color32;
-use epaint::{Mesh, TessellationOptions, Tessellator, TextureId};
-pub(crate) use epaint::{Shape, Stroke};
-use glam::{DMat4, DVec3};
+#[cfg(not(feature = "epaint"))]
+pub(crate) use crate::tessellator::{tessellate_into, TessellationScratch};
+#[cfg(feature = "epaint")]
+use epaint::{Mesh, PathShape, TessellationOptions, Tessellator};
+use glam::{DMat4, DVec3, DVec4};
 
 use crate::math::world_to_screen;
 
 const STEPS_PER_RAD: f64 = 20.0;
 
-pub(crate) struct ShapeBuidler {
-    mvp: DMat4,
-    viewport: Rect,
-    pixels_per_point: f32,
+/// Points with a smaller clip space w are considered to be behind the camera.
+const MIN_CLIP_W: f64 = 1e-5;
+
+/// Length used for lines that should extend to infinity, in world units.
+const INFINITE_LINE_LENGTH: f64 = 1e6;
+
+/// Width and color of a line
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Stroke {
+    pub(crate) width: f32,
+    pub(crate) color: Color32,
 }
 
-impl ShapeBuidler {
-    pub(crate) fn new(mvp: DMat4, viewport: Rect, pixels_per_point: f32) -> Self {
-        Self {
-            mvp,
-            viewport,
-            pixels_per_point,
-        }
+impl From<(f32, Color32)> for Stroke {
+    fn from((width, color): (f32, Color32)) -> Self {
+        Self { width, color }
     }
+}
 
-    fn tessellate_shape(&self, shape: Shape) -> Mesh {
-        let mut tessellator = Tessellator::new(
-            self.pixels_per_point,
-            TessellationOptions {
-                feathering: true,
-                ..Default::default()
-            },
-            Default::default(),
-            Default::default(),
-        );
+/// List of primitives.
+///
+/// The point buffers of cleared primitives are reused for new ones, along with the scratch
+/// buffers used for building them, so that primitives can be rebuilt without allocating.
+#[derive(Default, Clone, Debug)]
+pub(crate) struct Primitives {
+    pub(crate) primitives: Vec<GizmoPrimitive>,
+    /// Point buffers of cleared primitives
+    spare_points: Vec<Vec<mint::Vector3<f64>>>,
+    /// Scratch buffer for clip space points
+    clip_points: Vec<DVec4>,
+    /// Scratch buffer for screen space points of convex hulls
+    hull_points: Vec<Pos2>,
+    /// Scratch buffers for point indices of convex hulls
+    hull_indices: (Vec<usize>, Vec<usize>),
+}
+
+impl Primitives {
+    /// Removes all primitives, keeping their point buffers for reuse.
+    pub(crate) fn clear(&mut self) {
+        for primitive in self.primitives.drain(..) {
+            if let GizmoPrimitive::Polyline { mut points, .. }
+            | GizmoPrimitive::Polygon { mut points, .. } = primitive
+            {
+                points.clear();
+                self.spare_points.push(points);
+            }
+        }
+    }
 
-        let mut mesh = Mesh::default();
-        tessellator.tessellate_shape(shape, &mut mesh);
+    /// Empty point buffer for a new primitive
+    fn take_points(&mut self) -> Vec<mint::Vector3<f64>> {
+        self.spare_points.pop().unwrap_or_default()
+    }
 
-        mesh.texture_id = TextureId::default();
-        mesh
+    /// Keeps a point buffer that was not used for a primitive for reuse.
+    fn recycle_points(&mut self, mut points: Vec<mint::Vector3<f64>>) {
+        points.clear();
+        self.spare_points.push(points);
     }
+}
 
-    fn arc_points(&self, radius: f64, start_angle: f64, end_angle: f64) -> Vec<Pos2> {
-        let angle = f64::clamp(end_angle - start_angle, -TAU, TAU);
+/// Buffers reused between draws of a gizmo.
+#[derive(Default, Debug)]
+pub(crate) struct DrawScratch {
+    /// World space primitives of the latest draw
+    pub(crate) world: Primitives,
+    /// Screen space primitives of the latest draw
+    pub(crate) screen: Primitives,
+    pub(crate) tessellation: TessellationScratch,
+}
 
-        let step_count = steps(angle);
-        let mut points = Vec::with_capacity(step_count);
+impl Clone for DrawScratch {
+    fn clone(&self) -> Self {
+        // Only the capacity of the buffers would be worth cloning
+        Self::default()
+    }
+}
 
-        let step_size = angle / (step_count - 1) as f64;
+/// Builds world space primitives from shapes given in the local
+/// coordinates of a model transform.
+pub(crate) struct ShapeBuidler<'a> {
+    model: DMat4,
+    mvp: DMat4,
+    viewport: Rect,
+    stroke_scale: f32,
+    primitives: &'a mut Primitives,
+}
 
-        for step in (0..step_count).map(|i| step_size * i as f64) {
-            let x = f64::cos(start_angle + step) * radius;
-            let z = f64::sin(start_angle + step) * radius;
+impl<'a> ShapeBuidler<'a> {
+    pub(crate) fn new(
+        model: DMat4,
+        config: &PreparedGizmoConfig,
+        primitives: &'a mut Primitives,
+    ) -> Self {
+        Self {
+            model,
+            mvp: config.view_projection * model,
+            viewport: config.viewport,
+            stroke_scale: config.stroke_scale(),
+            primitives,
+        }
+    }
 
-            points.push(DVec3::new(x, 0.0, z));
+    /// Scales the width of a stroke given in gizmo pixels to screen pixels.
+    fn stroke(&self, stroke: impl Into<Stroke>) -> Stroke {
+        let stroke = stroke.into();
+        Stroke {
+            width: stroke.width * self.stroke_scale,
+            color: stroke.color,
         }
+    }
+
+    fn point(&self, point: DVec3) -> mint::Vector3<f64> {
+        self.model.transform_point3(point).into()
+    }
 
-        points
-            .into_iter()
-            .filter_map(|point| self.vec3_to_pos2(point))
-            .collect::<Vec<_>>()
+    fn vector(&self, vector: DVec3) -> mint::Vector3<f64> {
+        self.model.transform_vector3(vector).into()
+    }
+
+    fn points(&mut self, points: impl IntoIterator<Item = DVec3>) -> Vec<mint::Vector3<f64>> {
+        let model = self.model;
+        let mut buffer = self.primitives.take_points();
+        buffer.extend(
+            points
+                .into_iter()
+                .map(|point| mint::Vector3::from(model.transform_point3(point))),
+        );
+        buffer
+    }
+
+    fn push(&mut self, primitive: GizmoPrimitive) {
+        self.primitives.primitives.push(primitive);
     }
 
     pub(crate) fn arc(
-        &self,
+        &mut self,
         radius: f64,
         start_angle: f64,
         end_angle: f64,
         stroke: impl Into<Stroke>,
-    ) -> Mesh {
-        let mut points = self.arc_points(radius, start_angle, end_angle);
+    ) {
+        let stroke = self.stroke(stroke);
+
+        self.push(GizmoPrimitive::Arc {
+            center: self.point(DVec3::ZERO),
+            x_axis: self.vector(DVec3::X * radius),
+            y_axis: self.vector(DVec3::Z * radius),
+            start_angle,
+            end_angle,
+            width: stroke.width,
+            color: stroke.color,
+        });
+    }
 
-        let closed = points
-            .first()
-            .zip(points.last())
-            .filter(|(first, last)| first.distance(**last) < 1e-2)
-            .is_some();
+    pub(crate) fn circle(&mut self, radius: f64, stroke: impl Into<Stroke>) {
+        let stroke = self.stroke(stroke);
+
+        self.push(GizmoPrimitive::Circle {
+            center: self.point(DVec3::ZERO),
+            x_axis: self.vector(DVec3::X * radius),
+            y_axis: self.vector(DVec3::Z * radius),
+            width: stroke.width,
+            color: stroke.color,
+            filled: false,
+        });
+    }
 
-        self.tessellate_shape(if closed {
-            points.pop();
-            Shape::closed_line(points, stroke)
-        } else {
-            Shape::line(points, stroke)
-        })
+    pub(crate) fn filled_circle(&mut self, radius: f64, color: Color32) {
+        self.push(GizmoPrimitive::Circle {
+            center: self.point(DVec3::ZERO),
+            x_axis: self.vector(DVec3::X * radius),
+            y_axis: self.vector(DVec3::Z * radius),
+            width: 0.0,
+            color,
+            filled: true,
+        });
     }
 
-    pub(crate) fn circle(&self, radius: f64, stroke: impl Into<Stroke>) -> Mesh {
-        self.arc(radius, 0.0, TAU, stroke)
+    pub(crate) fn line_segment(&mut self, from: DVec3, to: DVec3, stroke: impl Into<Stroke>) {
+        self.polyline([from, to], stroke);
     }
 
-    pub(crate) fn filled_circle(
-        &self,
-        radius: f64,
-        color: Color32,
+    /// Draws an infinite line going through `point`.
+    pub(crate) fn infinite_line(
+        &mut self,
+        point: DVec3,
+        direction: DVec3,
         stroke: impl Into<Stroke>,
-    ) -> Mesh {
-        let mut points = self.arc_points(radius, 0.0, TAU);
-        points.pop();
+    ) {
+        let direction = direction.normalize_or_zero() * INFINITE_LINE_LENGTH;
 
-        self.tessellate_shape(Shape::convex_polygon(points, color, stroke.into()))
+        self.polyline([point - direction, point, point + direction], stroke);
     }
 
-    pub(crate) fn line_segment(&self, from: DVec3, to: DVec3, stroke: impl Into<Stroke>) -> Mesh {
-        let mut points: [Pos2; 2] = Default::default();
+    pub(crate) fn arrow(&mut self, from: DVec3, to: DVec3, stroke: impl Into<Stroke>) {
+        let stroke = self.stroke(stroke);
 
-        for (i, point) in points.iter_mut().enumerate() {
-            if let Some(pos) = world_to_screen(self.viewport, self.mvp, [from, to][i]) {
-                *point = pos;
-            } else {
-                return Mesh::default();
-            }
-        }
+        self.push(GizmoPrimitive::Arrow {
+            from: self.point(from),
+            to: self.point(to),
+            width: stroke.width,
+            color: stroke.color,
+        });
+    }
 
-        self.tessellate_shape(Shape::LineSegment {
+    pub(crate) fn polygon(
+        &mut self,
+        points: impl IntoIterator<Item = DVec3>,
+        fill: impl Into<Color32>,
+    ) {
+        let points = self.points(points);
+
+        self.push(GizmoPrimitive::Polygon {
             points,
-            stroke: stroke.into(),
-        })
+            color: fill.into(),
+        });
     }
 
-    pub(crate) fn arrow(&self, from: DVec3, to: DVec3, stroke: impl Into<Stroke>) -> Mesh {
-        let stroke = stroke.into();
-        let arrow_start = world_to_screen(self.viewport, self.mvp, from);
-        let arrow_end = world_to_screen(self.viewport, self.mvp, to);
+    /// Fills the screen space convex hull of the given points.
+    /// Useful for drawing silhouettes of convex 3d shapes.
+    pub(crate) fn convex_hull(&mut self, points: &[DVec3], fill: impl Into<Color32>) {
+        let mut projected = std::mem::take(&mut self.primitives.hull_points);
+        let (mut sorted, mut hull) = std::mem::take(&mut self.primitives.hull_indices);
+
+        projected.clear();
+        projected.extend(
+            points
+                .iter()
+                .filter_map(|pos| world_to_screen(self.viewport, self.mvp, *pos)),
+        );
 
-        self.tessellate_shape(if let Some((start, end)) = arrow_start.zip(arrow_end) {
-            let cross = (end - start).normalized().rot90() * stroke.width / 2.0;
+        // Points partially behind the camera are not drawn
+        if projected.len() == points.len() {
+            convex_hull(&projected, &mut sorted, &mut hull);
 
-            Shape::convex_polygon(
-                vec![start - cross, start + cross, end],
-                stroke.color,
-                Stroke::NONE,
-            )
-        } else {
-            Shape::Noop
-        })
-    }
-
-    pub(crate) fn polygon(
-        &self,
-        points: &[DVec3],
-        fill: impl Into<Color32>,
-        stroke: impl Into<Stroke>,
-    ) -> Mesh {
-        let points = points
-            .iter()
-            .filter_map(|pos| world_to_screen(self.viewport, self.mvp, *pos))
-            .collect::<Vec<_>>();
+            if hull.len() > 2 {
+                self.polygon(hull.iter().map(|&i| points[i]), fill);
+            }
+        }
 
-        self.tessellate_shape(if points.len() > 2 {
-            Shape::convex_polygon(points, fill, stroke)
-        } else {
-            Shape::Noop
-        })
+        self.primitives.hull_points = projected;
+        self.primitives.hull_indices = (sorted, hull);
     }
 
-    pub(crate) fn polyline(&self, points: &[DVec3], stroke: impl Into<Stroke>) -> Mesh {
-        let points = points
-            .iter()
-            .filter_map(|pos| world_to_screen(self.viewport, self.mvp, *pos))
-            .collect::<Vec<_>>();
+    pub(crate) fn polyline(
+        &mut self,
+        points: impl IntoIterator<Item = DVec3>,
+        stroke: impl Into<Stroke>,
+    ) {
+        let stroke = self.stroke(stroke);
+        let points = self.points(points);
 
-        self.tessellate_shape(if points.len() > 1 {
-            Shape::line(points, stroke)
-        } else {
-            Shape::Noop
-        })
+        self.push(GizmoPrimitive::Polyline {
+            points,
+            closed: false,
+            width: stroke.width,
+            color: stroke.color,
+        });
     }
 
     pub(crate) fn sector(
-        &self,
+        &mut self,
         radius: f64,
         start_angle: f64,
         end_angle: f64,
         fill: impl Into<Color32>,
-        stroke: impl Into<Stroke>,
-    ) -> Mesh {
+    ) {
         let angle_delta = end_angle - start_angle;
         let step_count = steps(angle_delta.abs());
 
         if step_count < 2 {
-            return Mesh::default();
+            return;
         }
 
-        let mut points = Vec::with_capacity(step_count + 1);
-
         let step_size = angle_delta / (step_count - 1) as f64;
 
         if ((start_angle - end_angle).abs() - TAU).abs() < step_size.abs() {
-            return self.filled_circle(radius, fill.into(), stroke);
+            self.filled_circle(radius, fill.into());
+            return;
         }
 
-        points.push(DVec3::new(0.0, 0.0, 0.0));
+        let points = std::iter::once(DVec3::ZERO).chain(arc_points(
+            DVec3::ZERO,
+            DVec3::X * radius,
+            DVec3::Z * radius,
+            start_angle,
+            end_angle,
+        ));
 
-        let (sin_step, cos_step) = step_size.sin_cos();
-        let (mut sin_angle, mut cos_angle) = start_angle.sin_cos();
+        self.polygon(points, fill);
+    }
+}
 
-        for _ in 0..step_count {
-            let x = cos_angle * radius;
-            let z = sin_angle * radius;
+/// Converts world space primitives to screen space, replacing the contents of `out`.
+///
+/// Circles, arcs and arrows are converted to polylines and polygons.
+/// Parts of the primitives that are behind the camera are clipped.
+pub(crate) fn primitives_to_screen(
+    primitives: &[GizmoPrimitive],
+    view_projection: DMat4,
+    viewport: Rect,
+    out: &mut Primitives,
+) {
+    out.clear();
+
+    let mut clip = std::mem::take(&mut out.clip_points);
+    for primitive in primitives {
+        clip.clear();
+        primitive_to_screen(primitive, view_projection, viewport, &mut clip, out);
+    }
+    out.clip_points = clip;
+}
 
-            points.push(DVec3::new(x, 0.0, z));
+fn primitive_to_screen(
+    primitive: &GizmoPrimitive,
+    view_projection: DMat4,
+    viewport: Rect,
+    clip: &mut Vec<DVec4>,
+    out: &mut Primitives,
+) {
+    let to_clip = |point: DVec3| view_projection * point.extend(1.0);
+
+    match primitive {
+        GizmoPrimitive::Polyline {
+            points,
+            closed,
+            width,
+            color,
+        } => {
+            clip.extend(points.iter().map(|&point| to_clip(point.into())));
+            polyline_to_screen(clip, *closed, *width, *color, viewport, out);
+        }
+        GizmoPrimitive::Polygon { points, color } => {
+            clip.extend(points.iter().map(|&point| to_clip(point.into())));
+            polygon_to_screen(clip, *color, viewport, out);
+        }
+        GizmoPrimitive::Circle {
+            center,
+            x_axis,
+            y_axis,
+            width,
+            color,
+            filled,
+        } => {
+            let points = arc_points(
+                (*center).into(),
+                (*x_axis).into(),
+                (*y_axis).into(),
+                0.0,
+                TAU,
+            );
+            // The last point is the same as the first one
+            let count = points.len() - 1;
+            clip.extend(points.take(count).map(to_clip));
+
+            if *filled {
+                polygon_to_screen(clip, *color, viewport, out);
+            } else {
+                polyline_to_screen(clip, true, *width, *color, viewport, out);
+            }
+        }
+        GizmoPrimitive::Arc {
+            center,
+            x_axis,
+            y_axis,
+            start_angle,
+            end_angle,
+            width,
+            color,
+        } => {
+            let points = arc_points(
+                (*center).into(),
+                (*x_axis).into(),
+                (*y_axis).into(),
+                *start_angle,
+                *end_angle,
+            );
+
+            let closed = (end_angle - start_angle).abs() >= TAU - 1e-6;
+            let count = if closed {
+                points.len() - 1
+            } else {
+                points.len()
+            };
+            clip.extend(points.take(count).map(to_clip));
 
-            let new_sin = sin_angle * cos_step + cos_angle * sin_step;
-            let new_cos = cos_angle * cos_step - sin_angle * sin_step;
+            polyline_to_screen(clip, closed, *width, *color, viewport, out);
+        }
+        GizmoPrimitive::Arrow {
+            from,
+            to,
+            width,
+            color,
+        } => {
+            let (from, to) = (to_clip((*from).into()), to_clip((*to).into()));
+            if from.w < MIN_CLIP_W || to.w < MIN_CLIP_W {
+                return;
+            }
 
-            sin_angle = new_sin;
-            cos_angle = new_cos;
+            let start = DVec3::from(clip_to_screen(from, viewport));
+            let end = DVec3::from(clip_to_screen(to, viewport));
+            let direction = (end - start).truncate().normalize_or_zero();
+            let cross = DVec3::new(-direction.y, direction.x, 0.0) * *width as f64 / 2.0;
+
+            let mut points = out.take_points();
+            points.extend([start - cross, start + cross, end].map(mint::Vector3::from));
+            out.primitives.push(GizmoPrimitive::Polygon {
+                points,
+                color: *color,
+            });
         }
+    }
+}
+
+/// Converts a clip space polyline to screen space, splitting it into the parts
+/// that are in front of the camera.
+fn polyline_to_screen(
+    clip: &[DVec4],
+    closed: bool,
+    width: f32,
+    color: Color32,
+    viewport: Rect,
+    out: &mut Primitives,
+) {
+    let to_screen = |clip: DVec4| clip_to_screen(clip, viewport);
+
+    if clip.iter().all(|point| point.w >= MIN_CLIP_W) {
+        let mut points = out.take_points();
+        points.extend(clip.iter().map(|&point| to_screen(point)));
+        out.primitives.push(GizmoPrimitive::Polyline {
+            points,
+            closed,
+            width,
+            color,
+        });
+        return;
+    }
+
+    let segment_count = if closed {
+        clip.len()
+    } else {
+        clip.len().saturating_sub(1)
+    };
+
+    // Split the line into the parts that are in front of the camera
+    let mut current = out.take_points();
+    for i in 0..segment_count {
+        let (a, b) = (clip[i], clip[(i + 1) % clip.len()]);
+        match (a.w >= MIN_CLIP_W, b.w >= MIN_CLIP_W) {
+            (true, true) => {
+                if current.is_empty() {
+                    current.push(to_screen(a));
+                }
+                current.push(to_screen(b));
+            }
+            (true, false) => {
+                if current.is_empty() {
+                    current.push(to_screen(a));
+                }
+                current.push(to_screen(clip_to_near(a, b)));
+                let points = std::mem::replace(&mut current, out.take_points());
+                out.primitives.push(GizmoPrimitive::Polyline {
+                    points,
+                    closed: false,
+                    width,
+                    color,
+                });
+            }
+            (false, true) => {
+                current.push(to_screen(clip_to_near(b, a)));
+                current.push(to_screen(b));
+            }
+            (false, false) => {}
+        }
+    }
+
+    if current.len() > 1 {
+        out.primitives.push(GizmoPrimitive::Polyline {
+            points: current,
+            closed: false,
+            width,
+            color,
+        });
+    } else {
+        out.recycle_points(current);
+    }
+}
+
+/// Converts a clip space polygon to screen space, clipping it against the near plane.
+fn polygon_to_screen(clip: &[DVec4], color: Color32, viewport: Rect, out: &mut Primitives) {
+    let mut points = out.take_points();
+    for (i, &a) in clip.iter().enumerate() {
+        let b = clip[(i + 1) % clip.len()];
+        if a.w >= MIN_CLIP_W {
+            points.push(clip_to_screen(a, viewport));
+        }
+        if (a.w >= MIN_CLIP_W) != (b.w >= MIN_CLIP_W) {
+            points.push(clip_to_screen(clip_to_near(a, b), viewport));
+        }
+    }
+
+    if points.len() > 2 {
+        out.primitives
+            .push(GizmoPrimitive::Polygon { points, color });
+    } else {
+        out.recycle_points(points);
+    }
+}
+
+/// Screen space position of a clip space point, with the normalized depth as z.
+fn clip_to_screen(clip: DVec4, viewport: Rect) -> mint::Vector3<f64> {
+    let ndc = clip.truncate() / clip.w;
+    let center = viewport.center();
+    mint::Vector3::from([
+        center.x as f64 + ndc.x * viewport.width() as f64 / 2.0,
+        center.y as f64 - ndc.y * viewport.height() as f64 / 2.0,
+        ndc.z,
+    ])
+}
 
-        let points = points
-            .into_iter()
-            .filter_map(|point| self.vec3_to_pos2(point))
-            .collect::<Vec<_>>();
+/// Buffers reused between tessellations.
+#[cfg(feature = "epaint")]
+#[derive(Default)]
+pub(crate) struct TessellationScratch {
+    /// Pixels per point the tessellator was created with
+    pixels_per_point: f32,
+    tessellator: Option<Tessellator>,
+    mesh: Mesh,
+    /// Points of the shape being tessellated
+    points: Vec<emath::Pos2>,
+}
 
-        self.tessellate_shape(Shape::convex_polygon(points, fill, stroke))
+#[cfg(feature = "epaint")]
+impl std::fmt::Debug for TessellationScratch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TessellationScratch")
+            .field("pixels_per_point", &self.pixels_per_point)
+            .field("mesh", &self.mesh)
+            .field("points", &self.points)
+            .finish_non_exhaustive()
     }
+}
+
+/// Tessellates screen space primitives to triangles, replacing the contents of `draw_data`.
+#[cfg(feature = "epaint")]
+pub(crate) fn tessellate_into(
+    primitives: &[GizmoPrimitive],
+    pixels_per_point: f32,
+    scratch: &mut TessellationScratch,
+    draw_data: &mut GizmoDrawData,
+) {
+    if scratch.pixels_per_point != pixels_per_point {
+        scratch.pixels_per_point = pixels_per_point;
+        scratch.tessellator = None;
+    }
+    let tessellator = scratch.tessellator.get_or_insert_with(|| {
+        Tessellator::new(
+            pixels_per_point,
+            TessellationOptions {
+                feathering: true,
+                ..Default::default()
+            },
+            Default::default(),
+            Default::default(),
+        )
+    });
+
+    scratch.mesh.clear();
+    for primitive in primitives {
+        let (points, closed, fill, stroke) = match primitive {
+            GizmoPrimitive::Polyline {
+                points,
+                closed,
+                width,
+                color,
+            } if points.len() > 1 => (
+                points,
+                *closed,
+                ecolor::Color32::TRANSPARENT,
+                epaint::Stroke::new(*width, *color),
+            ),
+            GizmoPrimitive::Polygon { points, color } if points.len() > 2 => {
+                (points, true, (*color).into(), epaint::Stroke::NONE)
+            }
+            _ => continue,
+        };
+
+        scratch.points.clear();
+        scratch.points.extend(
+            points
+                .iter()
+                .map(|point| emath::Pos2::new(point.x as f32, point.y as f32)),
+        );
 
-    fn vec3_to_pos2(&self, vec: DVec3) -> Option<Pos2> {
-        world_to_screen(self.viewport, self.mvp, vec)
+        let path = PathShape {
+            points: std::mem::take(&mut scratch.points),
+            closed,
+            fill,
+            stroke,
+        };
+        tessellator.tessellate_path(&path, &mut scratch.mesh);
+        scratch.points = path.points;
     }
+
+    let mesh = &scratch.mesh;
+
+    draw_data.vertices.clear();
+    draw_data.colors.clear();
+    draw_data.indices.clear();
+
+    draw_data.vertices.extend(
+        mesh.vertices
+            .iter()
+            .map(|vertex| [vertex.pos.x, vertex.pos.y]),
+    );
+    draw_data.colors.extend(
+        mesh.vertices
+            .iter()
+            .map(|vertex| Rgba::from(Color32::from(vertex.color)).to_array()),
+    );
+    draw_data.indices.extend_from_slice(&mesh.indices);
+}
+
+/// Point where the line from `a` to `b` crosses the near clipping limit.
+/// `a` is expected to be in front and `b` behind the camera, or vice versa.
+fn clip_to_near(a: DVec4, b: DVec4) -> DVec4 {
+    let t = (MIN_CLIP_W - a.w) / (b.w - a.w);
+    a + (b - a) * t
+}
+
+fn arc_points(
+    center: DVec3,
+    x_axis: DVec3,
+    y_axis: DVec3,
+    start_angle: f64,
+    end_angle: f64,
+) -> impl ExactSizeIterator<Item = DVec3> {
+    let angle = f64::clamp(end_angle - start_angle, -TAU, TAU);
+
+    let step_count = steps(angle).max(2);
+    let step_size = angle / (step_count - 1) as f64;
+
+    (0..step_count).map(move |i| {
+        let angle = start_angle + step_size * i as f64;
+        center + x_axis * angle.cos() + y_axis * angle.sin()
+    })
 }
 
 fn steps(angle: f64) -> usize {
     (STEPS_PER_RAD * angle.abs()).ceil().max(1.0) as usize
 }
+
+/// Calculates the convex hull of the given points.
+/// Writes indices of the hull points to `hull`, using `sorted` as a scratch buffer.
+///
+/// Uses Andrew's monotone chain algorithm.
+fn convex_hull(points: &[Pos2], sorted: &mut Vec<usize>, hull: &mut Vec<usize>) {
+    sorted.clear();
+    sorted.extend(0..points.len());
+    sorted.sort_unstable_by(|&a, &b| {
+        let (a, b) = (points[a], points[b]);
+        a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+    });
+
+    hull.clear();
+    if sorted.len() < 3 {
+        hull.extend_from_slice(sorted);
+        return;
+    }
+
+    let cross = |o: usize, a: usize, b: usize| {
+        let (o, a, b) = (points[o], points[a], points[b]);
+        (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+    };
+
+    // Lower hull
+    for &point in sorted.iter() {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+
+    // Upper hull
+    let lower_len = hull.len() + 1;
+    for &point in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower_len
+            && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+        {
+            hull.pop();
+        }
+        hull.push(point);
+    }
+
+    // The last point is the same as the first one
+    hull.pop();
+}
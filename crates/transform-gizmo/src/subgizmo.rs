@@ -5,7 +5,9 @@ use std::ops::Deref;
 
 use enum_dispatch::enum_dispatch;
 
-use crate::{config::PreparedGizmoConfig, gizmo::Ray, GizmoDrawData, GizmoResult};
+use crate::result::{GizmoHandle, GizmoResultKind};
+use crate::shape::Primitives;
+use crate::{config::PreparedGizmoConfig, gizmo::Ray};
 
 pub(crate) use arcball::ArcballSubGizmo;
 pub(crate) use rotation::RotationSubGizmo;
@@ -18,7 +20,7 @@ pub(crate) mod rotation;
 pub(crate) mod scale;
 pub(crate) mod translation;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 /// Enumeration of different subgizmo types.
 #[enum_dispatch(SubGizmoControl)]
 pub(crate) enum SubGizmo {
@@ -42,13 +44,26 @@ pub(crate) trait SubGizmoControl {
     fn is_focused(&self) -> bool;
     /// Returns true if this subgizmo is currently active.
     fn is_active(&self) -> bool;
+    /// Returns the current opacity of this subgizmo.
+    fn opacity(&self) -> f32;
+    /// Advance the highlight and fade transitions of this subgizmo.
+    /// If `delta_time` is not given, the transitions are finished immediately.
+    fn animate(&mut self, delta_time: Option<f32>, visible: bool);
+    /// Returns true if the transitions of the latest [`SubGizmoControl::animate`]
+    /// call did not reach their targets.
+    fn is_animating(&self) -> bool;
     /// Pick the subgizmo based on pointer ray. If it is close enough to
     /// the mouse pointer, distance from camera to the subgizmo is returned.
     fn pick(&mut self, ray: Ray) -> Option<f64>;
+    /// Start dragging the subgizmo from the pointer ray in the middle of a drag,
+    /// when an axis constraint switches the dragged handle.
+    fn resume(&mut self, ray: Ray);
     /// Update the subgizmo based on pointer ray and interaction.
-    fn update(&mut self, ray: Ray) -> Option<GizmoResult>;
-    /// Draw the subgizmo.
-    fn draw(&self) -> GizmoDrawData;
+    fn update(&mut self, ray: Ray) -> Option<GizmoResultKind>;
+    /// Handle of the gizmo this subgizmo represents.
+    fn handle(&self) -> GizmoHandle;
+    /// Draw the subgizmo, adding its primitives to `primitives`.
+    fn draw(&self, primitives: &mut Primitives);
 }
 
 pub(crate) trait SubGizmoKind: 'static {
@@ -58,15 +73,24 @@ pub(crate) trait SubGizmoKind: 'static {
     fn pick(subgizmo: &mut SubGizmoConfig<Self>, ray: Ray) -> Option<f64>
     where
         Self: Sized;
-    fn update(subgizmo: &mut SubGizmoConfig<Self>, ray: Ray) -> Option<GizmoResult>
+    fn resume(subgizmo: &mut SubGizmoConfig<Self>, ray: Ray)
+    where
+        Self: Sized,
+    {
+        Self::pick(subgizmo, ray);
+    }
+    fn update(subgizmo: &mut SubGizmoConfig<Self>, ray: Ray) -> Option<GizmoResultKind>
+    where
+        Self: Sized;
+    fn handle(subgizmo: &SubGizmoConfig<Self>) -> GizmoHandle
     where
         Self: Sized;
-    fn draw(subgizmo: &SubGizmoConfig<Self>) -> GizmoDrawData
+    fn draw(subgizmo: &SubGizmoConfig<Self>, primitives: &mut Primitives)
     where
         Self: Sized;
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct SubGizmoConfig<T: SubGizmoKind> {
     id: u64,
     /// Additional parameters depending on the subgizmo kind.
@@ -79,8 +103,14 @@ pub(crate) struct SubGizmoConfig<T: SubGizmoKind> {
     /// Whether this subgizmo is active this frame
     pub(crate) active: bool,
     /// Opacity of the subgizmo for this frame.
-    /// A fully invisible subgizmo cannot be interacted with.
     pub(crate) opacity: f32,
+    /// Opacity the subgizmo is transitioning to, based on the view direction.
+    /// A fully invisible subgizmo cannot be interacted with.
+    pub(crate) visibility: f32,
+    /// How highlighted the subgizmo is for this frame, from 0 (not at all) to 1 (fully).
+    pub(crate) highlight: f32,
+    /// Whether the opacity or highlight is still transitioning towards its target.
+    pub(crate) animating: bool,
     /// Implementation-specific state of the subgizmo.
     pub(crate) state: T::State,
 }
@@ -110,6 +140,9 @@ where
             focused: false,
             active: false,
             opacity: 0.0,
+            visibility: 1.0,
+            highlight: 0.0,
+            animating: false,
             state: Default::default(),
         }
     }
@@ -142,15 +175,84 @@ where
         self.active
     }
 
+    fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    fn animate(&mut self, delta_time: Option<f32>, visible: bool) {
+        let visuals = self.config.visuals;
+
+        let target_opacity = if visible { self.visibility } else { 0.0 };
+        self.opacity = transition(
+            self.opacity,
+            target_opacity,
+            visuals.fade_duration,
+            delta_time,
+        );
+
+        let target_highlight = if self.focused { 1.0 } else { 0.0 };
+        self.highlight = transition(
+            self.highlight,
+            target_highlight,
+            visuals.highlight_duration,
+            delta_time,
+        );
+
+        self.animating = self.opacity != target_opacity || self.highlight != target_highlight;
+    }
+
+    fn is_animating(&self) -> bool {
+        self.animating
+    }
+
     fn pick(&mut self, ray: Ray) -> Option<f64> {
         T::pick(self, ray)
     }
 
-    fn update(&mut self, ray: Ray) -> Option<GizmoResult> {
+    fn resume(&mut self, ray: Ray) {
+        T::resume(self, ray);
+    }
+
+    fn update(&mut self, ray: Ray) -> Option<GizmoResultKind> {
         T::update(self, ray)
     }
 
-    fn draw(&self) -> GizmoDrawData {
-        T::draw(self)
+    fn handle(&self) -> GizmoHandle {
+        T::handle(self)
+    }
+
+    fn draw(&self, primitives: &mut Primitives) {
+        T::draw(self, primitives);
+    }
+}
+
+/// Moves `value` linearly towards `target`, so that a full transition takes `duration` seconds.
+fn transition(value: f32, target: f32, duration: f32, delta_time: Option<f32>) -> f32 {
+    match delta_time {
+        Some(delta_time) if duration > 0.0 => {
+            let step = delta_time / duration;
+            if value < target {
+                (value + step).min(target)
+            } else {
+                (value - step).max(target)
+            }
+        }
+        _ => target,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transition_steps_towards_target() {
+        assert_eq!(transition(0.0, 1.0, 0.2, Some(0.05)), 0.25);
+        assert_eq!(transition(1.0, 0.0, 0.2, Some(0.05)), 0.75);
+        assert_eq!(transition(0.9, 1.0, 0.2, Some(0.05)), 1.0);
+        assert_eq!(transition(0.1, 0.0, 0.2, Some(0.05)), 0.0);
+        // Instant transitions
+        assert_eq!(transition(0.0, 1.0, 0.2, None), 1.0);
+        assert_eq!(transition(1.0, 0.0, 0.0, Some(0.05)), 0.0);
     }
 }
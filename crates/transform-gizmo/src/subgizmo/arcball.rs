@@ -1,18 +1,20 @@
+use crate::config::Color32;
 use crate::math::{screen_to_world, DQuat, Pos2};
+use crate::result::{GizmoHandle, GizmoResultKind};
+use crate::shape::Primitives;
 use crate::subgizmo::common::{draw_circle, pick_circle};
 use crate::subgizmo::{SubGizmoConfig, SubGizmoKind};
-use crate::{config::PreparedGizmoConfig, gizmo::Ray, GizmoDrawData, GizmoResult};
-use ecolor::Color32;
+use crate::{config::PreparedGizmoConfig, gizmo::Ray};
 
 pub(crate) type ArcballSubGizmo = SubGizmoConfig<Arcball>;
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub(crate) struct ArcballState {
     last_pos: Pos2,
     total_rotation: DQuat,
 }
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub(crate) struct Arcball;
 
 impl SubGizmoKind for Arcball {
@@ -35,7 +37,7 @@ impl SubGizmoKind for Arcball {
         Some(f64::MAX)
     }
 
-    fn update(subgizmo: &mut ArcballSubGizmo, ray: Ray) -> Option<GizmoResult> {
+    fn update(subgizmo: &mut ArcballSubGizmo, ray: Ray) -> Option<GizmoResultKind> {
         let dir = ray.screen_pos - subgizmo.state.last_pos;
 
         let rotation_delta = if dir.length_sq() > f32::EPSILON {
@@ -55,19 +57,24 @@ impl SubGizmoKind for Arcball {
         subgizmo.state.last_pos = ray.screen_pos;
         subgizmo.state.total_rotation = rotation_delta.mul_quat(subgizmo.state.total_rotation);
 
-        Some(GizmoResult::Arcball {
+        Some(GizmoResultKind::Arcball {
             delta: rotation_delta.into(),
             total: subgizmo.state.total_rotation.into(),
         })
     }
 
-    fn draw(subgizmo: &ArcballSubGizmo) -> GizmoDrawData {
+    fn handle(_subgizmo: &ArcballSubGizmo) -> GizmoHandle {
+        GizmoHandle::Arcball
+    }
+
+    fn draw(subgizmo: &ArcballSubGizmo, primitives: &mut Primitives) {
         draw_circle(
             &subgizmo.config,
-            Color32::WHITE.gamma_multiply(if subgizmo.focused { 0.10 } else { 0.0 }),
+            Color32::WHITE.gamma_multiply(0.10 * subgizmo.highlight * subgizmo.opacity),
             arcball_radius(&subgizmo.config),
             true,
-        )
+            primitives,
+        );
     }
 }
 
@@ -1,10 +1,13 @@
+use crate::config::Color32;
+use crate::config::{HandleStyle, HandleTip, PlaneShape};
 use crate::math::{ray_to_plane_origin, segment_to_segment};
 use crate::GizmoMode;
-use ecolor::Color32;
-use std::ops::{Add, RangeInclusive};
+use std::f64::consts::TAU;
+use std::ops::RangeInclusive;
 
-use crate::shape::ShapeBuidler;
-use crate::{config::PreparedGizmoConfig, gizmo::Ray, GizmoDirection, GizmoDrawData};
+use crate::result::GizmoHandle;
+use crate::shape::{Primitives, ShapeBuidler};
+use crate::{config::PreparedGizmoConfig, gizmo::Ray, GizmoDirection};
 use glam::{DMat3, DMat4, DQuat, DVec3};
 
 const ARROW_FADE: RangeInclusive<f64> = 0.95..=0.99;
@@ -16,6 +19,16 @@ pub(crate) enum TransformKind {
     Plane,
 }
 
+impl TransformKind {
+    /// Handle of a subgizmo of this kind
+    pub(crate) fn handle(self, mode: GizmoMode, direction: GizmoDirection) -> GizmoHandle {
+        match self {
+            Self::Axis => GizmoHandle::Axis { mode, direction },
+            Self::Plane => GizmoHandle::Plane { mode, direction },
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct PickResult {
     pub subgizmo_point: DVec3,
@@ -31,21 +44,33 @@ struct ArrowParams {
     length: f64,
 }
 
+fn handle_style(config: &PreparedGizmoConfig, mode: GizmoMode) -> HandleStyle {
+    match mode {
+        GizmoMode::Scale => config.visuals.scale_handle,
+        _ => config.visuals.translation_handle,
+    }
+}
+
 fn arrow_params(config: &PreparedGizmoConfig, direction: DVec3, mode: GizmoMode) -> ArrowParams {
-    let width = (config.scale_factor * config.visuals.stroke_width) as f64;
+    let style = handle_style(config, mode);
+    let width = (config.scale_factor * config.visuals.stroke_width * style.thickness) as f64;
 
     let (start, length) = if mode == GizmoMode::Translate && config.modes.contains(GizmoMode::Scale)
     {
         // Modes contain both translate and scale. Use a bit different translate arrow, so the modes do not overlap.
-        let length = (config.scale_factor * config.visuals.gizmo_size) as f64;
-        let start = direction * (length + (width * 3.0));
+        let scale_length = (config.scale_factor
+            * config.visuals.gizmo_size
+            * config.visuals.scale_handle.length) as f64;
+        let start = direction * (scale_length + (width * 3.0));
 
-        let length = length * 0.2 + width;
+        let length =
+            (config.scale_factor * config.visuals.gizmo_size * style.length) as f64 * 0.2 + width;
 
         (start, length)
     } else {
         let start = direction * (width * 0.5 + inner_circle_radius(config));
-        let mut length = (config.scale_factor * config.visuals.gizmo_size) as f64 - start.length();
+        let mut length = (config.scale_factor * config.visuals.gizmo_size * style.length) as f64
+            - start.length();
 
         if config.modes.len() > 1 {
             length -= width * 2.0;
@@ -124,7 +149,12 @@ pub(crate) fn pick_plane(
         - ((1.0 - dot) - *PLANE_FADE.start()) / (*PLANE_FADE.end() - *PLANE_FADE.start()))
     .min(1.0);
 
-    let picked = visibility > 0.0 && dist_from_origin <= plane_size(config);
+    let pick_radius = match config.visuals.plane_shape {
+        PlaneShape::Square => plane_size(config),
+        // Picked only within the drawn circle
+        PlaneShape::Circle => plane_size(config) * 0.5,
+    };
+    let picked = visibility > 0.0 && dist_from_origin <= pick_radius;
 
     PickResult {
         subgizmo_point: ray_point,
@@ -165,15 +195,16 @@ pub(crate) fn pick_circle(
 pub(crate) fn draw_arrow(
     config: &PreparedGizmoConfig,
     opacity: f32,
-    focused: bool,
+    highlight: f32,
     direction: GizmoDirection,
     mode: GizmoMode,
-) -> GizmoDrawData {
+    primitives: &mut Primitives,
+) {
     if opacity <= 1e-4 {
-        return GizmoDrawData::default();
+        return;
     }
 
-    let color = gizmo_color(config, focused, direction).gamma_multiply(opacity);
+    let color = gizmo_color(config, highlight, direction).gamma_multiply(opacity);
 
     let transform = if config.local_space() {
         DMat4::from_rotation_translation(config.rotation, config.translation)
@@ -181,64 +212,83 @@ pub(crate) fn draw_arrow(
         DMat4::from_translation(config.translation)
     };
 
-    let shape_builder = ShapeBuidler::new(
-        config.view_projection * transform,
-        config.viewport,
-        config.pixels_per_point,
-    );
+    let mut shape_builder = ShapeBuidler::new(transform, config, primitives);
 
     let direction = gizmo_local_normal(config, direction);
 
     let arrow_params = arrow_params(config, direction, mode);
+    let style = handle_style(config, mode);
 
-    let tip_stroke_width = 2.4 * config.visuals.stroke_width;
+    let stroke_width = config.visuals.stroke_width * style.thickness;
+    let tip_stroke_width = 2.4 * stroke_width;
     let tip_length = (tip_stroke_width * config.scale_factor) as f64;
 
     let tip_start = arrow_params.end - arrow_params.direction * tip_length;
 
-    let mut draw_data = GizmoDrawData::default();
-    draw_data = draw_data.add(
-        shape_builder
-            .line_segment(
-                arrow_params.start,
-                tip_start,
-                (config.visuals.stroke_width, color),
-            )
-            .into(),
-    );
+    shape_builder.line_segment(arrow_params.start, tip_start, (stroke_width, color));
 
-    match mode {
-        GizmoMode::Scale => {
-            draw_data = draw_data.add(
-                shape_builder
-                    .line_segment(tip_start, arrow_params.end, (tip_stroke_width, color))
-                    .into(),
-            );
+    let tip_center = tip_start + arrow_params.direction * tip_length * 0.5;
+    let tip_radius = tip_length * 0.5;
+    let (tangent, bitangent) = arrow_params.direction.any_orthonormal_pair();
+
+    match style.tip {
+        HandleTip::Triangle => {
+            shape_builder.arrow(tip_start, arrow_params.end, (tip_stroke_width, color));
         }
-        GizmoMode::Translate => {
-            draw_data = draw_data.add(
-                shape_builder
-                    .arrow(tip_start, arrow_params.end, (tip_stroke_width, color))
-                    .into(),
-            );
+        HandleTip::Bar => {
+            shape_builder.line_segment(tip_start, arrow_params.end, (tip_stroke_width, color));
+        }
+        HandleTip::Cone => {
+            let base = circle_points(tip_start, tangent, bitangent, tip_radius);
+            let points: [DVec3; CIRCLE_STEPS + 1] =
+                std::array::from_fn(|i| base.get(i).copied().unwrap_or(arrow_params.end));
+            shape_builder.convex_hull(&points, color);
+        }
+        HandleTip::Cube => {
+            let points: [DVec3; 8] = std::array::from_fn(|i| {
+                let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+                tip_center
+                    + (arrow_params.direction * sign(4) + tangent * sign(2) + bitangent * sign(1))
+                        * tip_radius
+            });
+            shape_builder.convex_hull(&points, color);
+        }
+        HandleTip::Sphere => {
+            // Silhouette of a sphere is a circle facing the viewer
+            let (mut right, mut up) = (config.view_right(), config.view_up());
+            if config.local_space() {
+                right = config.rotation.inverse() * right;
+                up = config.rotation.inverse() * up;
+            }
+            let points = circle_points(tip_center, right, up, tip_radius);
+            shape_builder.convex_hull(&points, color);
         }
-        GizmoMode::Rotate => {}
     }
+}
+
+/// Number of points in [`circle_points`]
+const CIRCLE_STEPS: usize = 32;
 
-    draw_data
+/// Points on a circle spanned by the given unit vectors
+fn circle_points(center: DVec3, a: DVec3, b: DVec3, radius: f64) -> [DVec3; CIRCLE_STEPS] {
+    std::array::from_fn(|i| {
+        let angle = i as f64 / CIRCLE_STEPS as f64 * TAU;
+        center + (a * angle.cos() + b * angle.sin()) * radius
+    })
 }
 
 pub(crate) fn draw_plane(
     config: &PreparedGizmoConfig,
     opacity: f32,
-    focused: bool,
+    highlight: f32,
     direction: GizmoDirection,
-) -> GizmoDrawData {
+    primitives: &mut Primitives,
+) {
     if opacity <= 1e-4 {
-        return GizmoDrawData::default();
+        return;
     }
 
-    let color = gizmo_color(config, focused, direction).gamma_multiply(opacity);
+    let color = gizmo_color(config, highlight, direction).gamma_multiply(opacity);
 
     let transform = if config.local_space() {
         DMat4::from_rotation_translation(config.rotation, config.translation)
@@ -246,33 +296,28 @@ pub(crate) fn draw_plane(
         DMat4::from_translation(config.translation)
     };
 
-    let shape_builder = ShapeBuidler::new(
-        config.view_projection * transform,
-        config.viewport,
-        config.pixels_per_point,
-    );
+    let mut shape_builder = ShapeBuidler::new(transform, config, primitives);
 
     let scale = plane_size(config) * 0.5;
     let a = plane_bitangent(direction) * scale;
     let b = plane_tangent(direction) * scale;
     let origin = plane_local_origin(config, direction);
 
-    let mut draw_data = GizmoDrawData::default();
-    draw_data = draw_data.add(
-        shape_builder
-            .polygon(
-                &[
-                    origin - b - a,
-                    origin + b - a,
-                    origin + b + a,
-                    origin - b + a,
-                ],
-                color,
-                (0.0, Color32::TRANSPARENT),
-            )
-            .into(),
-    );
-    draw_data
+    match config.visuals.plane_shape {
+        PlaneShape::Square => shape_builder.polygon(
+            [
+                origin - b - a,
+                origin + b - a,
+                origin + b + a,
+                origin - b + a,
+            ],
+            color,
+        ),
+        PlaneShape::Circle => shape_builder.polygon(
+            circle_points(origin, a.normalize(), b.normalize(), scale),
+            color,
+        ),
+    }
 }
 
 pub(crate) fn draw_circle(
@@ -280,9 +325,10 @@ pub(crate) fn draw_circle(
     color: Color32,
     radius: f64,
     filled: bool,
-) -> GizmoDrawData {
+    primitives: &mut Primitives,
+) {
     if color.a() == 0 {
-        return GizmoDrawData::default();
+        return;
     }
 
     let rotation = {
@@ -295,27 +341,169 @@ pub(crate) fn draw_circle(
 
     let transform = DMat4::from_rotation_translation(rotation, config.translation);
 
-    let shape_builder = ShapeBuidler::new(
-        config.view_projection * transform,
-        config.viewport,
-        config.pixels_per_point,
-    );
+    let mut shape_builder = ShapeBuidler::new(transform, config, primitives);
 
-    let mut draw_data = GizmoDrawData::default();
     if filled {
-        draw_data = draw_data.add(
-            shape_builder
-                .filled_circle(radius, color, (0.0, Color32::TRANSPARENT))
-                .into(),
-        );
+        shape_builder.filled_circle(radius, color);
     } else {
-        draw_data = draw_data.add(
-            shape_builder
-                .circle(radius, (config.visuals.stroke_width, color))
-                .into(),
+        shape_builder.circle(radius, (config.visuals.stroke_width, color));
+    }
+}
+
+/// Highlights the snap point the gizmo is snapped to, with a circle showing the snapping distance
+pub(crate) fn draw_snap_point(config: &PreparedGizmoConfig, primitives: &mut Primitives) {
+    let color = config
+        .visuals
+        .highlight_color
+        .unwrap_or(config.visuals.s_color);
+    let radius = (config.pixel_size * config.snap_point_distance) as f64;
+
+    draw_circle(config, color, radius, false, primitives);
+}
+
+/// Color of the drag overlays of a subgizmo pointing in the given direction
+pub(crate) fn overlay_color(config: &PreparedGizmoConfig, direction: GizmoDirection) -> Color32 {
+    gizmo_color(config, 0.0, direction).gamma_multiply(0.5)
+}
+
+/// Distance from the gizmo that the drag overlays extend to, in world units
+pub(crate) fn overlay_extent(config: &PreparedGizmoConfig) -> f64 {
+    (config.scale_factor * config.visuals.gizmo_size) as f64 * 1.5
+}
+
+fn world_shape_builder<'a>(
+    config: &PreparedGizmoConfig,
+    primitives: &'a mut Primitives,
+) -> ShapeBuidler<'a> {
+    ShapeBuidler::new(DMat4::IDENTITY, config, primitives)
+}
+
+/// Draws an infinite guide line going through `point`
+pub(crate) fn draw_guide_line(
+    config: &PreparedGizmoConfig,
+    color: Color32,
+    point: DVec3,
+    direction: DVec3,
+    primitives: &mut Primitives,
+) {
+    world_shape_builder(config, primitives).infinite_line(
+        point,
+        direction,
+        (config.visuals.stroke_width * 0.5, color),
+    );
+}
+
+/// Draws a grid patch around the gizmo on the plane of the given direction.
+/// The grid lines are aligned to `origin`, and `spacing` is given along each of the [`plane_axes`].
+pub(crate) fn draw_guide_grid(
+    config: &PreparedGizmoConfig,
+    color: Color32,
+    origin: DVec3,
+    direction: GizmoDirection,
+    spacing: (f64, f64),
+    primitives: &mut Primitives,
+) {
+    let (a, b) = plane_axes(config, direction);
+    let extent = overlay_extent(config);
+
+    let grid_spacing = |spacing: f64| {
+        let mut spacing = if spacing > 0.0 && spacing.is_finite() {
+            spacing
+        } else {
+            extent / 4.0
+        };
+        // Keep the amount of lines reasonable
+        while extent / spacing > 16.0 {
+            spacing *= 2.0;
+        }
+        spacing
+    };
+    let spacing = (grid_spacing(spacing.0), grid_spacing(spacing.1));
+
+    // Grid node closest to the gizmo
+    let offset = config.translation - origin;
+    let center = origin
+        + a * (offset.dot(a) / spacing.0).round() * spacing.0
+        + b * (offset.dot(b) / spacing.1).round() * spacing.1;
+
+    let lines = (
+        (extent / spacing.0).ceil() as i64,
+        (extent / spacing.1).ceil() as i64,
+    );
+    let half_length = (lines.0 as f64 * spacing.0, lines.1 as f64 * spacing.1);
+
+    let mut shape_builder = world_shape_builder(config, primitives);
+    for (a, spacing, lines, b, half_length) in [
+        (a, spacing.0, lines.0, b, half_length.1),
+        (b, spacing.1, lines.1, a, half_length.0),
+    ] {
+        for i in -lines..=lines {
+            // Fade out towards the edges of the patch
+            let color = color.gamma_multiply(1.0 - i.abs() as f32 / (lines + 1) as f32);
+            let stroke = (config.visuals.stroke_width * 0.25, color);
+            let point = center + a * (i as f64 * spacing);
+
+            shape_builder.line_segment(point - b * half_length, point + b * half_length, stroke);
+        }
+    }
+}
+
+/// Draws tick marks along an axis going through `origin`, every `spacing` units.
+/// Only ticks that are within `range` from the origin are drawn.
+pub(crate) fn draw_snap_ticks(
+    config: &PreparedGizmoConfig,
+    color: Color32,
+    origin: DVec3,
+    direction: DVec3,
+    spacing: f64,
+    range: (f64, f64),
+    primitives: &mut Primitives,
+) {
+    // Skip ticks that would be too dense to be useful
+    if spacing / (config.pixel_size as f64) < 4.0 {
+        return;
+    }
+
+    let side = direction.cross(config.view_forward());
+    if side.length_squared() < 1e-10 {
+        return;
+    }
+    let side = side.normalize() * (config.scale_factor * config.visuals.stroke_width) as f64 * 1.5;
+
+    let first = (range.0 / spacing).ceil() as i64;
+    let last = (range.1 / spacing).floor() as i64;
+
+    let mut shape_builder = world_shape_builder(config, primitives);
+    for i in first..=last.min(first + 256) {
+        let point = origin + direction * i as f64 * spacing;
+        shape_builder.line_segment(
+            point - side,
+            point + side,
+            (config.visuals.stroke_width * 0.5, color),
         );
     }
-    draw_data
+}
+
+/// Draws a marker at the position where a drag started
+pub(crate) fn draw_start_ghost(
+    config: &PreparedGizmoConfig,
+    color: Color32,
+    position: DVec3,
+    primitives: &mut Primitives,
+) {
+    let points = circle_points(
+        position,
+        config.view_right(),
+        config.view_up(),
+        inner_circle_radius(config) * 0.5,
+    );
+
+    let mut shape_builder = world_shape_builder(config, primitives);
+    shape_builder.polygon(points, color.gamma_multiply(0.5));
+    shape_builder.polyline(
+        points.into_iter().chain(points.first().copied()),
+        (config.visuals.stroke_width * 0.5, color),
+    );
 }
 
 pub(crate) const fn plane_bitangent(direction: GizmoDirection) -> DVec3 {
@@ -336,6 +524,20 @@ pub(crate) const fn plane_tangent(direction: GizmoDirection) -> DVec3 {
     }
 }
 
+/// World space axes of the plane of the given direction
+pub(crate) fn plane_axes(
+    config: &PreparedGizmoConfig,
+    direction: GizmoDirection,
+) -> (DVec3, DVec3) {
+    let mut bitangent = plane_bitangent(direction);
+    let mut tangent = plane_tangent(direction);
+    if config.local_space() {
+        bitangent = config.rotation * bitangent;
+        tangent = config.rotation * tangent;
+    }
+    (bitangent, tangent)
+}
+
 pub(crate) fn plane_size(config: &PreparedGizmoConfig) -> f64 {
     (config.scale_factor * (config.visuals.gizmo_size * 0.1 + config.visuals.stroke_width * 2.0))
         as f64
@@ -389,9 +591,11 @@ pub(crate) fn gizmo_normal(config: &PreparedGizmoConfig, direction: GizmoDirecti
     normal
 }
 
+/// Color of a subgizmo pointing in the given direction.
+/// `highlight` is between 0 (not highlighted) and 1 (fully highlighted).
 pub(crate) fn gizmo_color(
     config: &PreparedGizmoConfig,
-    focused: bool,
+    highlight: f32,
     direction: GizmoDirection,
 ) -> Color32 {
     let color = match direction {
@@ -401,17 +605,178 @@ pub(crate) fn gizmo_color(
         GizmoDirection::View => config.visuals.s_color,
     };
 
-    let color = if focused {
-        config.visuals.highlight_color.unwrap_or(color)
-    } else {
-        color
-    };
+    // Ease in and out of the highlight
+    let t = highlight * highlight * (3.0 - 2.0 * highlight);
 
-    let alpha = if focused {
-        config.visuals.highlight_alpha
-    } else {
-        config.visuals.inactive_alpha
-    };
+    let color = lerp_color(color, config.visuals.highlight_color.unwrap_or(color), t);
+    let alpha = lerp(
+        config.visuals.inactive_alpha,
+        config.visuals.highlight_alpha,
+        t,
+    );
 
     color.linear_multiply(alpha)
 }
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn lerp_color(a: Color32, b: Color32, t: f32) -> Color32 {
+    let channel = |a: u8, b: u8| lerp(a as f32, b as f32, t).round() as u8;
+
+    Color32::from_rgba_premultiplied(
+        channel(a.r(), b.r()),
+        channel(a.g(), b.g()),
+        channel(a.b(), b.b()),
+        channel(a.a(), b.a()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{GizmoConfig, GizmoVisuals};
+    use crate::gizmo::GizmoPrimitive;
+    use crate::math::{Pos2, Transform};
+    use crate::test_utils::test_config;
+
+    fn prepared_config(visuals: GizmoVisuals) -> PreparedGizmoConfig {
+        let mut config = PreparedGizmoConfig::default();
+        config.update_for_config(GizmoConfig {
+            visuals,
+            ..test_config(GizmoMode::Translate)
+        });
+        config.update_for_targets(&[Transform::default()]);
+        config
+    }
+
+    #[test]
+    fn handle_tips() {
+        for tip in [
+            HandleTip::Triangle,
+            HandleTip::Cone,
+            HandleTip::Bar,
+            HandleTip::Cube,
+            HandleTip::Sphere,
+        ] {
+            let config = prepared_config(GizmoVisuals {
+                translation_handle: HandleStyle {
+                    tip,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            let mut primitives = Primitives::default();
+            draw_arrow(
+                &config,
+                1.0,
+                0.0,
+                GizmoDirection::X,
+                GizmoMode::Translate,
+                &mut primitives,
+            );
+
+            // The line ends where the tip starts
+            let [GizmoPrimitive::Polyline {
+                points: line,
+                width: line_width,
+                ..
+            }, tip_primitive] = primitives.primitives.as_slice()
+            else {
+                panic!("unexpected primitives {:?}", primitives.primitives);
+            };
+            let tip_start = line[1].x;
+            let tip_length = f64::from(2.4 * config.visuals.stroke_width * config.scale_factor);
+            let on_tip = |point: &mint::Vector3<f64>| {
+                point.x > tip_start - 1e-9 && point.x < tip_start + tip_length + 1e-9
+            };
+
+            match (tip, tip_primitive) {
+                (HandleTip::Triangle, GizmoPrimitive::Arrow { from, to, .. }) => {
+                    assert_eq!(from.x, tip_start);
+                    assert!((to.x - tip_start - tip_length).abs() < 1e-9);
+                }
+                (HandleTip::Bar, GizmoPrimitive::Polyline { points, width, .. }) => {
+                    assert_eq!(points.len(), 2);
+                    assert!(points.iter().all(on_tip));
+                    assert!(width > line_width);
+                }
+                (
+                    HandleTip::Cone | HandleTip::Cube | HandleTip::Sphere,
+                    GizmoPrimitive::Polygon { points, .. },
+                ) => {
+                    assert!(points.iter().all(on_tip), "{tip:?}");
+                    let expected_points = match tip {
+                        // Silhouette of a cube is a hexagon, or less when seen face-on
+                        HandleTip::Cube => 4..=6,
+                        HandleTip::Sphere => CIRCLE_STEPS..=CIRCLE_STEPS,
+                        _ => 3..=CIRCLE_STEPS + 1,
+                    };
+                    assert!(expected_points.contains(&points.len()), "{tip:?}");
+                }
+                _ => panic!("unexpected {tip:?} tip {tip_primitive:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn plane_shapes() {
+        for (shape, point_count) in [(PlaneShape::Square, 4), (PlaneShape::Circle, CIRCLE_STEPS)] {
+            let config = prepared_config(GizmoVisuals {
+                plane_shape: shape,
+                ..Default::default()
+            });
+            let mut primitives = Primitives::default();
+            draw_plane(&config, 1.0, 0.0, GizmoDirection::Z, &mut primitives);
+
+            let [GizmoPrimitive::Polygon { points, .. }] = primitives.primitives.as_slice() else {
+                panic!("unexpected primitives {:?}", primitives.primitives);
+            };
+            assert_eq!(points.len(), point_count);
+
+            // Centered on the plane handle, with corners or edge at half the plane size
+            let origin = plane_local_origin(&config, GizmoDirection::Z);
+            let half_size = plane_size(&config) * 0.5;
+            for point in points {
+                let offset = DVec3::from(*point) - origin;
+                assert!(offset.z.abs() < 1e-9);
+                let distance = match shape {
+                    PlaneShape::Square => offset.x.abs().max(offset.y.abs()),
+                    PlaneShape::Circle => offset.length(),
+                };
+                assert!((distance - half_size).abs() < 1e-9, "{shape:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn circle_plane_is_picked_within_the_circle() {
+        for shape in [PlaneShape::Square, PlaneShape::Circle] {
+            let config = prepared_config(GizmoVisuals {
+                plane_shape: shape,
+                ..Default::default()
+            });
+            let origin = plane_global_origin(&config, GizmoDirection::Z);
+            let size = plane_size(&config);
+
+            // Ray going straight through the plane at the offset from its center
+            let picked = |x: f64, y: f64| {
+                let ray = Ray {
+                    screen_pos: Pos2::default(),
+                    origin: origin + DVec3::new(x, y, 5.0) * size,
+                    direction: -DVec3::Z,
+                    surface_hit: None,
+                };
+                pick_plane(&config, ray, GizmoDirection::Z).picked
+            };
+
+            assert!(picked(0.0, 0.0));
+            assert!(picked(0.45, 0.0));
+            assert!(picked(0.0, -0.45));
+            // Inside the square, outside the circle
+            assert_eq!(picked(0.4, 0.4), shape == PlaneShape::Square);
+            assert!(!picked(1.2, 0.0));
+        }
+    }
+}
@@ -1,32 +1,42 @@
 use std::f64::consts::{FRAC_PI_2, PI, TAU};
 
-use ecolor::Color32;
+use crate::config::Color32;
 
 use crate::math::{
-    ray_to_plane_origin, rotation_align, round_to_interval, world_to_screen, DMat3, DMat4, DQuat,
-    DVec2, DVec3, Pos2,
+    ray_to_plane_origin, rotation_align, world_to_screen, DMat3, DMat4, DQuat, DVec2, DVec3, Pos2,
 };
-use crate::shape::ShapeBuidler;
-use crate::subgizmo::common::{gizmo_color, gizmo_local_normal, gizmo_normal, outer_circle_radius};
+use crate::shape::{Primitives, ShapeBuidler};
+use crate::subgizmo::common::{gizmo_color, gizmo_local_normal, outer_circle_radius};
 use crate::subgizmo::{SubGizmoConfig, SubGizmoKind};
-use crate::{gizmo::Ray, GizmoDirection, GizmoDrawData, GizmoResult};
+use crate::{
+    config::{ArcStyle, RotationInput},
+    gizmo::Ray,
+    result::{GizmoHandle, GizmoResultKind},
+    GizmoDirection, GizmoMode, GizmoOrientation,
+};
 
 pub(crate) type RotationSubGizmo = SubGizmoConfig<Rotation>;
 
-#[derive(Debug, Copy, Clone, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Hash)]
 pub(crate) struct RotationParams {
     pub direction: GizmoDirection,
 }
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub(crate) struct RotationState {
     start_axis_angle: f64,
     start_rotation_angle: f64,
     last_rotation_angle: f64,
     current_delta: f64,
+    /// Whether linear rotation input is used
+    linear: bool,
+    /// Cursor position where the arc was grabbed
+    start_cursor_pos: Pos2,
+    /// Screen space direction of the arc tangent at the grab point
+    screen_tangent: DVec2,
 }
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub(crate) struct Rotation;
 
 impl SubGizmoKind for Rotation {
@@ -37,7 +47,7 @@ impl SubGizmoKind for Rotation {
         let radius = arc_radius(subgizmo);
         let config = subgizmo.config;
         let origin = config.translation;
-        let normal = gizmo_normal(&subgizmo.config, subgizmo.direction);
+        let normal = arc_normal(subgizmo);
         let tangent = tangent(subgizmo);
 
         let (t, dist_from_gizmo_origin) =
@@ -60,6 +70,10 @@ impl SubGizmoKind for Rotation {
             f64::atan2(offset.cross(forward).dot(normal), offset.dot(forward))
         };
 
+        subgizmo.state.linear = use_linear_input(subgizmo);
+        subgizmo.state.start_cursor_pos = ray.screen_pos;
+        subgizmo.state.screen_tangent = screen_tangent(subgizmo, offset);
+
         let rotation_angle = rotation_angle(subgizmo, ray.screen_pos).unwrap_or(0.0);
         subgizmo.state.start_axis_angle = angle;
         subgizmo.state.start_rotation_angle = rotation_angle;
@@ -74,32 +88,41 @@ impl SubGizmoKind for Rotation {
         }
     }
 
-    fn update(subgizmo: &mut RotationSubGizmo, ray: Ray) -> Option<GizmoResult> {
+    fn update(subgizmo: &mut RotationSubGizmo, ray: Ray) -> Option<GizmoResultKind> {
         let config = subgizmo.config;
 
         let mut rotation_angle = rotation_angle(subgizmo, ray.screen_pos)?;
         if config.snapping {
-            rotation_angle = round_to_interval(
-                rotation_angle - subgizmo.state.start_rotation_angle,
-                config.snap_angle as f64,
-            ) + subgizmo.state.start_rotation_angle;
+            rotation_angle = config
+                .snap_settings
+                .angle
+                .snap(rotation_angle - subgizmo.state.start_rotation_angle)
+                + subgizmo.state.start_rotation_angle;
         }
 
         let mut angle_delta = rotation_angle - subgizmo.state.last_rotation_angle;
 
-        // Always take the smallest angle, e.g. -10° instead of 350°
-        if angle_delta > PI {
-            angle_delta -= TAU;
-        } else if angle_delta < -PI {
-            angle_delta += TAU;
+        // Always take the smallest angle, e.g. -10° instead of 350°.
+        // Linear input is not limited to a single turn, so it does not wrap around.
+        if !subgizmo.state.linear {
+            if angle_delta > PI {
+                angle_delta -= TAU;
+            } else if angle_delta < -PI {
+                angle_delta += TAU;
+            }
         }
 
         subgizmo.state.last_rotation_angle = rotation_angle;
         subgizmo.state.current_delta += angle_delta;
 
-        let normal = gizmo_local_normal(&subgizmo.config, subgizmo.direction);
+        // Gimbal axes differ per target, so the axis of the last target is used for all of them
+        let normal = if config.orientation() == GizmoOrientation::Gimbal {
+            arc_normal(subgizmo)
+        } else {
+            gizmo_local_normal(&subgizmo.config, subgizmo.direction)
+        };
 
-        Some(GizmoResult::Rotation {
+        Some(GizmoResultKind::Rotation {
             axis: normal.into(),
             delta: -angle_delta,
             total: subgizmo.state.current_delta,
@@ -107,28 +130,42 @@ impl SubGizmoKind for Rotation {
         })
     }
 
-    fn draw(subgizmo: &RotationSubGizmo) -> GizmoDrawData {
+    fn handle(subgizmo: &RotationSubGizmo) -> GizmoHandle {
+        GizmoHandle::Axis {
+            mode: GizmoMode::Rotate,
+            direction: subgizmo.direction,
+        }
+    }
+
+    fn draw(subgizmo: &RotationSubGizmo, primitives: &mut Primitives) {
+        if subgizmo.opacity <= 1e-4 {
+            return;
+        }
+
         let config = subgizmo.config;
 
         let transform = rotation_matrix(subgizmo);
-        let shape_builder = ShapeBuidler::new(
-            config.view_projection * transform,
-            config.viewport,
-            config.pixels_per_point,
-        );
+        let mut shape_builder = ShapeBuidler::new(transform, &config, primitives);
 
-        let color = gizmo_color(&subgizmo.config, subgizmo.focused, subgizmo.direction);
-        let stroke = (config.visuals.stroke_width, color);
+        let color = gizmo_color(&subgizmo.config, subgizmo.highlight, subgizmo.direction)
+            .gamma_multiply(subgizmo.opacity);
+        let stroke = (
+            config.visuals.stroke_width * config.visuals.rotation_handle.thickness,
+            color,
+        );
 
         let radius = arc_radius(subgizmo);
 
-        let mut draw_data = GizmoDrawData::default();
-
         if !subgizmo.active {
             let angle = arc_angle(subgizmo);
-            draw_data += shape_builder
-                .arc(radius, FRAC_PI_2 - angle, FRAC_PI_2 + angle, stroke)
-                .into();
+            styled_arc(
+                subgizmo,
+                &mut shape_builder,
+                radius,
+                FRAC_PI_2 - angle,
+                FRAC_PI_2 + angle,
+                stroke,
+            );
         } else {
             let mut start_angle = subgizmo.state.start_axis_angle + FRAC_PI_2;
             let mut end_angle = start_angle + subgizmo.state.current_delta;
@@ -151,70 +188,55 @@ impl SubGizmoKind for Rotation {
             let mut start_angle_2 = end_angle;
             let mut end_angle_2 = start_angle + TAU;
 
-            if config
-                .view_forward()
-                .dot(gizmo_normal(&config, subgizmo.direction))
-                < 0.0
-            {
+            if config.view_forward().dot(arc_normal(subgizmo)) < 0.0 {
                 // Swap start and end angles based on the view direction relative to gizmo normal.
                 // Otherwise the filled sector gets drawn incorrectly.
                 std::mem::swap(&mut start_angle, &mut end_angle);
                 std::mem::swap(&mut start_angle_2, &mut end_angle_2);
             }
 
-            draw_data += shape_builder
-                .polyline(
-                    &[
-                        DVec3::new(start_angle.cos() * radius, 0.0, start_angle.sin() * radius),
-                        DVec3::new(0.0, 0.0, 0.0),
-                        DVec3::new(end_angle.cos() * radius, 0.0, end_angle.sin() * radius),
-                    ],
-                    stroke,
-                )
-                .into();
+            shape_builder.polyline(
+                [
+                    DVec3::new(start_angle.cos() * radius, 0.0, start_angle.sin() * radius),
+                    DVec3::new(0.0, 0.0, 0.0),
+                    DVec3::new(end_angle.cos() * radius, 0.0, end_angle.sin() * radius),
+                ],
+                stroke,
+            );
 
             if full_circles > 0 {
-                draw_data += shape_builder
-                    .sector(
-                        radius,
-                        start_angle_2,
-                        end_angle_2,
-                        color.linear_multiply((0.25 * full_circles as f32).min(1.0)),
-                        (0.0, Color32::TRANSPARENT),
-                    )
-                    .into();
+                shape_builder.sector(
+                    radius,
+                    start_angle_2,
+                    end_angle_2,
+                    color.linear_multiply((0.25 * full_circles as f32).min(1.0)),
+                );
             }
 
-            draw_data += shape_builder
-                .sector(
-                    radius,
-                    start_angle,
-                    end_angle,
-                    color.linear_multiply((0.25 * (full_circles + 1) as f32).min(1.0)),
-                    (0.0, Color32::TRANSPARENT),
-                )
-                .into();
+            shape_builder.sector(
+                radius,
+                start_angle,
+                end_angle,
+                color.linear_multiply((0.25 * (full_circles + 1) as f32).min(1.0)),
+            );
 
-            draw_data += shape_builder.circle(radius, stroke).into();
+            styled_arc(subgizmo, &mut shape_builder, radius, 0.0, TAU, stroke);
 
             // Draw snapping ticks
             if config.snapping {
                 let stroke_width = stroke.0 / 2.0;
-                for i in 0..((TAU / config.snap_angle as f64) as usize + 1) {
-                    let angle = i as f64 * config.snap_angle as f64 + end_angle;
+                let start_angle = subgizmo.state.start_axis_angle + FRAC_PI_2;
+                for tick in config.snap_settings.angle.ticks() {
+                    let angle = start_angle + tick;
                     let pos = DVec3::new(angle.cos(), 0.0, angle.sin());
-                    draw_data += shape_builder
-                        .line_segment(
-                            pos * radius * 1.1,
-                            pos * radius * 1.2,
-                            (stroke_width, stroke.1),
-                        )
-                        .into();
+                    shape_builder.line_segment(
+                        pos * radius * 1.1,
+                        pos * radius * 1.2,
+                        (stroke_width, stroke.1),
+                    );
                 }
             }
         }
-
-        draw_data
     }
 }
 
@@ -222,7 +244,7 @@ impl SubGizmoKind for Rotation {
 /// The arc is a semicircle, which turns into a full circle when viewed
 /// directly from the front.
 fn arc_angle(subgizmo: &SubGizmoConfig<Rotation>) -> f64 {
-    let dot = gizmo_normal(&subgizmo.config, subgizmo.direction)
+    let dot = arc_normal(subgizmo)
         .dot(subgizmo.config.view_forward())
         .abs();
     let min_dot = 0.990;
@@ -254,12 +276,10 @@ fn rotation_matrix(subgizmo: &SubGizmoConfig<Rotation>) -> DMat4 {
     let mut rotation = DQuat::from_mat3(&rotation);
     let config = subgizmo.config;
 
-    if config.local_space() {
-        rotation = config.rotation * rotation;
-    }
+    rotation = arc_rotation(subgizmo) * rotation;
 
     let tangent = tangent(subgizmo);
-    let normal = gizmo_normal(&subgizmo.config, subgizmo.direction);
+    let normal = arc_normal(subgizmo);
     let mut forward = config.view_forward();
     if config.left_handed {
         forward *= -1.0;
@@ -273,6 +293,10 @@ fn rotation_matrix(subgizmo: &SubGizmoConfig<Rotation>) -> DMat4 {
 }
 
 fn rotation_angle(subgizmo: &SubGizmoConfig<Rotation>, cursor_pos: Pos2) -> Option<f64> {
+    if subgizmo.state.linear {
+        return Some(linear_rotation_angle(subgizmo, cursor_pos));
+    }
+
     let viewport = subgizmo.config.viewport;
     let gizmo_pos = world_to_screen(viewport, subgizmo.config.mvp, DVec3::new(0.0, 0.0, 0.0))?;
     let delta = DVec2::new(
@@ -286,18 +310,81 @@ fn rotation_angle(subgizmo: &SubGizmoConfig<Rotation>, cursor_pos: Pos2) -> Opti
     }
 
     let mut angle = f64::atan2(delta.y, delta.x);
-    if subgizmo
-        .config
-        .view_forward()
-        .dot(gizmo_normal(&subgizmo.config, subgizmo.direction))
-        < 0.0
-    {
+    if subgizmo.config.view_forward().dot(arc_normal(subgizmo)) < 0.0 {
         angle *= -1.0;
     }
 
     Some(angle)
 }
 
+/// Calculates rotation angle from the cursor movement along the arc tangent.
+/// Moving the cursor by the arc's radius in pixels corresponds to one radian.
+fn linear_rotation_angle(subgizmo: &SubGizmoConfig<Rotation>, cursor_pos: Pos2) -> f64 {
+    let start_pos = subgizmo.state.start_cursor_pos;
+    let delta = DVec2::new(
+        cursor_pos.x as f64 - start_pos.x as f64,
+        cursor_pos.y as f64 - start_pos.y as f64,
+    );
+
+    let radius_in_pixels = arc_radius(subgizmo) / subgizmo.config.pixel_size as f64;
+
+    // Rotation angles are measured in the opposite direction to the rotation itself,
+    // see the circular rotation angle.
+    -delta.dot(subgizmo.state.screen_tangent) / radius_in_pixels
+}
+
+/// Whether linear rotation input should be used, based on configuration and
+/// the angle between the view direction and the rotation plane.
+fn use_linear_input(subgizmo: &SubGizmoConfig<Rotation>) -> bool {
+    match subgizmo.config.rotation_input {
+        RotationInput::Circular => false,
+        RotationInput::Linear => true,
+        RotationInput::Auto { threshold } => {
+            let dot = arc_normal(subgizmo)
+                .dot(subgizmo.config.eye_to_model_dir)
+                .abs();
+
+            subgizmo.direction != GizmoDirection::View && dot.asin() < threshold as f64
+        }
+    }
+}
+
+/// Calculates the screen space direction of the arc tangent at the given
+/// arc point, pointing in the direction of positive rotation.
+fn screen_tangent(subgizmo: &SubGizmoConfig<Rotation>, offset: DVec3) -> DVec2 {
+    let config = subgizmo.config;
+    let normal = arc_normal(subgizmo);
+    let radius = arc_radius(subgizmo);
+
+    let arc_point = config.translation + offset * radius;
+    let tangent_point = arc_point + normal.cross(offset) * radius * 0.1;
+
+    let screen_tangent = world_to_screen(config.viewport, config.view_projection, arc_point)
+        .zip(world_to_screen(
+            config.viewport,
+            config.view_projection,
+            tangent_point,
+        ))
+        .map(|(a, b)| DVec2::new((b.x - a.x) as f64, (b.y - a.y) as f64))
+        .unwrap_or_default();
+
+    if screen_tangent.length() > 1e-3 {
+        return screen_tangent.normalize();
+    }
+
+    // The tangent points directly towards the camera. Fall back to the screen
+    // space direction perpendicular to the rotation axis.
+    world_to_screen(config.viewport, config.view_projection, config.translation)
+        .zip(world_to_screen(
+            config.viewport,
+            config.view_projection,
+            config.translation + normal * radius,
+        ))
+        .map(|(a, b)| DVec2::new((a.y - b.y) as f64, (b.x - a.x) as f64))
+        .unwrap_or_default()
+        .normalize_or_zero()
+}
+
 fn tangent(subgizmo: &SubGizmoConfig<Rotation>) -> DVec3 {
     let mut tangent = match subgizmo.direction {
         GizmoDirection::X | GizmoDirection::Y => DVec3::Z,
@@ -305,17 +392,166 @@ fn tangent(subgizmo: &SubGizmoConfig<Rotation>) -> DVec3 {
         GizmoDirection::View => -subgizmo.config.view_right(),
     };
 
-    if subgizmo.config.local_space() && subgizmo.direction != GizmoDirection::View {
-        tangent = subgizmo.config.rotation * tangent;
+    if subgizmo.direction != GizmoDirection::View {
+        tangent = arc_rotation(subgizmo) * tangent;
     }
 
     tangent
 }
 
+/// Rotation of the arc from its global orientation
+fn arc_rotation(subgizmo: &SubGizmoConfig<Rotation>) -> DQuat {
+    let config = subgizmo.config;
+    let axis = match subgizmo.direction {
+        GizmoDirection::X => 0,
+        GizmoDirection::Y => 1,
+        GizmoDirection::Z => 2,
+        GizmoDirection::View => return DQuat::IDENTITY,
+    };
+
+    match config.orientation() {
+        GizmoOrientation::Global => DQuat::IDENTITY,
+        GizmoOrientation::Local => config.rotation,
+        GizmoOrientation::Gimbal => config.euler_order.gimbal_rotation(config.rotation, axis),
+    }
+}
+
+/// World space rotation axis of the arc
+fn arc_normal(subgizmo: &SubGizmoConfig<Rotation>) -> DVec3 {
+    arc_rotation(subgizmo) * gizmo_local_normal(&subgizmo.config, subgizmo.direction)
+}
+
+/// Draws an arc using the configured [`ArcStyle`]
+fn styled_arc(
+    subgizmo: &SubGizmoConfig<Rotation>,
+    shape_builder: &mut ShapeBuidler,
+    radius: f64,
+    start_angle: f64,
+    end_angle: f64,
+    stroke: (f32, Color32),
+) {
+    let width = (stroke.0 * subgizmo.config.scale_factor) as f64;
+
+    match subgizmo.config.visuals.rotation_handle.arc {
+        ArcStyle::Solid => {
+            shape_builder.arc(radius, start_angle, end_angle, stroke);
+        }
+        ArcStyle::Dashed => {
+            // Dashes and gaps are roughly three stroke widths long
+            let dashes = ((end_angle - start_angle) * radius / (width * 6.0)).clamp(1.0, 256.0);
+            let step = (end_angle - start_angle) / dashes.round();
+            let mut angle = start_angle;
+            while angle + step * 0.25 < end_angle {
+                shape_builder.arc(radius, angle, angle + step * 0.5, stroke);
+                angle += step;
+            }
+        }
+        ArcStyle::Double => {
+            let offset = width * 0.75;
+            for radius in [radius - offset, radius + offset] {
+                shape_builder.arc(radius, start_angle, end_angle, (stroke.0 * 0.5, stroke.1));
+            }
+        }
+    }
+}
+
 fn arc_radius(subgizmo: &SubGizmoConfig<Rotation>) -> f64 {
+    let config = &subgizmo.config;
+    let radius = config.visuals.rotation_handle.radius;
+
     if subgizmo.direction == GizmoDirection::View {
-        outer_circle_radius(&subgizmo.config)
+        outer_circle_radius(config)
+            + (config.scale_factor * config.visuals.gizmo_size * (radius - 1.0)) as f64
     } else {
-        (subgizmo.config.scale_factor * subgizmo.config.visuals.gizmo_size) as f64
+        (config.scale_factor * config.visuals.gizmo_size * radius) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{GizmoConfig, GizmoVisuals, PreparedGizmoConfig, RotationHandleStyle};
+    use crate::gizmo::GizmoPrimitive;
+    use crate::math::Transform;
+    use crate::subgizmo::SubGizmoControl;
+    use crate::test_utils::test_config;
+
+    /// Radius, angle range and width of the arcs drawn by the X rotation handle
+    fn draw_arcs(arc: ArcStyle) -> Vec<(f64, f64, f64, f32)> {
+        let mut config = PreparedGizmoConfig::default();
+        config.update_for_config(GizmoConfig {
+            visuals: GizmoVisuals {
+                rotation_handle: RotationHandleStyle {
+                    arc,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..test_config(GizmoMode::Rotate)
+        });
+        config.update_for_targets(&[Transform::default()]);
+
+        let mut subgizmo = RotationSubGizmo::new(
+            config,
+            RotationParams {
+                direction: GizmoDirection::X,
+            },
+        );
+        subgizmo.opacity = 1.0;
+
+        let mut primitives = Primitives::default();
+        subgizmo.draw(&mut primitives);
+        primitives
+            .primitives
+            .iter()
+            .map(|primitive| match primitive {
+                GizmoPrimitive::Arc {
+                    x_axis,
+                    start_angle,
+                    end_angle,
+                    width,
+                    ..
+                } => (
+                    DVec3::from(*x_axis).length(),
+                    *start_angle,
+                    *end_angle,
+                    *width,
+                ),
+                _ => panic!("unexpected primitive {primitive:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn arc_styles() {
+        let [(radius, start, end, width)] = draw_arcs(ArcStyle::Solid)[..] else {
+            panic!("expected a single arc");
+        };
+        assert!(end > start);
+
+        // Dashes with gaps between them, along the solid arc
+        let dashes = draw_arcs(ArcStyle::Dashed);
+        assert!(dashes.len() > 4);
+        assert_eq!(dashes[0].1, start);
+        for &(dash_radius, dash_start, dash_end, dash_width) in &dashes {
+            assert_eq!((dash_radius, dash_width), (radius, width));
+            assert!(dash_start < dash_end && dash_end < end);
+        }
+        for pair in dashes.windows(2) {
+            assert!(pair[1].1 > pair[0].2);
+        }
+
+        // Thinner arcs on both sides of the solid arc
+        let [(inner, inner_start, inner_end, inner_width), (outer, outer_start, outer_end, outer_width)] =
+            draw_arcs(ArcStyle::Double)[..]
+        else {
+            panic!("expected two arcs");
+        };
+        assert!(inner < radius && outer > radius);
+        assert!(((inner + outer) * 0.5 - radius).abs() < 1e-9);
+        assert_eq!((inner_start, inner_end), (start, end));
+        assert_eq!((outer_start, outer_end), (start, end));
+        assert_eq!(inner_width, width * 0.5);
+        assert_eq!(outer_width, width * 0.5);
     }
 }
@@ -1,28 +1,40 @@
-use glam::DVec3;
+use glam::{DVec2, DVec3};
 
 use crate::math::{round_to_interval, world_to_screen, Pos2};
 
+use crate::result::{GizmoHandle, GizmoResultKind};
+use crate::shape::Primitives;
 use crate::subgizmo::common::{
-    draw_arrow, draw_circle, draw_plane, gizmo_color, gizmo_local_normal, inner_circle_radius,
-    outer_circle_radius, pick_arrow, pick_circle, pick_plane, plane_bitangent, plane_tangent,
+    draw_arrow, draw_circle, draw_guide_line, draw_plane, draw_snap_ticks, draw_start_ghost,
+    gizmo_color, gizmo_local_normal, gizmo_normal, inner_circle_radius, outer_circle_radius,
+    overlay_color, overlay_extent, pick_arrow, pick_circle, pick_plane, plane_axes,
+    plane_bitangent, plane_tangent,
 };
 use crate::subgizmo::{common::TransformKind, SubGizmoConfig, SubGizmoKind};
-use crate::{gizmo::Ray, GizmoDirection, GizmoDrawData, GizmoMode, GizmoResult};
+use crate::{gizmo::Ray, GizmoDirection, GizmoMode};
 
 pub(crate) type ScaleSubGizmo = SubGizmoConfig<Scale>;
 
-#[derive(Debug, Copy, Clone, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Hash)]
 pub(crate) struct ScaleParams {
     pub direction: GizmoDirection,
     pub transform_kind: TransformKind,
 }
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub(crate) struct ScaleState {
     start_delta: f64,
+    /// Screen space direction from the gizmo origin to the cursor, when the subgizmo was grabbed
+    start_direction: DVec2,
+    /// Point where the subgizmo was grabbed
+    start_point: DVec3,
+    /// Current scale along the subgizmo direction
+    current_scale: f64,
+    /// Scale given by the previous update
+    last_scale: DVec3,
 }
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub(crate) struct Scale;
 
 impl SubGizmoKind for Scale {
@@ -54,11 +66,15 @@ impl SubGizmoKind for Scale {
             }
         };
 
-        let start_delta = distance_from_origin_2d(subgizmo, ray.screen_pos)?;
+        let start_offset = offset_from_origin_2d(subgizmo, ray.screen_pos)?;
 
-        subgizmo.opacity = pick_result.visibility as _;
+        subgizmo.visibility = pick_result.visibility as _;
 
-        subgizmo.state.start_delta = start_delta;
+        subgizmo.state.start_delta = start_offset.length();
+        subgizmo.state.start_direction = start_offset.normalize_or_zero();
+        subgizmo.state.start_point = pick_result.subgizmo_point;
+        subgizmo.state.current_scale = 1.0;
+        subgizmo.state.last_scale = DVec3::ONE;
 
         if pick_result.picked {
             Some(pick_result.t)
@@ -67,15 +83,16 @@ impl SubGizmoKind for Scale {
         }
     }
 
-    fn update(subgizmo: &mut ScaleSubGizmo, ray: Ray) -> Option<GizmoResult> {
-        let mut delta = distance_from_origin_2d(subgizmo, ray.screen_pos)?;
+    fn update(subgizmo: &mut ScaleSubGizmo, ray: Ray) -> Option<GizmoResultKind> {
+        let offset = offset_from_origin_2d(subgizmo, ray.screen_pos)?;
+        let mut delta = if subgizmo.config.negative_scaling {
+            // Dragging past the origin gives a negative distance, mirroring the targets
+            offset.dot(subgizmo.state.start_direction)
+        } else {
+            offset.length()
+        };
         delta /= subgizmo.state.start_delta;
 
-        if subgizmo.config.snapping {
-            delta = round_to_interval(delta, subgizmo.config.snap_scale as f64);
-        }
-        delta = delta.max(1e-4) - 1.0;
-
         let direction = match (subgizmo.transform_kind, subgizmo.direction) {
             (TransformKind::Axis, _) => gizmo_local_normal(&subgizmo.config, subgizmo.direction),
             (TransformKind::Plane, GizmoDirection::View) => DVec3::ONE,
@@ -84,51 +101,146 @@ impl SubGizmoKind for Scale {
             .normalize(),
         };
 
+        if subgizmo.config.snapping {
+            let snap_scale = subgizmo.config.snap_settings.scale_along(direction);
+            delta = round_to_interval(delta, snap_scale);
+        }
+        delta = if delta < 0.0 {
+            delta.min(-1e-4)
+        } else {
+            delta.max(1e-4)
+        };
+        subgizmo.state.current_scale = delta;
+        delta -= 1.0;
+
         let scale = DVec3::ONE + (direction * delta);
+        let last_scale = subgizmo.state.last_scale;
+        subgizmo.state.last_scale = scale;
 
-        Some(GizmoResult::Scale {
+        Some(GizmoResultKind::Scale {
+            // An axis scaled to zero can not be scaled relative to its previous scale
+            delta: DVec3::select(last_scale.cmpeq(DVec3::ZERO), scale, scale / last_scale).into(),
             total: scale.into(),
         })
     }
 
-    fn draw(subgizmo: &ScaleSubGizmo) -> GizmoDrawData {
+    fn handle(subgizmo: &ScaleSubGizmo) -> GizmoHandle {
+        subgizmo
+            .transform_kind
+            .handle(GizmoMode::Scale, subgizmo.direction)
+    }
+
+    fn draw(subgizmo: &ScaleSubGizmo, primitives: &mut Primitives) {
         match (subgizmo.transform_kind, subgizmo.direction) {
             (TransformKind::Axis, _) => draw_arrow(
                 &subgizmo.config,
                 subgizmo.opacity,
-                subgizmo.focused,
+                subgizmo.highlight,
                 subgizmo.direction,
                 GizmoMode::Scale,
+                primitives,
             ),
             (TransformKind::Plane, GizmoDirection::View) => {
+                let color = gizmo_color(&subgizmo.config, subgizmo.highlight, subgizmo.direction)
+                    .gamma_multiply(subgizmo.opacity);
+
                 draw_circle(
                     &subgizmo.config,
-                    gizmo_color(&subgizmo.config, subgizmo.focused, subgizmo.direction),
+                    color,
                     inner_circle_radius(&subgizmo.config),
                     false,
-                ) + draw_circle(
+                    primitives,
+                );
+                draw_circle(
                     &subgizmo.config,
-                    gizmo_color(&subgizmo.config, subgizmo.focused, subgizmo.direction),
+                    color,
                     outer_circle_radius(&subgizmo.config),
                     false,
-                )
+                    primitives,
+                );
             }
             (TransformKind::Plane, _) => draw_plane(
                 &subgizmo.config,
                 subgizmo.opacity,
-                subgizmo.focused,
+                subgizmo.highlight,
                 subgizmo.direction,
+                primitives,
             ),
         }
+
+        if subgizmo.active {
+            draw_overlays(subgizmo, primitives);
+        }
+    }
+}
+
+/// Draws the drag overlays of an active scale subgizmo
+fn draw_overlays(subgizmo: &ScaleSubGizmo, primitives: &mut Primitives) {
+    let config = &subgizmo.config;
+    let overlays = config.visuals.drag_overlays;
+    // Axis constraints are always shown with guides
+    let guides = overlays.guides || config.constraint_orientation.is_some();
+    let color = overlay_color(config, subgizmo.direction);
+    let origin = config.translation;
+
+    match (subgizmo.transform_kind, subgizmo.direction) {
+        (TransformKind::Axis, _) => {
+            let direction = gizmo_normal(config, subgizmo.direction);
+            if guides {
+                draw_guide_line(config, color, origin, direction, primitives);
+            }
+            if overlays.snap_ticks && config.snapping {
+                // Ticks are placed where the grabbed point would be at each scale increment
+                let start_distance = (subgizmo.state.start_point - origin).dot(direction);
+                let direction = direction * start_distance.signum();
+                let start_distance = start_distance.abs();
+                let current = start_distance * subgizmo.state.current_scale;
+                let extent = overlay_extent(config);
+                // Ticks continue past the origin when the targets can be mirrored
+                let min = if config.negative_scaling {
+                    (current - extent).min(0.0)
+                } else {
+                    0.0
+                };
+                draw_snap_ticks(
+                    config,
+                    color,
+                    origin,
+                    direction,
+                    start_distance
+                        * config
+                            .snap_settings
+                            .scale_along(gizmo_local_normal(config, subgizmo.direction)),
+                    (min, current.max(0.0) + extent),
+                    primitives,
+                );
+            }
+        }
+        (TransformKind::Plane, GizmoDirection::View) => {}
+        (TransformKind::Plane, _) => {
+            if guides {
+                let (a, b) = plane_axes(config, subgizmo.direction);
+                draw_guide_line(config, color, origin, a, primitives);
+                draw_guide_line(config, color, origin, b, primitives);
+            }
+        }
+    }
+
+    if overlays.start_ghost {
+        draw_start_ghost(config, color, subgizmo.state.start_point, primitives);
     }
 }
 
-fn distance_from_origin_2d<T: SubGizmoKind>(
+/// Screen space offset of the cursor from the gizmo origin
+fn offset_from_origin_2d<T: SubGizmoKind>(
     subgizmo: &SubGizmoConfig<T>,
     cursor_pos: Pos2,
-) -> Option<f64> {
+) -> Option<DVec2> {
     let viewport = subgizmo.config.viewport;
     let gizmo_pos = world_to_screen(viewport, subgizmo.config.mvp, DVec3::new(0.0, 0.0, 0.0))?;
 
-    Some(cursor_pos.distance(gizmo_pos) as f64)
+    Some(DVec2::new(
+        (cursor_pos.x - gizmo_pos.x) as f64,
+        (cursor_pos.y - gizmo_pos.y) as f64,
+    ))
 }
@@ -1,28 +1,43 @@
-use crate::math::{intersect_plane, ray_to_ray, round_to_interval, DVec3};
+use crate::config::SurfacePlacement;
+use crate::math::{intersect_plane, ray_to_ray, round_to_interval, DQuat, DVec3};
 
+use crate::result::{GizmoHandle, GizmoResultKind};
+use crate::shape::Primitives;
 use crate::subgizmo::common::{
-    draw_arrow, draw_circle, draw_plane, gizmo_color, gizmo_normal, inner_circle_radius,
-    pick_arrow, pick_circle, pick_plane, plane_bitangent, plane_global_origin, plane_tangent,
+    draw_arrow, draw_circle, draw_guide_grid, draw_guide_line, draw_plane, draw_snap_ticks,
+    draw_start_ghost, gizmo_color, gizmo_local_normal, gizmo_normal, inner_circle_radius,
+    overlay_color, overlay_extent, pick_arrow, pick_circle, pick_plane, plane_axes,
+    plane_bitangent, plane_tangent,
 };
 use crate::subgizmo::{common::TransformKind, SubGizmoConfig, SubGizmoKind};
-use crate::{gizmo::Ray, GizmoDirection, GizmoDrawData, GizmoMode, GizmoOrientation, GizmoResult};
+use crate::{gizmo::Ray, GizmoDirection, GizmoMode};
 
 pub(crate) type TranslationSubGizmo = SubGizmoConfig<Translation>;
 
-#[derive(Debug, Copy, Clone, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Hash)]
 pub(crate) struct TranslationParams {
     pub direction: GizmoDirection,
     pub transform_kind: TransformKind,
 }
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub(crate) struct TranslationState {
+    /// Gizmo position when the drag started
+    start_translation: DVec3,
+    /// Gizmo rotation when the drag started
+    start_rotation: DQuat,
     start_point: DVec3,
     last_point: DVec3,
     current_delta: DVec3,
+    /// Whether the gizmo has been placed on a scene surface during the drag
+    on_surface: bool,
+    /// Rotation of the gizmo on the latest surface it was placed on
+    surface_rotation: DQuat,
+    /// Normal of the latest surface the gizmo was placed on
+    surface_normal: DVec3,
 }
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub(crate) struct Translation;
 
 impl SubGizmoKind for Translation {
@@ -46,11 +61,14 @@ impl SubGizmoKind for Translation {
             ),
         };
 
-        subgizmo.opacity = pick_result.visibility as _;
+        subgizmo.visibility = pick_result.visibility as _;
 
+        subgizmo.state.start_translation = subgizmo.config.translation;
+        subgizmo.state.start_rotation = subgizmo.config.rotation;
         subgizmo.state.start_point = pick_result.subgizmo_point;
         subgizmo.state.last_point = pick_result.subgizmo_point;
         subgizmo.state.current_delta = DVec3::ZERO;
+        subgizmo.state.on_surface = false;
 
         if pick_result.picked {
             Some(pick_result.t)
@@ -59,15 +77,36 @@ impl SubGizmoKind for Translation {
         }
     }
 
-    fn update(subgizmo: &mut TranslationSubGizmo, ray: Ray) -> Option<GizmoResult> {
+    fn resume(subgizmo: &mut TranslationSubGizmo, ray: Ray) {
+        Self::pick(subgizmo, ray);
+
+        // Picking clamps the point to the handle, but the drag continues from the cursor
+        let point = if subgizmo.transform_kind == TransformKind::Axis {
+            Some(point_on_axis(subgizmo, ray))
+        } else {
+            point_on_drag_plane(subgizmo, ray)
+        };
+
+        if let Some(point) = point {
+            subgizmo.state.start_point = point;
+            subgizmo.state.last_point = point;
+        }
+    }
+
+    fn update(subgizmo: &mut TranslationSubGizmo, ray: Ray) -> Option<GizmoResultKind> {
+        if subgizmo.transform_kind == TransformKind::Plane
+            && subgizmo.direction == GizmoDirection::View
+            && subgizmo.config.surface_placement != SurfacePlacement::Disabled
+            && (ray.surface_hit.is_some() || subgizmo.state.on_surface)
+        {
+            // Once on a surface, the gizmo stays in place while there is no surface under the cursor
+            return Some(update_surface(subgizmo, ray));
+        }
+
         let mut new_point = if subgizmo.transform_kind == TransformKind::Axis {
             point_on_axis(subgizmo, ray)
         } else {
-            point_on_plane(
-                gizmo_normal(&subgizmo.config, subgizmo.direction),
-                plane_global_origin(&subgizmo.config, subgizmo.direction),
-                ray,
-            )?
+            point_on_drag_plane(subgizmo, ray)?
         };
 
         let mut new_delta = new_point - subgizmo.state.start_point;
@@ -84,7 +123,7 @@ impl SubGizmoKind for Translation {
         let mut translation_delta = new_point - subgizmo.state.last_point;
         let mut total_translation = new_point - subgizmo.state.start_point;
 
-        if subgizmo.config.orientation() == GizmoOrientation::Local {
+        if subgizmo.config.local_space() {
             let inverse_rotation = subgizmo.config.rotation.inverse();
             translation_delta = inverse_rotation * translation_delta;
             total_translation = inverse_rotation * total_translation;
@@ -93,47 +132,184 @@ impl SubGizmoKind for Translation {
         subgizmo.state.last_point = new_point;
         subgizmo.state.current_delta = new_delta;
 
-        Some(GizmoResult::Translation {
+        Some(GizmoResultKind::Translation {
             delta: translation_delta.into(),
             total: total_translation.into(),
         })
     }
 
-    fn draw(subgizmo: &TranslationSubGizmo) -> GizmoDrawData {
+    fn handle(subgizmo: &TranslationSubGizmo) -> GizmoHandle {
+        subgizmo
+            .transform_kind
+            .handle(GizmoMode::Translate, subgizmo.direction)
+    }
+
+    fn draw(subgizmo: &TranslationSubGizmo, primitives: &mut Primitives) {
         match (subgizmo.transform_kind, subgizmo.direction) {
             (TransformKind::Axis, _) => draw_arrow(
                 &subgizmo.config,
                 subgizmo.opacity,
-                subgizmo.focused,
+                subgizmo.highlight,
                 subgizmo.direction,
                 GizmoMode::Translate,
+                primitives,
             ),
             (TransformKind::Plane, GizmoDirection::View) => draw_circle(
                 &subgizmo.config,
-                gizmo_color(&subgizmo.config, subgizmo.focused, subgizmo.direction),
+                gizmo_color(&subgizmo.config, subgizmo.highlight, subgizmo.direction)
+                    .gamma_multiply(subgizmo.opacity),
                 inner_circle_radius(&subgizmo.config),
                 false,
+                primitives,
             ),
             (TransformKind::Plane, _) => draw_plane(
                 &subgizmo.config,
                 subgizmo.opacity,
-                subgizmo.focused,
+                subgizmo.highlight,
                 subgizmo.direction,
+                primitives,
             ),
         }
+
+        if subgizmo.active {
+            draw_overlays(subgizmo, primitives);
+        }
     }
 }
 
-/// Finds the nearest point on line that points in translation subgizmo direction
+/// Places the gizmo on the scene surface hit by the ray.
+///
+/// If the ray does not hit any surface, the gizmo stays where it was last placed.
+fn update_surface(subgizmo: &mut TranslationSubGizmo, ray: Ray) -> GizmoResultKind {
+    let Some(hit) = ray.surface_hit else {
+        return GizmoResultKind::Surface {
+            delta: DVec3::ZERO.into(),
+            total: subgizmo.state.current_delta.into(),
+            rotation: subgizmo.state.surface_rotation.into(),
+            normal: subgizmo.state.surface_normal.into(),
+        };
+    };
+    let point = DVec3::from(hit.point);
+    let normal = DVec3::from(hit.normal).normalize_or_zero();
+
+    let rotation = if subgizmo.config.surface_placement == SurfacePlacement::AlignToNormal
+        && normal != DVec3::ZERO
+    {
+        let start_up = subgizmo.state.start_rotation * DVec3::Y;
+        DQuat::from_rotation_arc(start_up, normal)
+    } else {
+        DQuat::IDENTITY
+    };
+
+    let delta = point - subgizmo.config.translation;
+    let total = point - subgizmo.state.start_translation;
+
+    subgizmo.state.current_delta = total;
+    subgizmo.state.on_surface = true;
+    subgizmo.state.surface_rotation = rotation;
+    subgizmo.state.surface_normal = normal;
+
+    GizmoResultKind::Surface {
+        delta: delta.into(),
+        total: total.into(),
+        rotation: rotation.into(),
+        normal: normal.into(),
+    }
+}
+
+/// Draws the drag overlays of an active translation subgizmo
+fn draw_overlays(subgizmo: &TranslationSubGizmo, primitives: &mut Primitives) {
+    let config = &subgizmo.config;
+    let overlays = config.visuals.drag_overlays;
+    // Axis constraints are always shown with guides
+    let guides = overlays.guides || config.constraint_orientation.is_some();
+    let color = overlay_color(config, subgizmo.direction);
+    let start = subgizmo.state.start_translation;
+
+    match (subgizmo.transform_kind, subgizmo.direction) {
+        (TransformKind::Axis, _) => {
+            let direction = gizmo_normal(config, subgizmo.direction);
+            if guides {
+                draw_guide_line(config, color, start, direction, primitives);
+            }
+            if overlays.snap_ticks && config.snapping {
+                let current = (config.translation - start).dot(direction);
+                let extent = overlay_extent(config);
+                draw_snap_ticks(
+                    config,
+                    color,
+                    start,
+                    direction,
+                    config.snap_distance_along(gizmo_local_normal(config, subgizmo.direction)),
+                    (current - extent, current + extent),
+                    primitives,
+                );
+            }
+        }
+        (TransformKind::Plane, GizmoDirection::View) => {}
+        (TransformKind::Plane, _) => {
+            if guides {
+                let spacing = if config.snapping {
+                    (
+                        config.snap_distance_along(plane_bitangent(subgizmo.direction)),
+                        config.snap_distance_along(plane_tangent(subgizmo.direction)),
+                    )
+                } else {
+                    (0.0, 0.0)
+                };
+                draw_guide_grid(
+                    config,
+                    color,
+                    start,
+                    subgizmo.direction,
+                    spacing,
+                    primitives,
+                );
+            }
+        }
+    }
+
+    if overlays.start_ghost {
+        draw_start_ghost(config, color, start, primitives);
+    }
+}
+
+/// Direction of the subgizmo, in the orientation the gizmo had when the drag started
+fn start_normal(subgizmo: &SubGizmoConfig<Translation>) -> DVec3 {
+    let normal = gizmo_local_normal(&subgizmo.config, subgizmo.direction);
+
+    if subgizmo.config.local_space() && subgizmo.direction != GizmoDirection::View {
+        subgizmo.state.start_rotation * normal
+    } else {
+        normal
+    }
+}
+
+/// Finds the nearest point on line that points in translation subgizmo direction.
+///
+/// The line goes through the position of the gizmo at the start of the drag, so that it
+/// stays in place when the gizmo is moved off the line, for example by snapping to a point.
 fn point_on_axis(subgizmo: &SubGizmoConfig<Translation>, ray: Ray) -> DVec3 {
-    let origin = subgizmo.config.translation;
-    let direction = gizmo_normal(&subgizmo.config, subgizmo.direction);
+    let origin = subgizmo.state.start_translation;
+    let direction = start_normal(subgizmo);
 
     let (_ray_t, subgizmo_t) = ray_to_ray(ray.origin, ray.direction, origin, direction);
 
     origin + direction * subgizmo_t
 }
 
+/// Finds the point on the plane of the translation subgizmo that the ray hits.
+///
+/// Like the line in [`point_on_axis`], the plane goes through the position of the gizmo
+/// at the start of the drag.
+fn point_on_drag_plane(subgizmo: &SubGizmoConfig<Translation>, ray: Ray) -> Option<DVec3> {
+    point_on_plane(
+        start_normal(subgizmo),
+        subgizmo.state.start_translation,
+        ray,
+    )
+}
+
 fn point_on_plane(plane_normal: DVec3, plane_origin: DVec3, ray: Ray) -> Option<DVec3> {
     let mut t = 0.0;
     if !intersect_plane(
@@ -152,32 +328,31 @@ fn point_on_plane(plane_normal: DVec3, plane_origin: DVec3, ray: Ray) -> Option<
 fn snap_translation_vector(subgizmo: &SubGizmoConfig<Translation>, new_delta: DVec3) -> DVec3 {
     let delta_length = new_delta.length();
     if delta_length > 1e-5 {
-        new_delta / delta_length
-            * round_to_interval(delta_length, subgizmo.config.snap_distance as f64)
+        let snap_distance = subgizmo
+            .config
+            .snap_distance_along(gizmo_local_normal(&subgizmo.config, subgizmo.direction));
+        new_delta / delta_length * round_to_interval(delta_length, snap_distance)
     } else {
         new_delta
     }
 }
 
 fn snap_translation_plane(subgizmo: &SubGizmoConfig<Translation>, new_delta: DVec3) -> DVec3 {
-    let mut bitangent = plane_bitangent(subgizmo.direction);
-    let mut tangent = plane_tangent(subgizmo.direction);
-    if subgizmo.config.local_space() {
-        bitangent = subgizmo.config.rotation * bitangent;
-        tangent = subgizmo.config.rotation * tangent;
-    }
-    let cb = new_delta.cross(-bitangent);
-    let ct = new_delta.cross(tangent);
-    let lb = cb.length();
-    let lt = ct.length();
-    let n = gizmo_normal(&subgizmo.config, subgizmo.direction);
-
-    if lb > 1e-5 && lt > 1e-5 {
-        bitangent * round_to_interval(lt, subgizmo.config.snap_distance as f64) * (ct / lt).dot(n)
-            + tangent
-                * round_to_interval(lb, subgizmo.config.snap_distance as f64)
-                * (cb / lb).dot(n)
-    } else {
-        new_delta
+    if subgizmo.direction == GizmoDirection::View {
+        return new_delta;
     }
+
+    let (bitangent, tangent) = plane_axes(&subgizmo.config, subgizmo.direction);
+    let config = &subgizmo.config;
+
+    bitangent
+        * round_to_interval(
+            new_delta.dot(bitangent),
+            config.snap_distance_along(plane_bitangent(subgizmo.direction)),
+        )
+        + tangent
+            * round_to_interval(
+                new_delta.dot(tangent),
+                config.snap_distance_along(plane_tangent(subgizmo.direction)),
+            )
 }
@@ -0,0 +1,381 @@
+//! Lightweight tessellator for screen space primitives, used when the `epaint` feature is disabled.
+//!
+//! Produces anti-aliased triangles, by surrounding each shape with a thin
+//! feathered edge that fades to transparent.
+
+use crate::color::Rgba;
+use crate::gizmo::{GizmoDrawData, GizmoPrimitive};
+use crate::math::{Pos2, Vec2};
+
+/// Corners sharper than this have their miter length limited.
+const MIN_MITER_DOT: f32 = 0.25;
+
+/// Buffers reused between tessellations.
+#[derive(Default, Debug)]
+pub(crate) struct TessellationScratch {
+    points: Vec<Pos2>,
+    normals: Vec<Vec2>,
+}
+
+/// Tessellates screen space primitives to triangles, replacing the contents of `draw_data`.
+pub(crate) fn tessellate_into(
+    primitives: &[GizmoPrimitive],
+    pixels_per_point: f32,
+    scratch: &mut TessellationScratch,
+    draw_data: &mut GizmoDrawData,
+) {
+    draw_data.vertices.clear();
+    draw_data.colors.clear();
+    draw_data.indices.clear();
+
+    let mut tessellator = Tessellator {
+        feathering: 1.0 / pixels_per_point.max(f32::EPSILON),
+        draw_data,
+        points: std::mem::take(&mut scratch.points),
+        normals: std::mem::take(&mut scratch.normals),
+    };
+
+    for primitive in primitives {
+        match primitive {
+            GizmoPrimitive::Polyline {
+                points,
+                closed,
+                width,
+                color,
+            } => {
+                tessellator.set_points(points, *closed);
+                tessellator.stroke(*closed, *width, Rgba::from(*color));
+            }
+            GizmoPrimitive::Polygon { points, color } => {
+                tessellator.set_points(points, true);
+                tessellator.fill(Rgba::from(*color));
+            }
+            // Only polylines and polygons are present in screen space
+            GizmoPrimitive::Circle { .. }
+            | GizmoPrimitive::Arc { .. }
+            | GizmoPrimitive::Arrow { .. } => {}
+        }
+    }
+
+    scratch.points = tessellator.points;
+    scratch.normals = tessellator.normals;
+}
+
+struct Tessellator<'a> {
+    /// Width of the anti-aliasing edge, in points
+    feathering: f32,
+    draw_data: &'a mut GizmoDrawData,
+    /// Scratch buffer for the points of the current shape
+    points: Vec<Pos2>,
+    /// Scratch buffer for the vertex normals of the current shape
+    normals: Vec<Vec2>,
+}
+
+impl Tessellator<'_> {
+    /// Stores the points of the next shape, skipping consecutive duplicates.
+    fn set_points(&mut self, points: &[mint::Vector3<f64>], closed: bool) {
+        self.points.clear();
+        for point in points {
+            let point = Pos2::new(point.x as f32, point.y as f32);
+            if self
+                .points
+                .last()
+                .map_or(true, |last| last.distance_sq(point) > 1e-8)
+            {
+                self.points.push(point);
+            }
+        }
+
+        if closed && self.points.len() > 1 {
+            let first = self.points[0];
+            if self
+                .points
+                .last()
+                .is_some_and(|last| last.distance_sq(first) <= 1e-8)
+            {
+                self.points.pop();
+            }
+        }
+    }
+
+    /// Calculates the miter normals of the current points.
+    fn calculate_normals(&mut self, closed: bool) {
+        let points = &self.points;
+        let n = points.len();
+
+        self.normals.clear();
+        for i in 0..n {
+            let prev = if i > 0 {
+                Some(points[i - 1])
+            } else if closed {
+                Some(points[n - 1])
+            } else {
+                None
+            };
+            let next = if i + 1 < n {
+                Some(points[i + 1])
+            } else if closed {
+                Some(points[0])
+            } else {
+                None
+            };
+
+            let edge_normal = |a: Pos2, b: Pos2| (b - a).normalized().rot90();
+
+            let normal = match (prev, next) {
+                (Some(prev), Some(next)) => {
+                    let n0 = edge_normal(prev, points[i]);
+                    let n1 = edge_normal(points[i], next);
+                    let normal = (n0 + n1).normalized();
+                    let dot = normal.dot(n0).max(MIN_MITER_DOT);
+                    normal * (1.0 / dot)
+                }
+                (Some(prev), None) => edge_normal(prev, points[i]),
+                (None, Some(next)) => edge_normal(points[i], next),
+                (None, None) => Vec2::ZERO,
+            };
+
+            self.normals.push(normal);
+        }
+    }
+
+    fn stroke(&mut self, closed: bool, width: f32, color: Rgba) {
+        let n = self.points.len();
+        if n < 2 || width <= 0.0 {
+            return;
+        }
+
+        self.calculate_normals(closed);
+
+        // Lines thinner than the feathering are faded instead
+        let color = if width < self.feathering {
+            color * (width / self.feathering)
+        } else {
+            color
+        };
+        let inner = (width - self.feathering).max(0.0) / 2.0;
+        let outer = inner + self.feathering;
+
+        let color = color.to_array();
+        let transparent = Rgba::TRANSPARENT.to_array();
+
+        let base = self.draw_data.vertices.len() as u32;
+        for (&point, &normal) in self.points.iter().zip(&self.normals) {
+            push_vertex(self.draw_data, point + normal * outer, transparent);
+            push_vertex(self.draw_data, point + normal * inner, color);
+            push_vertex(self.draw_data, point - normal * inner, color);
+            push_vertex(self.draw_data, point - normal * outer, transparent);
+        }
+
+        let segments = if closed { n } else { n - 1 };
+        for i in 0..segments {
+            let a = base + 4 * i as u32;
+            let b = base + 4 * ((i + 1) % n) as u32;
+            for strip in 0..3 {
+                self.quad(a + strip, a + strip + 1, b + strip, b + strip + 1);
+            }
+        }
+    }
+
+    fn fill(&mut self, color: Rgba) {
+        let n = self.points.len();
+        if n < 3 {
+            return;
+        }
+
+        // Make the normals point outwards, regardless of winding
+        let area: f32 = (0..n)
+            .map(|i| {
+                let (a, b) = (self.points[i], self.points[(i + 1) % n]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        let sign = if area > 0.0 { 1.0 } else { -1.0 };
+
+        self.calculate_normals(true);
+
+        let color = color.to_array();
+        let transparent = Rgba::TRANSPARENT.to_array();
+        let offset = self.feathering / 2.0;
+
+        let base = self.draw_data.vertices.len() as u32;
+        for (&point, &normal) in self.points.iter().zip(&self.normals) {
+            let normal = normal * (sign * offset);
+            push_vertex(self.draw_data, point - normal, color);
+            push_vertex(self.draw_data, point + normal, transparent);
+        }
+
+        for i in 1..(n - 1) as u32 {
+            self.triangle(base, base + 2 * i, base + 2 * (i + 1));
+        }
+
+        for i in 0..n {
+            let a = base + 2 * i as u32;
+            let b = base + 2 * ((i + 1) % n) as u32;
+            self.quad(a, a + 1, b, b + 1);
+        }
+    }
+
+    fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.draw_data.indices.extend_from_slice(&[a, b, c]);
+    }
+
+    /// Adds two triangles, covering the quad between two consecutive vertex pairs.
+    fn quad(&mut self, a0: u32, a1: u32, b0: u32, b1: u32) {
+        self.triangle(a0, a1, b0);
+        self.triangle(a1, b1, b0);
+    }
+}
+
+fn push_vertex(draw_data: &mut GizmoDrawData, pos: Pos2, color: [f32; 4]) {
+    draw_data.vertices.push([pos.x, pos.y]);
+    draw_data.colors.push(color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color32;
+
+    fn tessellate(primitives: &[GizmoPrimitive], pixels_per_point: f32) -> GizmoDrawData {
+        let mut draw_data = GizmoDrawData::default();
+        tessellate_into(
+            primitives,
+            pixels_per_point,
+            &mut TessellationScratch::default(),
+            &mut draw_data,
+        );
+        draw_data
+    }
+
+    fn polyline(points: &[[f64; 2]], closed: bool, width: f32) -> GizmoPrimitive {
+        GizmoPrimitive::Polyline {
+            points: points.iter().map(|&[x, y]| [x, y, 0.0].into()).collect(),
+            closed,
+            width,
+            color: Color32::RED,
+        }
+    }
+
+    fn polygon(points: &[[f64; 2]]) -> GizmoPrimitive {
+        GizmoPrimitive::Polygon {
+            points: points.iter().map(|&[x, y]| [x, y, 0.0].into()).collect(),
+            color: Color32::RED,
+        }
+    }
+
+    #[test]
+    fn stroke_counts() {
+        let points = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]];
+
+        // Four vertices per point, and three quads per segment
+        let open = tessellate(&[polyline(&points, false, 2.0)], 1.0);
+        assert_eq!(open.vertices.len(), 12);
+        assert_eq!(open.colors.len(), 12);
+        assert_eq!(open.indices.len(), 2 * 3 * 6);
+
+        let closed = tessellate(&[polyline(&points, true, 2.0)], 1.0);
+        assert_eq!(closed.vertices.len(), 12);
+        assert_eq!(closed.indices.len(), 3 * 3 * 6);
+
+        // Consecutive duplicates, and a closing point equal to the first one, are skipped
+        let duplicates = [
+            [0.0, 0.0],
+            [10.0, 0.0],
+            [10.0, 0.0],
+            [10.0, 10.0],
+            [0.0, 0.0],
+        ];
+        let deduplicated = tessellate(&[polyline(&duplicates, true, 2.0)], 1.0);
+        assert_eq!(deduplicated.vertices, closed.vertices);
+        assert_eq!(deduplicated.indices, closed.indices);
+
+        // Nothing to draw
+        let empty = tessellate(
+            &[
+                polyline(&points[..1], false, 2.0),
+                polyline(&points, false, 0.0),
+            ],
+            1.0,
+        );
+        assert!(empty.vertices.is_empty() && empty.indices.is_empty());
+    }
+
+    #[test]
+    fn stroke_is_feathered() {
+        let red = Rgba::from(Color32::RED).to_array();
+        let transparent = Rgba::TRANSPARENT.to_array();
+
+        // Opaque core of the stroke, with a feathered edge of one pixel
+        let draw_data = tessellate(&[polyline(&[[0.0, 0.0], [10.0, 0.0]], false, 4.0)], 1.0);
+        let offsets: Vec<f32> = draw_data.vertices[..4].iter().map(|v| v[1]).collect();
+        assert_eq!(offsets, [-2.5, -1.5, 1.5, 2.5]);
+        assert_eq!(draw_data.colors[..4], [transparent, red, red, transparent]);
+
+        // The edge is thinner on high density displays
+        let draw_data = tessellate(&[polyline(&[[0.0, 0.0], [10.0, 0.0]], false, 4.0)], 2.0);
+        let offsets: Vec<f32> = draw_data.vertices[..4].iter().map(|v| v[1]).collect();
+        assert_eq!(offsets, [-2.25, -1.75, 1.75, 2.25]);
+
+        // Strokes thinner than the edge are faded instead
+        let draw_data = tessellate(&[polyline(&[[0.0, 0.0], [10.0, 0.0]], false, 0.5)], 1.0);
+        let offsets: Vec<f32> = draw_data.vertices[..4].iter().map(|v| v[1]).collect();
+        assert_eq!(offsets, [-1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(
+            draw_data.colors[1],
+            (Rgba::from(Color32::RED) * 0.5).to_array()
+        );
+    }
+
+    #[test]
+    fn fill_triangulation() {
+        let square = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]];
+        let red = Rgba::from(Color32::RED).to_array();
+        let transparent = Rgba::TRANSPARENT.to_array();
+
+        let reversed: Vec<[f64; 2]> = square.iter().rev().copied().collect();
+        for points in [&square[..], &reversed] {
+            let draw_data = tessellate(&[polygon(points)], 1.0);
+
+            // Inner and outer vertex per point
+            assert_eq!(draw_data.vertices.len(), 8);
+            // Fan of two triangles, and a feathered quad along each edge
+            assert_eq!(draw_data.indices.len(), (2 + 4 * 2) * 3);
+            // The fan only uses the opaque inner vertices
+            assert!(draw_data.indices[..6].iter().all(|index| index % 2 == 0));
+
+            // Inner vertices are inside the polygon and outer ones outside, regardless of winding
+            for (vertex, color) in draw_data.vertices.iter().zip(&draw_data.colors) {
+                let inside = vertex.iter().all(|&c| c > 0.0 && c < 10.0);
+                if *color == red {
+                    assert!(inside, "{vertex:?}");
+                } else {
+                    assert_eq!(*color, transparent);
+                    assert!(!inside, "{vertex:?}");
+                }
+            }
+        }
+
+        // Fewer than three points do not make a polygon
+        assert!(tessellate(&[polygon(&square[..2])], 1.0)
+            .vertices
+            .is_empty());
+    }
+
+    #[test]
+    fn shapes_are_appended() {
+        let line = polyline(&[[0.0, 0.0], [10.0, 0.0]], false, 2.0);
+        let triangle = polygon(&[[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]]);
+        let draw_data = tessellate(&[line, triangle], 1.0);
+
+        assert_eq!(draw_data.vertices.len(), 8 + 6);
+        // The triangle indices start after the vertices of the line
+        let line_indices = 3 * 6;
+        assert!(draw_data.indices[..line_indices]
+            .iter()
+            .all(|&index| index < 8));
+        assert!(draw_data.indices[line_indices..]
+            .iter()
+            .all(|&index| (8..14).contains(&index)));
+    }
+}
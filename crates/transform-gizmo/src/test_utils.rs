@@ -0,0 +1,45 @@
+//! Camera and interaction fixtures shared by the unit tests.
+
+use enumset::EnumSet;
+
+use crate::config::{GizmoConfig, GizmoMode};
+use crate::gizmo::GizmoInteraction;
+use crate::math::{world_to_screen, DMat4, DVec3, Pos2, Rect};
+
+/// Position of the test camera, looking at the origin.
+pub(crate) const EYE: DVec3 = DVec3::new(3.0, 2.0, 5.0);
+
+/// Configuration with a perspective camera looking at the origin from [`EYE`],
+/// drawing to an 800x600 viewport.
+pub(crate) fn test_config(modes: impl Into<EnumSet<GizmoMode>>) -> GizmoConfig {
+    GizmoConfig {
+        view_matrix: DMat4::look_at_rh(EYE, DVec3::ZERO, DVec3::Y).into(),
+        projection_matrix: DMat4::perspective_rh(1.0, 800.0 / 600.0, 0.1, 100.0).into(),
+        viewport: Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(800.0, 600.0)),
+        modes: modes.into(),
+        ..Default::default()
+    }
+}
+
+/// Screen position of a world space point with the camera of the configuration.
+pub(crate) fn screen_pos(config: &GizmoConfig, point: DVec3) -> (f32, f32) {
+    let view_projection = DMat4::from(config.projection_matrix) * DMat4::from(config.view_matrix);
+    let pos = world_to_screen(config.viewport, view_projection, point).unwrap();
+    (pos.x, pos.y)
+}
+
+pub(crate) fn hover(cursor_pos: (f32, f32)) -> GizmoInteraction {
+    GizmoInteraction {
+        cursor_pos,
+        ..Default::default()
+    }
+}
+
+pub(crate) fn drag(cursor_pos: (f32, f32), started: bool) -> GizmoInteraction {
+    GizmoInteraction {
+        cursor_pos,
+        drag_started: started,
+        dragging: true,
+        ..Default::default()
+    }
+}
@@ -3,10 +3,7 @@ use bevy_egui::{
     egui::{self, Layout, Widget},
     EguiContexts, EguiPlugin,
 };
-use transform_gizmo_bevy::{
-    config::{TransformPivotPoint, DEFAULT_SNAP_ANGLE, DEFAULT_SNAP_DISTANCE, DEFAULT_SNAP_SCALE},
-    prelude::*,
-};
+use transform_gizmo_bevy::{config::TransformPivotPoint, prelude::*};
 
 pub struct GuiPlugin;
 
@@ -19,27 +16,9 @@ impl Plugin for GuiPlugin {
 fn update_ui(
     mut contexts: EguiContexts,
     mut gizmo_options: ResMut<GizmoOptions>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
 
     gizmo_targets: Query<&GizmoTarget>,
 ) {
-    // Snapping is enabled when CTRL is pressed.
-    let snapping = keyboard_input.pressed(KeyCode::ControlLeft);
-    // Accurate snapping is enabled when both CTRL and SHIFT are pressed
-    let accurate_snapping = snapping && keyboard_input.pressed(KeyCode::ShiftLeft);
-
-    gizmo_options.snapping = snapping;
-
-    gizmo_options.snap_angle = DEFAULT_SNAP_ANGLE;
-    gizmo_options.snap_distance = DEFAULT_SNAP_DISTANCE;
-    gizmo_options.snap_scale = DEFAULT_SNAP_SCALE;
-
-    if accurate_snapping {
-        gizmo_options.snap_angle /= 2.0;
-        gizmo_options.snap_distance /= 2.0;
-        gizmo_options.snap_scale /= 2.0;
-    }
-
     egui::SidePanel::left("options").show(contexts.ctx_mut(), |ui| {
         draw_options(ui, &mut gizmo_options);
     });
@@ -57,8 +36,8 @@ fn update_ui(
 
 fn draw_gizmo_result(ui: &mut egui::Ui, gizmo_result: Option<GizmoResult>) {
     if let Some(result) = gizmo_result {
-        let text = match result {
-            GizmoResult::Rotation {
+        let text = match result.kind {
+            GizmoResultKind::Rotation {
                 axis,
                 delta: _,
                 total,
@@ -72,16 +51,16 @@ fn draw_gizmo_result(ui: &mut egui::Ui, gizmo_result: Option<GizmoResult>) {
                     total.to_degrees()
                 )
             }
-            GizmoResult::Translation { delta: _, total } => {
+            GizmoResultKind::Translation { delta: _, total } => {
                 format!(
                     "Translation: ({:.2}, {:.2}, {:.2})",
                     total.x, total.y, total.z,
                 )
             }
-            GizmoResult::Scale { total } => {
+            GizmoResultKind::Scale { delta: _, total } => {
                 format!("Scale: ({:.2}, {:.2}, {:.2})", total.x, total.y, total.z,)
             }
-            GizmoResult::Arcball { delta: _, total } => {
+            GizmoResultKind::Arcball { delta: _, total } => {
                 let (axis, angle) = DQuat::from(total).to_axis_angle();
                 format!(
                     "Rotation axis: ({:.2}, {:.2}, {:.2}), Angle: {:.2} deg",
@@ -91,6 +70,21 @@ fn draw_gizmo_result(ui: &mut egui::Ui, gizmo_result: Option<GizmoResult>) {
                     angle.to_degrees()
                 )
             }
+            GizmoResultKind::Surface { total, .. } => {
+                format!(
+                    "Surface placement: ({:.2}, {:.2}, {:.2})",
+                    total.x, total.y, total.z,
+                )
+            }
+            GizmoResultKind::Flip { axis } => {
+                format!("Flip axis: ({:.2}, {:.2}, {:.2})", axis.x, axis.y, axis.z)
+            }
+            GizmoResultKind::Arrangement { axis } => {
+                format!(
+                    "Arrangement axis: ({:.2}, {:.2}, {:.2})",
+                    axis.x, axis.y, axis.z
+                )
+            }
         };
 
         egui::Frame::none()
@@ -124,7 +118,11 @@ fn draw_options(ui: &mut egui::Ui, gizmo_options: &mut GizmoOptions) {
             egui::ComboBox::from_id_source("orientation_cb")
                 .selected_text(format!("{:?}", gizmo_options.gizmo_orientation))
                 .show_ui(ui, |ui| {
-                    for orientation in [GizmoOrientation::Global, GizmoOrientation::Local] {
+                    for orientation in [
+                        GizmoOrientation::Global,
+                        GizmoOrientation::Local,
+                        GizmoOrientation::Gimbal,
+                    ] {
                         ui.selectable_value(
                             &mut gizmo_options.gizmo_orientation,
                             orientation,
@@ -264,7 +262,8 @@ fn draw_options(ui: &mut egui::Ui, gizmo_options: &mut GizmoOptions) {
         egui::Hyperlink::from_label_and_url("(source code)", "https://github.com/urholaukkarinen/transform-gizmo/blob/main/examples/bevy/src/main.rs").ui(ui);
 
         ui.label("Move and rotate the camera using the middle and right mouse buttons");
-        ui.label("Toggle gizmo snapping with left ctrl & shift");
+        ui.label("Hold ctrl to toggle snapping, shift for precision and alt to duplicate");
+        ui.label("Press X, Y or Z while dragging to constrain to an axis, with shift to a plane");
     });
 }
 
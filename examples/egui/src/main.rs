@@ -41,16 +41,12 @@ impl ExampleApp {
         // Fixed camera position
         let view_matrix = DMat4::look_at_lh(DVec3::splat(5.0), DVec3::ZERO, DVec3::Y);
 
-        // Ctrl toggles snapping
-        let snapping = ui.input(|input| input.modifiers.ctrl);
-
         self.gizmo.update_config(GizmoConfig {
             view_matrix: view_matrix.into(),
             projection_matrix: projection_matrix.into(),
-            viewport,
+            viewport: viewport.into(),
             modes: self.gizmo_modes,
             orientation: self.gizmo_orientation,
-            snapping,
             ..Default::default()
         });
 
@@ -68,8 +64,8 @@ impl ExampleApp {
             self.rotation = transform.rotation.into();
             self.translation = transform.translation.into();
 
-            let text = match result {
-                GizmoResult::Rotation {
+            let text = match result.kind {
+                GizmoResultKind::Rotation {
                     axis,
                     delta: _,
                     total,
@@ -83,16 +79,16 @@ impl ExampleApp {
                         total.to_degrees()
                     )
                 }
-                GizmoResult::Translation { delta: _, total } => {
+                GizmoResultKind::Translation { delta: _, total } => {
                     format!(
                         "Translation: ({:.2}, {:.2}, {:.2})",
                         total.x, total.y, total.z,
                     )
                 }
-                GizmoResult::Scale { total } => {
+                GizmoResultKind::Scale { delta: _, total } => {
                     format!("Scale: ({:.2}, {:.2}, {:.2})", total.x, total.y, total.z,)
                 }
-                GizmoResult::Arcball { delta: _, total } => {
+                GizmoResultKind::Arcball { delta: _, total } => {
                     let (axis, angle) = DQuat::from(total).to_axis_angle();
                     format!(
                         "Rotation axis: ({:.2}, {:.2}, {:.2}), Angle: {:.2} deg",
@@ -102,6 +98,21 @@ impl ExampleApp {
                         angle.to_degrees()
                     )
                 }
+                GizmoResultKind::Surface { total, .. } => {
+                    format!(
+                        "Surface placement: ({:.2}, {:.2}, {:.2})",
+                        total.x, total.y, total.z,
+                    )
+                }
+                GizmoResultKind::Flip { axis } => {
+                    format!("Flip axis: ({:.2}, {:.2}, {:.2})", axis.x, axis.y, axis.z)
+                }
+                GizmoResultKind::Arrangement { axis } => {
+                    format!(
+                        "Arrangement axis: ({:.2}, {:.2}, {:.2})",
+                        axis.x, axis.y, axis.z
+                    )
+                }
             };
 
             ui.label(text);
@@ -135,7 +146,11 @@ impl ExampleApp {
                 egui::ComboBox::from_id_source("orientation_cb")
                     .selected_text(format!("{:?}", self.gizmo_orientation))
                     .show_ui(ui, |ui| {
-                        for orientation in [GizmoOrientation::Global, GizmoOrientation::Local] {
+                        for orientation in [
+                            GizmoOrientation::Global,
+                            GizmoOrientation::Local,
+                            GizmoOrientation::Gimbal,
+                        ] {
                             ui.selectable_value(
                                 &mut self.gizmo_orientation,
                                 orientation,